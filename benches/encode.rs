@@ -2,7 +2,9 @@ extern crate criterion;
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use image::ExtendedColorType;
-use image::{codecs::bmp::BmpEncoder, codecs::jpeg::JpegEncoder, ColorType};
+use image::{
+    codecs::bmp::BmpEncoder, codecs::jpeg::JpegEncoder, codecs::webp::WebPEncoder, ColorType,
+};
 
 use std::fs::File;
 use std::io::{BufWriter, Seek, SeekFrom, Write};
@@ -35,6 +37,12 @@ fn encode_all(c: &mut Criterion) {
             sizes: &[64u32, 128, 256],
             colors: &[ColorType::L8, ColorType::Rgb8, ColorType::Rgba8],
         },
+        BenchDef {
+            with: &WebP,
+            name: "webp-lossless",
+            sizes: &[4000u32],
+            colors: &[ColorType::Rgb8, ColorType::Rgba8],
+        },
     ];
 
     for definition in BENCH_DEFS {
@@ -96,6 +104,8 @@ struct Bmp;
 
 struct Jpeg;
 
+struct WebP;
+
 trait EncoderBase {
     fn encode(&self, into: impl Write, im: &[u8], dims: u32, color: ExtendedColorType);
 }
@@ -132,3 +142,10 @@ impl EncoderBase for Jpeg {
         x.encode(im, size, size, color).unwrap();
     }
 }
+
+impl EncoderBase for WebP {
+    fn encode(&self, mut into: impl Write, im: &[u8], size: u32, color: ExtendedColorType) {
+        let x = WebPEncoder::new_lossless(&mut into);
+        x.encode(im, size, size, color).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use image::imageops::{resize, FilterType, ResizePlan};
+use image::{ImageBuffer, Rgba};
+
+pub fn bench_resize_triangle(c: &mut Criterion) {
+    let src = ImageBuffer::from_fn(1999, 1501, |x, y| {
+        Rgba([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8, 255])
+    });
+
+    c.bench_function("resize_triangle_down", |b| {
+        b.iter(|| resize(black_box(&src), 321, 241, FilterType::Triangle))
+    });
+    c.bench_function("resize_triangle_up", |b| {
+        b.iter(|| resize(black_box(&src), 2999, 2251, FilterType::Triangle))
+    });
+}
+
+pub fn bench_resize_plan(c: &mut Criterion) {
+    let images: Vec<_> = (0..64u32)
+        .map(|i| {
+            ImageBuffer::from_fn(64, 48, |x, y| {
+                Rgba([(x * 4) as u8, (y * 5) as u8, (i * 4) as u8, 255])
+            })
+        })
+        .collect();
+
+    c.bench_function("resize_many_small", |b| {
+        b.iter(|| {
+            for image in &images {
+                black_box(resize(black_box(image), 24, 18, FilterType::Lanczos3));
+            }
+        })
+    });
+    c.bench_function("resize_many_small_with_plan", |b| {
+        let plan = ResizePlan::new((64, 48), (24, 18), FilterType::Lanczos3);
+        b.iter(|| {
+            for image in &images {
+                black_box(plan.resize(black_box(image)).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_resize_triangle, bench_resize_plan);
+criterion_main!(benches);
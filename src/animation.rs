@@ -1,4 +1,6 @@
 use std::cmp::Ordering;
+use std::iter::Sum;
+use std::ops::Add;
 use std::time::Duration;
 
 use crate::error::ImageResult;
@@ -42,6 +44,8 @@ pub struct Frame {
     /// y offset
     top: u32,
     buffer: RgbaImage,
+    /// Whether the color channels of the buffer are premultiplied by alpha
+    premultiplied: bool,
 }
 
 /// The delay of a frame relative to the previous one.
@@ -58,17 +62,48 @@ impl Frame {
             left: 0,
             top: 0,
             buffer,
+            premultiplied: false,
         }
     }
 
     /// Constructs a new frame
+    ///
+    /// The buffer may be smaller than the canvas of the animation, in which case it is drawn with
+    /// its top-left corner at (`left`, `top`).
     pub fn from_parts(buffer: RgbaImage, left: u32, top: u32, delay: Delay) -> Frame {
         Frame {
             delay,
             left,
             top,
             buffer,
+            premultiplied: false,
+        }
+    }
+
+    /// Marks whether the color channels of the buffer are premultiplied by its alpha channel.
+    ///
+    /// Frames hold straight alpha unless marked otherwise, which is also what all decoders
+    /// produce and what their compositing of frames expects. The animation formats only store
+    /// straight alpha, so encoders convert premultiplied frames with
+    /// [`into_straight_alpha`](Frame::into_straight_alpha) before writing them. Treating one
+    /// as the other darkens the semi-transparent edges of shapes, which shows as a halo.
+    pub fn with_premultiplied_alpha(mut self, premultiplied: bool) -> Frame {
+        self.premultiplied = premultiplied;
+        self
+    }
+
+    /// Whether the color channels of the buffer are premultiplied by its alpha channel.
+    pub fn is_premultiplied(&self) -> bool {
+        self.premultiplied
+    }
+
+    /// Converts the buffer to straight alpha if it is premultiplied.
+    pub fn into_straight_alpha(mut self) -> Frame {
+        if self.premultiplied {
+            crate::imageops::unpremultiply_alpha(&mut self.buffer);
+            self.premultiplied = false;
         }
+        self
     }
 
     /// Delay of this frame
@@ -117,6 +152,41 @@ impl Delay {
         }
     }
 
+    /// Create a delay of a whole number of milliseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use image::Delay;
+    /// let delay = Delay::from_millis(40);
+    /// assert_eq!(delay.as_millis(), 40);
+    /// ```
+    pub fn from_millis(millis: u32) -> Self {
+        Self::from_numer_denom_ms(millis, 1)
+    }
+
+    /// Create the delay between frames of an animation played at `fps` frames per second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fps` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use image::Delay;
+    /// let delay = Delay::from_fps(30);
+    /// assert_eq!(delay, Delay::from_numer_denom_ms(1000, 30));
+    /// ```
+    pub fn from_fps(fps: u32) -> Self {
+        Self::from_numer_denom_ms(1000, fps)
+    }
+
+    /// The delay in whole milliseconds, rounded down.
+    pub fn as_millis(self) -> u64 {
+        u64::from(self.ratio.to_integer())
+    }
+
     /// Convert from a duration, clamped between 0 and an implemented defined maximum.
     ///
     /// The maximum is *at least* `i32::MAX` milliseconds. It should be noted that the accuracy of
@@ -275,6 +345,40 @@ impl From<Delay> for Duration {
     }
 }
 
+impl Add for Delay {
+    type Output = Delay;
+
+    /// Add two delays, exactly if the sum can be represented and approximately otherwise.
+    fn add(self, other: Delay) -> Delay {
+        // Each product is below 2^64, their sum is not, so widen to u128.
+        let (a, b) = (u128::from(self.ratio.numer), u128::from(self.ratio.denom));
+        let (c, d) = (u128::from(other.ratio.numer), u128::from(other.ratio.denom));
+
+        let (numer, denom) = (a * d + c * b, b * d);
+        let divisor = gcd(numer, denom);
+        match (
+            u32::try_from(numer / divisor),
+            u32::try_from(denom / divisor),
+        ) {
+            (Ok(numer), Ok(denom)) => Delay::from_numer_denom_ms(numer, denom),
+            _ => Delay::from_saturating_duration(Duration::from(self) + Duration::from(other)),
+        }
+    }
+}
+
+impl Sum for Delay {
+    fn sum<I: Iterator<Item = Delay>>(iter: I) -> Delay {
+        iter.fold(Delay::from_millis(0), Add::add)
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct Ratio {
     numer: u32,
@@ -336,6 +440,47 @@ impl Ord for Ratio {
 mod tests {
     use super::{Delay, Duration, Ratio};
 
+    #[test]
+    fn from_fps() {
+        assert_eq!(Delay::from_fps(25).as_millis(), 40);
+        assert_eq!(Delay::from_fps(30).as_millis(), 33);
+        assert_eq!(Delay::from_millis(7), Delay::from_numer_denom_ms(7, 1));
+    }
+
+    #[test]
+    fn sum_of_delays() {
+        let total: Delay = std::iter::repeat(Delay::from_fps(30)).take(90).sum();
+        assert_eq!(total, Delay::from_millis(3000));
+        assert_eq!(total.numer_denom_ms(), (3000, 1));
+
+        let mixed = Delay::from_millis(15) + Delay::from_numer_denom_ms(1, 3);
+        assert_eq!(mixed.numer_denom_ms(), (46, 3));
+
+        let empty: Delay = std::iter::empty().sum();
+        assert_eq!(empty.as_millis(), 0);
+    }
+
+    #[test]
+    fn add_overflowing_ratio() {
+        let a = Delay::from_numer_denom_ms(u32::MAX / 2, 4_000_000_007);
+        let b = Delay::from_numer_denom_ms(1, 4_000_000_009);
+        let sum = Duration::from(a + b);
+        let exact = Duration::from(a) + Duration::from(b);
+        assert!(sum.max(exact) - sum.min(exact) < Duration::from_micros(1));
+    }
+
+    #[test]
+    fn add_large_terms() {
+        let one = Delay::from_numer_denom_ms(u32::MAX, u32::MAX);
+        assert_eq!((one + one).numer_denom_ms(), (2, 1));
+
+        let a = Delay::from_numer_denom_ms(u32::MAX, u32::MAX - 1);
+        assert_eq!((a + a).numer_denom_ms(), (u32::MAX, (u32::MAX - 1) / 2));
+
+        let max = Delay::from_numer_denom_ms(u32::MAX, 1);
+        assert_eq!((max + max).numer_denom_ms(), (u32::MAX, 1));
+    }
+
     #[test]
     fn simple() {
         let second = Delay::from_numer_denom_ms(1000, 1);
@@ -2,6 +2,7 @@
 use num_traits::Zero;
 use std::fmt;
 use std::marker::PhantomData;
+use std::mem::size_of;
 use std::ops::{Deref, DerefMut, Index, IndexMut, Range};
 use std::path::Path;
 use std::slice::{ChunksExact, ChunksExactMut};
@@ -709,6 +710,32 @@ where
         self.height
     }
 
+    /// The number of bytes in one row of pixels.
+    ///
+    /// Rows are tightly packed, so this is also the stride between the starts of two rows.
+    pub fn bytes_per_row(&self) -> usize {
+        self.width as usize * usize::from(P::CHANNEL_COUNT) * size_of::<P::Subpixel>()
+    }
+
+    /// The number of bytes of pixel data, which is `bytes_per_row() * height()`.
+    ///
+    /// This does not include any excess samples at the end of the container.
+    pub fn byte_len(&self) -> usize {
+        self.bytes_per_row() * self.height as usize
+    }
+
+    /// The number of pixels in this image, `width() * height()`.
+    ///
+    /// This is computed in `u64`, which cannot overflow for any dimensions.
+    pub fn pixel_count(&self) -> u64 {
+        u64::from(self.width) * u64::from(self.height)
+    }
+
+    /// Whether this image has no pixels, because its width or height is zero.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
     // TODO: choose name under which to expose.
     pub(crate) fn inner_pixels(&self) -> &[P::Subpixel] {
         let len = Self::image_buffer_len(self.width, self.height).unwrap();
@@ -734,6 +761,17 @@ where
         Rows::with_image(&self.data, self.width, self.height)
     }
 
+    /// Returns an iterator over the rows of this image as slices of subpixels.
+    ///
+    /// Each item holds the channels of one row of pixels, packed in the layout of the buffer.
+    /// Like [`rows`](ImageBuffer::rows), no item is yielded when the rows are empty.
+    pub fn scanlines(&self) -> ChunksExact<'_, P::Subpixel> {
+        match self.width as usize * usize::from(<P as Pixel>::CHANNEL_COUNT) {
+            0 => [].chunks_exact(1),
+            row_len => self.inner_pixels().chunks_exact(row_len),
+        }
+    }
+
     /// Enumerates over the pixels of the image.
     /// The iterator yields the coordinates of each pixel
     /// along with a reference to them.
@@ -759,6 +797,30 @@ where
         }
     }
 
+    /// Constructs a new image buffer, possibly of another pixel type, by applying the supplied
+    /// function to every pixel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use image::{Luma, Rgb, RgbImage};
+    ///
+    /// let rgb = RgbImage::from_pixel(2, 2, Rgb([255, 0, 0]));
+    /// let red = rgb.map_pixels(|p| Luma([p[0]]));
+    /// assert_eq!(red.get_pixel(1, 1), &Luma([255]));
+    /// ```
+    pub fn map_pixels<Q, F>(&self, mut f: F) -> ImageBuffer<Q, Vec<Q::Subpixel>>
+    where
+        Q: Pixel,
+        F: FnMut(&P) -> Q,
+    {
+        let mut buf = ImageBuffer::new(self.width, self.height);
+        for (q, p) in buf.pixels_mut().zip(self.pixels()) {
+            *q = f(p);
+        }
+        buf
+    }
+
     /// Gets a reference to the pixel at location `(x, y)`
     ///
     /// # Panics
@@ -912,6 +974,16 @@ where
         RowsMut::with_image(&mut self.data, self.width, self.height)
     }
 
+    /// Returns an iterator over the rows of this image as mutable slices of subpixels.
+    ///
+    /// See [`scanlines`](ImageBuffer::scanlines) for details.
+    pub fn scanlines_mut(&mut self) -> ChunksExactMut<'_, P::Subpixel> {
+        match self.width as usize * usize::from(<P as Pixel>::CHANNEL_COUNT) {
+            0 => [].chunks_exact_mut(1),
+            row_len => self.inner_pixels_mut().chunks_exact_mut(row_len),
+        }
+    }
+
     /// Enumerates over the pixels of the image.
     /// The iterator yields the coordinates of each pixel
     /// along with a mutable reference to them.
@@ -984,6 +1056,63 @@ where
     }
 }
 
+impl<Container> ImageBuffer<Rgb<u8>, Container>
+where
+    Container: Deref<Target = [u8]>,
+{
+    /// Packs the image into RGB565, two little-endian bytes per pixel.
+    ///
+    /// Rows are stored without padding, as expected by most 16-bit framebuffers. See
+    /// [`Rgb::to_rgb565`] for the layout of a pixel.
+    pub fn to_rgb565_le(&self) -> Vec<u8> {
+        self.pixels()
+            .flat_map(|p| p.to_rgb565().to_le_bytes())
+            .collect()
+    }
+
+    /// Packs the image into RGB565, two big-endian bytes per pixel.
+    ///
+    /// Apart from the byte order, this is the same as [`ImageBuffer::to_rgb565_le`].
+    pub fn to_rgb565_be(&self) -> Vec<u8> {
+        self.pixels()
+            .flat_map(|p| p.to_rgb565().to_be_bytes())
+            .collect()
+    }
+}
+
+impl ImageBuffer<Rgb<u8>, Vec<u8>> {
+    /// Unpacks an image from RGB565 with two little-endian bytes per pixel.
+    ///
+    /// Returns `None` if `bytes` does not hold exactly `width * height` pixels.
+    pub fn from_rgb565_le(width: u32, height: u32, bytes: &[u8]) -> Option<Self> {
+        Self::from_rgb565(width, height, bytes, u16::from_le_bytes)
+    }
+
+    /// Unpacks an image from RGB565 with two big-endian bytes per pixel.
+    ///
+    /// Returns `None` if `bytes` does not hold exactly `width * height` pixels.
+    pub fn from_rgb565_be(width: u32, height: u32, bytes: &[u8]) -> Option<Self> {
+        Self::from_rgb565(width, height, bytes, u16::from_be_bytes)
+    }
+
+    fn from_rgb565(
+        width: u32,
+        height: u32,
+        bytes: &[u8],
+        read: fn([u8; 2]) -> u16,
+    ) -> Option<Self> {
+        let pixels = (width as usize).checked_mul(height as usize)?;
+        if pixels.checked_mul(2)? != bytes.len() {
+            return None;
+        }
+        let data = bytes
+            .chunks_exact(2)
+            .flat_map(|b| Rgb::from_rgb565(read([b[0], b[1]])).0)
+            .collect();
+        ImageBuffer::from_raw(width, height, data)
+    }
+}
+
 impl<P, Container> ImageBuffer<P, Container>
 where
     P: Pixel,
@@ -1485,6 +1614,27 @@ mod test {
     use crate::ImageFormat;
     use crate::{color, Rgb};
 
+    #[test]
+    fn pixel_count_of_huge_image() {
+        // A nominal image, large enough for `width * height` to overflow `u32`, without
+        // allocating its pixels.
+        let huge: GrayImage = ImageBuffer {
+            width: 70_000,
+            height: 70_000,
+            _phantom: std::marker::PhantomData,
+            data: Vec::new(),
+        };
+        assert_eq!(huge.pixel_count(), 4_900_000_000);
+        assert!(!huge.is_empty());
+        let huge = crate::DynamicImage::ImageLuma8(huge);
+        assert_eq!(huge.pixel_count(), 4_900_000_000);
+        assert!(!huge.is_empty());
+
+        assert_eq!(RgbImage::new(3, 5).pixel_count(), 15);
+        assert!(RgbImage::new(0, 5).is_empty());
+        assert!(crate::DynamicImage::new_rgb8(4, 0).is_empty());
+    }
+
     #[test]
     /// Tests if image buffers from slices work
     fn slice_buffer() {
@@ -1503,6 +1653,25 @@ mod test {
         assert_eq!(a.get_pixel(0, 1)[0], 255)
     }
 
+    #[test]
+    fn map_pixels() {
+        let rgb = RgbImage::from_fn(4, 3, |x, y| Rgb([(x * 60) as u8, (y * 100) as u8, 30]));
+        let gray: GrayImage = rgb.map_pixels(|p| {
+            let [r, g, b] = p.0.map(u32::from);
+            color::Luma([((r * 2 + g + b) / 4) as u8])
+        });
+
+        assert_eq!(gray.dimensions(), rgb.dimensions());
+        for (x, y, p) in gray.enumerate_pixels() {
+            let expected = (x * 60 * 2 + y * 100 + 30) / 4;
+            assert_eq!(p.0[0], expected as u8);
+        }
+
+        let wide: ImageBuffer<color::Luma<u16>, _> =
+            gray.map_pixels(|p| color::Luma([u16::from(p.0[0]) * 257]));
+        assert_eq!(wide.get_pixel(0, 0).0[0], 30 / 4 * 257);
+    }
+
     #[test]
     fn get_pixel_checked() {
         let mut a: RgbImage = ImageBuffer::new(10, 10);
@@ -1575,6 +1744,68 @@ mod test {
 
         assert_eq!(image.rows().count(), 1);
         assert_eq!(image.rows_mut().count(), 1);
+        assert_eq!(image.scanlines().count(), 1);
+        assert_eq!(image.scanlines_mut().count(), 1);
+    }
+
+    #[test]
+    fn bytes_per_row() {
+        let image = super::RgbaImage::new(7, 3);
+        assert_eq!(image.bytes_per_row(), 28);
+        assert_eq!(image.byte_len(), 84);
+
+        let image = ImageBuffer::<Rgb<u16>, _>::from_raw(5, 2, vec![0u16; 40]).unwrap();
+        assert_eq!(image.bytes_per_row(), 30);
+        assert_eq!(image.byte_len(), 60);
+    }
+
+    #[test]
+    fn rgb565_byte_order() {
+        let image = RgbImage::from_fn(3, 2, |x, y| {
+            if (x + y) % 2 == 0 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        let le = image.to_rgb565_le();
+        let be = image.to_rgb565_be();
+        assert_eq!(le[..4], [0x00, 0xf8, 0x1f, 0x00]);
+        assert_eq!(be[..4], [0xf8, 0x00, 0x00, 0x1f]);
+        assert_eq!(le.len(), 12);
+
+        assert_eq!(RgbImage::from_rgb565_le(3, 2, &le).unwrap(), image);
+        assert_eq!(RgbImage::from_rgb565_be(3, 2, &be).unwrap(), image);
+        assert_ne!(RgbImage::from_rgb565_le(3, 2, &be).unwrap(), image);
+        assert!(RgbImage::from_rgb565_le(3, 2, &le[1..]).is_none());
+        assert!(RgbImage::from_rgb565_be(2, 2, &be).is_none());
+    }
+
+    #[test]
+    fn scanlines() {
+        let mut image = RgbImage::from_fn(5, 3, |x, y| Rgb([x as u8, y as u8, (x * y) as u8]));
+        let row_len = 5 * 3;
+
+        let means: Vec<f32> = image
+            .scanlines()
+            .map(|row| row.iter().map(|&c| f32::from(c)).sum::<f32>() / row.len() as f32)
+            .collect();
+        let expected: Vec<f32> = (0..3)
+            .map(|y| {
+                let row = &image.as_raw()[y * row_len..][..row_len];
+                row.iter().map(|&c| f32::from(c)).sum::<f32>() / row_len as f32
+            })
+            .collect();
+        assert_eq!(means, expected);
+
+        for (y, row) in image.scanlines_mut().enumerate() {
+            row.fill(y as u8);
+        }
+        assert_eq!(image.get_pixel(4, 2), &Rgb([2, 2, 2]));
+
+        let mut empty = RgbImage::new(0, 3);
+        assert_eq!(empty.scanlines().count(), 0);
+        assert_eq!(empty.scanlines_mut().count(), 0);
     }
 
     #[test]
@@ -1678,6 +1909,32 @@ mod test {
         assert!(img.write_to(&mut buffer, ImageFormat::Png).is_ok());
     }
 
+    #[test]
+    #[cfg(feature = "webp")]
+    fn save_webp_round_trip() {
+        let img = RgbImage::from_fn(7, 5, |x, y| Rgb([x as u8 * 30, y as u8 * 50, 200]));
+        let dir = std::env::temp_dir();
+        let by_extension = dir.join(format!("image-rs-buffer-{}.webp", std::process::id()));
+        let by_format = dir.join(format!("image-rs-buffer-{}.img", std::process::id()));
+
+        let saved = img
+            .save(&by_extension)
+            .and_then(|()| img.save_with_format(&by_format, ImageFormat::WebP));
+        let decoded = saved.and_then(|()| {
+            let by_extension = crate::open(&by_extension)?;
+            let by_format = crate::io::Reader::open(&by_format)?
+                .with_guessed_format()?
+                .decode()?;
+            Ok((by_extension, by_format))
+        });
+        let _ = std::fs::remove_file(&by_extension);
+        let _ = std::fs::remove_file(&by_format);
+
+        let (by_extension, by_format) = decoded.unwrap();
+        assert_eq!(by_extension.into_rgb8(), img);
+        assert_eq!(by_format.into_rgb8(), img);
+    }
+
     #[test]
     fn exact_size_iter_size_hint() {
         // The docs for `std::iter::ExactSizeIterator` requires that the implementation of
@@ -129,6 +129,15 @@ impl<W: Write> ImageEncoder for AvifEncoder<W> {
         self.inner.write_all(&data.avif_file)?;
         Ok(())
     }
+
+    fn set_quality(&mut self, quality: u8) {
+        let quality = f32::from(min(quality, 100));
+        self.encoder = self
+            .encoder
+            .clone()
+            .with_quality(quality)
+            .with_alpha_quality(quality);
+    }
 }
 
 impl<W: Write> AvifEncoder<W> {
@@ -5,7 +5,7 @@ use crate::error::{
     EncodingError, ImageError, ImageFormatHint, ImageResult, ParameterError, ParameterErrorKind,
 };
 use crate::image::ImageEncoder;
-use crate::{ExtendedColorType, ImageFormat};
+use crate::{ColorType, ExtendedColorType, ImageFormat};
 
 const BITMAPFILEHEADER_SIZE: u32 = 14;
 const BITMAPINFOHEADER_SIZE: u32 = 40;
@@ -54,13 +54,7 @@ impl<'a, W: Write + 'a> BmpEncoder<'a, W> {
         palette: Option<&[[u8; 3]]>,
     ) -> ImageResult<()> {
         if palette.is_some() && c != ExtendedColorType::L8 && c != ExtendedColorType::La8 {
-            return Err(ImageError::IoError(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!(
-                    "Unsupported color type {:?} when using a non-empty palette. Supported types: Gray(8), GrayA(8).",
-                    c
-                ),
-            )));
+            return Err(ImageError::unsupported_color(ImageFormat::Bmp, c));
         }
 
         let expected_buffer_len = c.buffer_size(width, height);
@@ -139,21 +133,16 @@ impl<'a, W: Write + 'a> BmpEncoder<'a, W> {
         }
 
         // write image data
-        match c {
-            ExtendedColorType::Rgb8 => self.encode_rgb(image, width, height, row_pad_size, 3)?,
-            ExtendedColorType::Rgba8 => self.encode_rgba(image, width, height, row_pad_size, 4)?,
-            ExtendedColorType::L8 => {
+        match c.to_color_type() {
+            Some(ColorType::Rgb8) => self.encode_rgb(image, width, height, row_pad_size, 3)?,
+            Some(ColorType::Rgba8) => self.encode_rgba(image, width, height, row_pad_size, 4)?,
+            Some(ColorType::L8) => {
                 self.encode_gray(image, width, height, row_pad_size, 1, palette)?
             }
-            ExtendedColorType::La8 => {
+            Some(ColorType::La8) => {
                 self.encode_gray(image, width, height, row_pad_size, 2, palette)?
             }
-            _ => {
-                return Err(ImageError::IoError(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    &get_unsupported_error_message(c)[..],
-                )))
-            }
+            _ => return Err(ImageError::unsupported_color(ImageFormat::Bmp, c)),
         }
 
         Ok(())
@@ -279,37 +268,25 @@ impl<'a, W: Write> ImageEncoder for BmpEncoder<'a, W> {
     }
 }
 
-fn get_unsupported_error_message(c: ExtendedColorType) -> String {
-    format!(
-        "Unsupported color type {:?}.  Supported types: RGB(8), RGBA(8), Gray(8), GrayA(8).",
-        c
-    )
-}
-
 /// Returns a tuple representing: (dib header size, written pixel size, palette color count).
 fn get_pixel_info(
     c: ExtendedColorType,
     palette: Option<&[[u8; 3]]>,
-) -> io::Result<(u32, u32, u32)> {
-    let sizes = match c {
-        ExtendedColorType::Rgb8 => (BITMAPINFOHEADER_SIZE, 3, 0),
-        ExtendedColorType::Rgba8 => (BITMAPV4HEADER_SIZE, 4, 0),
-        ExtendedColorType::L8 => (
+) -> ImageResult<(u32, u32, u32)> {
+    let sizes = match c.to_color_type() {
+        Some(ColorType::Rgb8) => (BITMAPINFOHEADER_SIZE, 3, 0),
+        Some(ColorType::Rgba8) => (BITMAPV4HEADER_SIZE, 4, 0),
+        Some(ColorType::L8) => (
             BITMAPINFOHEADER_SIZE,
             1,
             palette.map(|p| p.len()).unwrap_or(256) as u32,
         ),
-        ExtendedColorType::La8 => (
+        Some(ColorType::La8) => (
             BITMAPINFOHEADER_SIZE,
             1,
             palette.map(|p| p.len()).unwrap_or(256) as u32,
         ),
-        _ => {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                &get_unsupported_error_message(c)[..],
-            ))
-        }
+        _ => return Err(ImageError::unsupported_color(ImageFormat::Bmp, c)),
     };
 
     Ok(sizes)
@@ -300,11 +300,9 @@ impl<W: Write> ImageEncoder for FarbfeldEncoder<W> {
         color_type: ExtendedColorType,
     ) -> ImageResult<()> {
         if color_type != ExtendedColorType::Rgba16 {
-            return Err(ImageError::Unsupported(
-                UnsupportedError::from_format_and_kind(
-                    ImageFormat::Farbfeld.into(),
-                    UnsupportedErrorKind::Color(color_type),
-                ),
+            return Err(ImageError::unsupported_color(
+                ImageFormat::Farbfeld,
+                color_type,
             ));
         }
 
@@ -35,7 +35,7 @@ use gif::ColorOutput;
 use gif::{DisposalMethod, Frame};
 
 use crate::animation::{self, Ratio};
-use crate::color::{ColorType, Rgba};
+use crate::color::{ColorType, Rgb, Rgba};
 use crate::error::LimitError;
 use crate::error::LimitErrorKind;
 use crate::error::{
@@ -495,16 +495,39 @@ impl<W: Write> GifEncoder<W> {
             ExtendedColorType::Rgba8 => {
                 self.encode_gif(Frame::from_rgba(width, height, &mut data.to_owned()))
             }
-            _ => Err(ImageError::Unsupported(
-                UnsupportedError::from_format_and_kind(
-                    ImageFormat::Gif.into(),
-                    UnsupportedErrorKind::Color(color),
-                ),
-            )),
+            _ => Err(ImageError::unsupported_color(ImageFormat::Gif, color)),
         }
     }
 
+    /// Encode a single paletted image.
+    ///
+    /// Each byte of `indices` is one pixel, referring to an entry of `palette` which may hold at
+    /// most 256 colors. See [`ImageEncoder::write_indexed_image`](crate::ImageEncoder).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width * height != indices.len()`.
+    #[track_caller]
+    pub fn encode_indexed(
+        &mut self,
+        indices: &[u8],
+        width: u32,
+        height: u32,
+        palette: &[Rgb<u8>],
+    ) -> ImageResult<()> {
+        crate::image::check_indexed_image(indices, width, height, palette)?;
+        let (width, height) = self.gif_dimensions(width, height)?;
+        let palette: Vec<u8> = palette.iter().flat_map(|color| color.0).collect();
+        self.encode_gif(Frame::from_palette_pixels(
+            width, height, indices, palette, None,
+        ))
+    }
+
     /// Encode one frame of animation.
+    ///
+    /// The frame is placed at its [`left`](animation::Frame::left) and
+    /// [`top`](animation::Frame::top) offset. The size of the logical screen is taken from the
+    /// extent of the first frame.
     pub fn encode_frame(&mut self, img_frame: animation::Frame) -> ImageResult<()> {
         let frame = self.convert_frame(img_frame)?;
         self.encode_gif(frame)
@@ -539,14 +562,17 @@ impl<W: Write> GifEncoder<W> {
         &mut self,
         img_frame: animation::Frame,
     ) -> ImageResult<Frame<'static>> {
-        // get the delay before converting img_frame
+        // get the delay and offset before converting img_frame
         let frame_delay = img_frame.delay().into_ratio().to_integer();
+        let (left, top) = self.gif_dimensions(img_frame.left(), img_frame.top())?;
         // convert img_frame into RgbaImage
-        let mut rbga_frame = img_frame.into_buffer();
+        let mut rbga_frame = img_frame.into_straight_alpha().into_buffer();
         let (width, height) = self.gif_dimensions(rbga_frame.width(), rbga_frame.height())?;
 
         // Create the gif::Frame from the animation::Frame
         let mut frame = Frame::from_rgba_speed(width, height, &mut rbga_frame, self.speed);
+        frame.left = left;
+        frame.top = top;
         // Saturate the conversion to u16::MAX instead of returning an error as that
         // would require a new special cased variant in ParameterErrorKind which most
         // likely couldn't be reused for other cases. This isn't a bad trade-off given
@@ -577,7 +603,9 @@ impl<W: Write> GifEncoder<W> {
             gif_encoder = encoder;
         } else {
             let writer = self.w.take().unwrap();
-            let mut encoder = gif::Encoder::new(writer, frame.width, frame.height, &[])
+            let screen_width = frame.left.saturating_add(frame.width);
+            let screen_height = frame.top.saturating_add(frame.height);
+            let mut encoder = gif::Encoder::new(writer, screen_width, screen_height, &[])
                 .map_err(ImageError::from_encoding)?;
             if let Some(ref repeat) = self.repeat {
                 encoder
@@ -621,6 +649,7 @@ impl ImageError {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::RgbaImage;
 
     #[test]
     fn frames_exceeding_logical_screen_size() {
@@ -639,4 +668,57 @@ mod test {
 
         assert!(decoder.read_image(&mut buf).is_ok());
     }
+
+    #[test]
+    fn encode_indexed() {
+        let palette = [Rgb([255, 0, 0]), Rgb([0, 255, 0]), Rgb([0, 0, 255])];
+        let indices = [0, 1, 2, 2, 1, 0];
+
+        let mut buf = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut buf);
+            encoder.encode_indexed(&indices, 3, 2, &palette).unwrap();
+        }
+
+        let decoder = GifDecoder::new(Cursor::new(buf)).unwrap();
+        let mut decoded = vec![0u8; decoder.total_bytes() as usize];
+        decoder.read_image(&mut decoded).unwrap();
+        for (pixel, &index) in decoded.chunks_exact(4).zip(&indices) {
+            let Rgb([r, g, b]) = palette[usize::from(index)];
+            assert_eq!(pixel, [r, g, b, 255]);
+        }
+    }
+
+    #[test]
+    fn encode_frame_at_offset() {
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        let delay = animation::Delay::from_millis(100);
+
+        let mut buf = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut buf);
+            encoder
+                .encode_frames([
+                    animation::Frame::from_parts(RgbaImage::from_pixel(4, 4, red), 0, 0, delay),
+                    animation::Frame::from_parts(RgbaImage::from_pixel(2, 1, blue), 1, 2, delay),
+                ])
+                .unwrap();
+        }
+
+        let decoder = GifDecoder::new(Cursor::new(buf)).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 2);
+
+        let second = frames[1].buffer();
+        assert_eq!(second.dimensions(), (4, 4));
+        for (x, y, &pixel) in second.enumerate_pixels() {
+            if (1..3).contains(&x) && y == 2 {
+                assert_eq!(pixel, blue);
+            } else {
+                // The first frame is disposed to the background.
+                assert_eq!(pixel, Rgba([0, 0, 0, 0]));
+            }
+        }
+    }
 }
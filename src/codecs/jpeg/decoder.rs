@@ -1,6 +1,7 @@
 use std::io::{BufRead, Seek};
 use std::marker::PhantomData;
 
+use super::extended;
 use crate::color::ColorType;
 use crate::error::{
     DecodingError, ImageError, ImageResult, LimitError, UnsupportedError, UnsupportedErrorKind,
@@ -11,12 +12,18 @@ use crate::io::Limits;
 type ZuneColorSpace = zune_core::colorspace::ColorSpace;
 
 /// JPEG decoder
+///
+/// Images are always decoded at their full size, the backend has no reduced-size inverse DCT.
+/// For a smaller version, resize the decoded image, for example with
+/// [`DynamicImage::thumbnail`](crate::DynamicImage::thumbnail).
 pub struct JpegDecoder<R> {
     input: Vec<u8>,
     orig_color_space: ZuneColorSpace,
     width: u16,
     height: u16,
     limits: Limits,
+    /// The header of an image with 12-bit samples, which the backend cannot decode.
+    extended: Option<extended::Header>,
     // For API compatibility with the previous jpeg_decoder wrapper.
     // Can be removed later, which would be an API break.
     phantom: PhantomData<R>,
@@ -28,6 +35,21 @@ impl<R: BufRead + Seek> JpegDecoder<R> {
         let mut input = Vec::new();
         let mut r = r;
         r.read_to_end(&mut input)?;
+        if let Some(header) = extended::read_header(&input)? {
+            let orig_color_space = match header.components {
+                1 => ZuneColorSpace::Luma,
+                _ => ZuneColorSpace::RGB,
+            };
+            return Ok(JpegDecoder {
+                input,
+                orig_color_space,
+                width: header.width,
+                height: header.height,
+                limits: Limits::no_limits(),
+                extended: Some(header),
+                phantom: PhantomData,
+            });
+        }
         let mut decoder = zune_jpeg::JpegDecoder::new(input.as_slice());
         decoder.decode_headers().map_err(ImageError::from_jpeg)?;
         // now that we've decoded the headers we can `.unwrap()`
@@ -45,6 +67,7 @@ impl<R: BufRead + Seek> JpegDecoder<R> {
             width,
             height,
             limits,
+            extended: None,
             phantom: PhantomData,
         })
     }
@@ -56,10 +79,17 @@ impl<R: BufRead + Seek> ImageDecoder for JpegDecoder<R> {
     }
 
     fn color_type(&self) -> ColorType {
-        ColorType::from_jpeg(self.orig_color_space)
+        match &self.extended {
+            Some(header) if header.components == 1 => ColorType::L16,
+            Some(_) => ColorType::Rgb16,
+            None => ColorType::from_jpeg(self.orig_color_space),
+        }
     }
 
     fn icc_profile(&mut self) -> ImageResult<Option<Vec<u8>>> {
+        if let Some(header) = &self.extended {
+            return Ok(header.icc_profile.clone());
+        }
         let mut decoder = zune_jpeg::JpegDecoder::new(&self.input);
         decoder.decode_headers().map_err(ImageError::from_jpeg)?;
         Ok(decoder.icc_profile())
@@ -80,6 +110,17 @@ impl<R: BufRead + Seek> ImageDecoder for JpegDecoder<R> {
             )));
         }
 
+        if self.extended.is_some() {
+            let mut limits = self.limits;
+            limits.reserve_usize(buf.len())?;
+            let mut samples = vec![0u16; buf.len() / 2];
+            extended::decode(&self.input, &mut samples, &mut limits)?;
+            for (bytes, sample) in buf.chunks_exact_mut(2).zip(samples) {
+                bytes.copy_from_slice(&sample.to_ne_bytes());
+            }
+            return Ok(());
+        }
+
         let mut decoder = new_zune_decoder(&self.input, self.orig_color_space, self.limits);
         decoder.decode_into(buf).map_err(ImageError::from_jpeg)?;
         Ok(())
@@ -158,3 +199,22 @@ impl ImageError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icc_profile() {
+        let file = std::fs::File::open("tests/images/jpg/progressive/cat.jpg")
+            .map(std::io::BufReader::new)
+            .unwrap();
+        let mut decoder = JpegDecoder::new(file).unwrap();
+        let profile = decoder.icc_profile().unwrap().unwrap();
+
+        // The profile header starts with its size and carries the `acsp` signature.
+        let size = u32::from_be_bytes(profile[..4].try_into().unwrap());
+        assert_eq!(size as usize, profile.len());
+        assert_eq!(&profile[36..40], b"acsp");
+    }
+}
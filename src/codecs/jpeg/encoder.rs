@@ -3,10 +3,7 @@
 use std::borrow::Cow;
 use std::io::{self, Write};
 
-use crate::error::{
-    ImageError, ImageResult, ParameterError, ParameterErrorKind, UnsupportedError,
-    UnsupportedErrorKind,
-};
+use crate::error::{ImageError, ImageResult, ParameterError, ParameterErrorKind};
 use crate::image::{ImageEncoder, ImageFormat};
 use crate::utils::clamp;
 use crate::{ExtendedColorType, GenericImageView, ImageBuffer, Luma, LumaA, Pixel, Rgb, Rgba};
@@ -138,7 +135,7 @@ static CHROMAREDID: u8 = 3;
 
 /// The permutation of dct coefficients.
 #[rustfmt::skip]
-static UNZIGZAG: [u8; 64] = [
+pub(super) static UNZIGZAG: [u8; 64] = [
      0,  1,  8, 16,  9,  2,  3, 10,
     17, 24, 32, 25, 18, 11,  4,  5,
     12, 19, 26, 33, 40, 48, 41, 34,
@@ -389,30 +386,11 @@ impl<W: Write> JpegEncoder<W> {
             },
         ];
 
-        // Derive our quantization table scaling value using the libjpeg algorithm
-        let scale = u32::from(clamp(quality, 1, 100));
-        let scale = if scale < 50 {
-            5000 / scale
-        } else {
-            200 - scale * 2
-        };
-
-        let mut tables = vec![STD_LUMA_QTABLE, STD_CHROMA_QTABLE];
-        tables.iter_mut().for_each(|t| {
-            t.iter_mut().for_each(|v| {
-                *v = clamp(
-                    (u32::from(*v) * scale + 50) / 100,
-                    1,
-                    u32::from(u8::max_value()),
-                ) as u8;
-            })
-        });
-
         JpegEncoder {
             writer: BitWriter::new(w),
 
             components,
-            tables,
+            tables: quantization_tables(quality),
 
             luma_dctable: Cow::Borrowed(&STD_LUMA_DC_HUFF_LUT),
             luma_actable: Cow::Borrowed(&STD_LUMA_AC_HUFF_LUT),
@@ -455,6 +433,10 @@ impl<W: Write> JpegEncoder<W> {
             image.len(),
         );
 
+        if let Some((rgb, color_type)) = color_type.swizzle_bgr(image) {
+            return self.encode(&rgb, width, height, color_type);
+        }
+
         match color_type {
             ExtendedColorType::L8 => {
                 let image: ImageBuffer<Luma<_>, _> =
@@ -476,12 +458,7 @@ impl<W: Write> JpegEncoder<W> {
                     ImageBuffer::from_raw(width, height, image).unwrap();
                 self.encode_image(&image)
             }
-            _ => Err(ImageError::Unsupported(
-                UnsupportedError::from_format_and_kind(
-                    ImageFormat::Jpeg.into(),
-                    UnsupportedErrorKind::Color(color_type),
-                ),
-            )),
+            _ => Err(ImageError::unsupported_color(ImageFormat::Jpeg, color_type)),
         }
     }
 
@@ -676,6 +653,33 @@ impl<W: Write> ImageEncoder for JpegEncoder<W> {
     ) -> ImageResult<()> {
         self.encode(buf, width, height, color_type)
     }
+
+    fn set_quality(&mut self, quality: u8) {
+        self.tables = quantization_tables(quality);
+    }
+}
+
+/// Scale the standard quantization tables for a quality of 1 to 100, as libjpeg does.
+fn quantization_tables(quality: u8) -> Vec<[u8; 64]> {
+    // Derive our quantization table scaling value using the libjpeg algorithm
+    let scale = u32::from(clamp(quality, 1, 100));
+    let scale = if scale < 50 {
+        5000 / scale
+    } else {
+        200 - scale * 2
+    };
+
+    let mut tables = vec![STD_LUMA_QTABLE, STD_CHROMA_QTABLE];
+    tables.iter_mut().for_each(|t| {
+        t.iter_mut().for_each(|v| {
+            *v = clamp(
+                (u32::from(*v) * scale + 50) / 100,
+                1,
+                u32::from(u8::max_value()),
+            ) as u8;
+        })
+    });
+    tables
 }
 
 fn build_jfif_header(m: &mut Vec<u8>, density: PixelDensity) {
@@ -865,6 +869,53 @@ mod tests {
         STD_LUMA_DC_CODE_LENGTHS, STD_LUMA_DC_VALUES,
     };
 
+    /// Encode a noisy gradient with an encoder configured only through `ImageEncoder`.
+    fn encode_with_quality<E: ImageEncoder>(mut encoder: E, quality: u8) {
+        let image: Vec<u8> = (0..64u32 * 64 * 3)
+            .map(|i| (i / 3 % 64 * 4) as u8 ^ (i.wrapping_mul(2_654_435_761) >> 28) as u8)
+            .collect();
+        encoder.set_quality(quality);
+        encoder
+            .write_image(&image, 64, 64, ExtendedColorType::Rgb8)
+            .unwrap();
+    }
+
+    #[test]
+    fn set_quality_through_trait() {
+        let encode = |quality| {
+            let mut encoded = Vec::new();
+            encode_with_quality(JpegEncoder::new(&mut encoded), quality);
+            encoded
+        };
+        let (low, default, high) = (encode(10), encode(75), encode(100));
+        assert!(low.len() < default.len() && default.len() < high.len());
+
+        let mut unchanged = Vec::new();
+        JpegEncoder::new(&mut unchanged)
+            .write_image(&[0; 3], 1, 1, ExtendedColorType::Rgb8)
+            .unwrap();
+        let mut configured = Vec::new();
+        let mut encoder = JpegEncoder::new_with_quality(&mut configured, 10);
+        encoder.set_quality(75);
+        encoder
+            .write_image(&[0; 3], 1, 1, ExtendedColorType::Rgb8)
+            .unwrap();
+        assert_eq!(configured, unchanged);
+    }
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn set_quality_is_ignored_by_lossless_webp() {
+        use crate::codecs::webp::WebPEncoder;
+
+        let encode = |quality| {
+            let mut encoded = Vec::new();
+            encode_with_quality(WebPEncoder::new_lossless(&mut encoded), quality);
+            encoded
+        };
+        assert_eq!(encode(10), encode(100));
+    }
+
     fn decode(encoded: &[u8]) -> Vec<u8> {
         let decoder = JpegDecoder::new(Cursor::new(encoded)).expect("Could not decode image");
 
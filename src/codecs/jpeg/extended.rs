@@ -0,0 +1,982 @@
+//! Decoding of JPEG images with 12-bit samples.
+//!
+//! The extended sequential process of the JPEG standard allows 12 bits per sample, which the
+//! decoding backend used for all other JPEG images does not support. This implements that
+//! process for Huffman coded images with one or three components, producing 16-bit samples.
+//!
+//! # Related Links
+//! * <http://www.w3.org/Graphics/JPEG/itu-t81.pdf> - The JPEG specification
+
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+use super::encoder::UNZIGZAG;
+use crate::error::{
+    DecodingError, ImageError, ImageResult, UnsupportedError, UnsupportedErrorKind,
+};
+use crate::image::ImageFormat;
+use crate::io::Limits;
+
+const SOF0: u8 = 0xC0;
+const SOF1: u8 = 0xC1;
+const DHT: u8 = 0xC4;
+const RST0: u8 = 0xD0;
+const RST7: u8 = 0xD7;
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const SOS: u8 = 0xDA;
+const DQT: u8 = 0xDB;
+const DRI: u8 = 0xDD;
+const APP2: u8 = 0xE2;
+const APP14: u8 = 0xEE;
+
+/// The properties of a 12-bit image, as read from the markers up to its frame header.
+pub(super) struct Header {
+    pub(super) width: u16,
+    pub(super) height: u16,
+    pub(super) components: u8,
+    pub(super) icc_profile: Option<Vec<u8>>,
+}
+
+/// Read the header of `data` if it is a JPEG image with 12-bit samples.
+///
+/// Returns `None` for any other image, including malformed ones, which are left to the backend.
+pub(super) fn read_header(data: &[u8]) -> ImageResult<Option<Header>> {
+    let mut parser = Parser { data, pos: 0 };
+    if parser.marker() != Some(SOI) {
+        return Ok(None);
+    }
+
+    let mut icc_chunks = Vec::new();
+    while let Some(marker) = parser.marker() {
+        let Some(segment) = parser.segment() else {
+            return Ok(None);
+        };
+        match marker {
+            APP2 if segment.starts_with(b"ICC_PROFILE\0") && segment.len() >= 14 => {
+                icc_chunks.push((segment[12], &segment[14..]));
+            }
+            0xC0..=0xCF if !matches!(marker, DHT | 0xC8 | 0xCC) => {
+                let Ok(frame) = Frame::parse(segment) else {
+                    return Ok(None);
+                };
+                if frame.precision != 12 {
+                    return Ok(None);
+                }
+                if !matches!(marker, SOF0 | SOF1) {
+                    return Err(unsupported(
+                        "12-bit JPEG images are only supported with sequential Huffman coding",
+                    ));
+                }
+                frame.check_supported()?;
+
+                icc_chunks.sort_by_key(|&(sequence, _)| sequence);
+                let icc_profile = (!icc_chunks.is_empty()).then(|| {
+                    icc_chunks
+                        .iter()
+                        .flat_map(|(_, chunk)| *chunk)
+                        .copied()
+                        .collect()
+                });
+                return Ok(Some(Header {
+                    width: frame.width,
+                    height: frame.height,
+                    components: frame.components.len() as u8,
+                    icc_profile,
+                }));
+            }
+            SOS | EOI => return Ok(None),
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Decode a 12-bit image into interleaved samples scaled to the full 16-bit range.
+///
+/// `out` must hold `width * height * components` samples of the image described by
+/// [`read_header`]. Subsampled components are upsampled by repeating their samples. The planes
+/// holding the samples of each component until then are counted against `limits`.
+pub(super) fn decode(data: &[u8], out: &mut [u16], limits: &mut Limits) -> ImageResult<()> {
+    let mut parser = Parser { data, pos: 0 };
+    if parser.marker() != Some(SOI) {
+        return Err(decoding("missing start of image marker"));
+    }
+
+    let mut quantization = [[0u16; 64]; 4];
+    let mut dc_tables: [Option<HuffmanTable>; 4] = Default::default();
+    let mut ac_tables: [Option<HuffmanTable>; 4] = Default::default();
+    let mut restart_interval = 0;
+    let mut adobe_transform = None;
+    let mut frame: Option<Frame> = None;
+    let mut planes: Vec<Plane> = Vec::new();
+
+    loop {
+        let marker = parser
+            .marker()
+            .ok_or_else(|| decoding("missing end of image marker"))?;
+        if marker == EOI {
+            break;
+        }
+        let segment = parser
+            .segment()
+            .ok_or_else(|| decoding("truncated marker segment"))?;
+        match marker {
+            DQT => read_quantization_tables(segment, &mut quantization)?,
+            DHT => read_huffman_tables(segment, &mut dc_tables, &mut ac_tables)?,
+            DRI => {
+                let interval = segment
+                    .get(..2)
+                    .ok_or_else(|| decoding("truncated restart interval"))?;
+                restart_interval = u16::from_be_bytes([interval[0], interval[1]]);
+            }
+            APP14 if segment.starts_with(b"Adobe") && segment.len() >= 12 => {
+                adobe_transform = Some(segment[11]);
+            }
+            0xC0..=0xCF if !matches!(marker, DHT | 0xC8 | 0xCC) => {
+                if frame.is_some() {
+                    return Err(decoding("more than one frame header"));
+                }
+                let parsed = Frame::parse(segment)?;
+                if !matches!(marker, SOF0 | SOF1) || parsed.precision != 12 {
+                    return Err(decoding("frame header differs from the first one read"));
+                }
+                parsed.check_supported()?;
+                let samples = usize::from(parsed.width)
+                    * usize::from(parsed.height)
+                    * parsed.components.len();
+                if samples != out.len() {
+                    return Err(decoding("frame header differs from the first one read"));
+                }
+                planes = parsed.planes(limits)?;
+                frame = Some(parsed);
+            }
+            SOS => {
+                let frame = frame
+                    .as_ref()
+                    .ok_or_else(|| decoding("scan before frame header"))?;
+                let scan = Scan::parse(segment, frame)?;
+                let mut reader = BitReader::new(&data[parser.pos..]);
+                let tables = Tables {
+                    quantization: &quantization,
+                    dc: &dc_tables,
+                    ac: &ac_tables,
+                };
+                decode_scan(
+                    &mut reader,
+                    frame,
+                    &scan,
+                    &tables,
+                    restart_interval,
+                    &mut planes,
+                )?;
+                parser.pos += reader.pos;
+                parser.skip_entropy_coded_data();
+            }
+            _ => {}
+        }
+    }
+
+    let frame = frame.ok_or_else(|| decoding("missing frame header"))?;
+    let ids: Vec<u8> = frame.components.iter().map(|c| c.id).collect();
+    let ycbcr = match adobe_transform {
+        Some(transform) => transform != 0,
+        None => ids != b"RGB",
+    };
+    frame.write_samples(&planes, ycbcr, out);
+    Ok(())
+}
+
+fn decoding(message: &str) -> ImageError {
+    ImageError::Decoding(DecodingError::new(ImageFormat::Jpeg.into(), message))
+}
+
+fn unsupported(message: &str) -> ImageError {
+    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+        ImageFormat::Jpeg.into(),
+        UnsupportedErrorKind::GenericFeature(message.into()),
+    ))
+}
+
+/// Walks the marker segments of an image.
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Read the next marker, skipping any fill bytes.
+    fn marker(&mut self) -> Option<u8> {
+        if self.data.get(self.pos) != Some(&0xFF) {
+            return None;
+        }
+        while self.data.get(self.pos) == Some(&0xFF) {
+            self.pos += 1;
+        }
+        let marker = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(marker)
+    }
+
+    /// Read the contents of a segment following its length.
+    fn segment(&mut self) -> Option<&'a [u8]> {
+        let length = self.data.get(self.pos..self.pos + 2)?;
+        let length = usize::from(u16::from_be_bytes([length[0], length[1]]));
+        let segment = self.data.get(self.pos + 2..self.pos + length.max(2))?;
+        self.pos += length.max(2);
+        Some(segment)
+    }
+
+    /// Move to the next marker that is neither a stuffed byte nor a restart marker.
+    fn skip_entropy_coded_data(&mut self) {
+        while let Some(window) = self.data.get(self.pos..self.pos + 2) {
+            if window[0] == 0xFF && window[1] != 0 && !(RST0..=RST7).contains(&window[1]) {
+                return;
+            }
+            self.pos += 1;
+        }
+        self.pos = self.data.len();
+    }
+}
+
+struct Component {
+    id: u8,
+    h: u8,
+    v: u8,
+    tq: u8,
+}
+
+struct Frame {
+    precision: u8,
+    width: u16,
+    height: u16,
+    components: Vec<Component>,
+}
+
+impl Frame {
+    fn parse(segment: &[u8]) -> ImageResult<Frame> {
+        let header = segment
+            .get(..6)
+            .ok_or_else(|| decoding("truncated frame header"))?;
+        let count = usize::from(header[5]);
+        let specs = segment
+            .get(6..6 + 3 * count)
+            .ok_or_else(|| decoding("truncated frame header"))?;
+        let components = specs
+            .chunks_exact(3)
+            .map(|spec| Component {
+                id: spec[0],
+                h: spec[1] >> 4,
+                v: spec[1] & 0xF,
+                tq: spec[2],
+            })
+            .collect();
+        Ok(Frame {
+            precision: header[0],
+            height: u16::from_be_bytes([header[1], header[2]]),
+            width: u16::from_be_bytes([header[3], header[4]]),
+            components,
+        })
+    }
+
+    fn check_supported(&self) -> ImageResult<()> {
+        if self.width == 0 || self.height == 0 {
+            return Err(unsupported(
+                "JPEG images without a height in the frame header",
+            ));
+        }
+        if !matches!(self.components.len(), 1 | 3) {
+            return Err(unsupported(
+                "12-bit JPEG images are only supported with one or three components",
+            ));
+        }
+        let valid = |c: &Component| (1..=4).contains(&c.h) && (1..=4).contains(&c.v) && c.tq < 4;
+        if !self.components.iter().all(valid) {
+            return Err(decoding("invalid component in frame header"));
+        }
+        Ok(())
+    }
+
+    fn max_sampling(&self) -> (usize, usize) {
+        let h = self.components.iter().map(|c| c.h).max().unwrap_or(1);
+        let v = self.components.iter().map(|c| c.v).max().unwrap_or(1);
+        (usize::from(h), usize::from(v))
+    }
+
+    /// The number of MCUs of an interleaved scan, horizontally and vertically.
+    fn mcus(&self) -> (usize, usize) {
+        let (hmax, vmax) = self.max_sampling();
+        (
+            div_ceil(usize::from(self.width), 8 * hmax),
+            div_ceil(usize::from(self.height), 8 * vmax),
+        )
+    }
+
+    /// The number of blocks covering a component by itself, horizontally and vertically.
+    fn component_blocks(&self, component: &Component) -> (usize, usize) {
+        let (hmax, vmax) = self.max_sampling();
+        let width = div_ceil(usize::from(self.width) * usize::from(component.h), hmax);
+        let height = div_ceil(usize::from(self.height) * usize::from(component.v), vmax);
+        (div_ceil(width, 8), div_ceil(height, 8))
+    }
+
+    /// Allocate the sample planes of all components, covering all MCUs.
+    fn planes(&self, limits: &mut Limits) -> ImageResult<Vec<Plane>> {
+        let (mcus_x, mcus_y) = self.mcus();
+        self.components
+            .iter()
+            .map(|c| {
+                let width = mcus_x * usize::from(c.h) * 8;
+                let height = mcus_y * usize::from(c.v) * 8;
+                // Two bytes for every sample.
+                limits.reserve_usize(2 * width * height)?;
+                Ok(Plane {
+                    width,
+                    samples: vec![0; width * height],
+                    prediction: 0,
+                })
+            })
+            .collect()
+    }
+
+    fn write_samples(&self, planes: &[Plane], ycbcr: bool, out: &mut [u16]) {
+        let (hmax, vmax) = self.max_sampling();
+        let width = usize::from(self.width);
+        let channels = self.components.len();
+        let scale = |value: f32| {
+            let value = value.round().clamp(0.0, 4095.0) as u16;
+            value << 4 | value >> 8
+        };
+
+        for (index, pixel) in out.chunks_exact_mut(channels).enumerate() {
+            let (x, y) = (index % width, index / width);
+            let mut samples = [0.0f32; 3];
+            for ((sample, component), plane) in samples.iter_mut().zip(&self.components).zip(planes)
+            {
+                let cx = x * usize::from(component.h) / hmax;
+                let cy = y * usize::from(component.v) / vmax;
+                *sample = f32::from(plane.samples[cy * plane.width + cx]);
+            }
+
+            if channels == 3 && ycbcr {
+                let [luma, cb, cr] = samples;
+                let (cb, cr) = (cb - 2048.0, cr - 2048.0);
+                samples = [
+                    luma + 1.402 * cr,
+                    luma - 0.344_136 * cb - 0.714_136 * cr,
+                    luma + 1.772 * cb,
+                ];
+            }
+            for (out, &sample) in pixel.iter_mut().zip(&samples) {
+                *out = scale(sample);
+            }
+        }
+    }
+}
+
+/// The decoded samples of one component.
+struct Plane {
+    width: usize,
+    samples: Vec<u16>,
+    prediction: i32,
+}
+
+struct Scan {
+    /// The index in the frame, and the DC and AC table of each component in the scan.
+    components: Vec<(usize, usize, usize)>,
+}
+
+impl Scan {
+    fn parse(segment: &[u8], frame: &Frame) -> ImageResult<Scan> {
+        let count = usize::from(
+            *segment
+                .first()
+                .ok_or_else(|| decoding("empty scan header"))?,
+        );
+        let specs = segment
+            .get(1..1 + 2 * count)
+            .ok_or_else(|| decoding("truncated scan header"))?;
+        let spectral = segment
+            .get(1 + 2 * count..4 + 2 * count)
+            .ok_or_else(|| decoding("truncated scan header"))?;
+        if spectral != [0, 63, 0] {
+            return Err(unsupported(
+                "12-bit JPEG images are only supported with sequential Huffman coding",
+            ));
+        }
+
+        let components = specs
+            .chunks_exact(2)
+            .map(|spec| {
+                let index = frame
+                    .components
+                    .iter()
+                    .position(|c| c.id == spec[0])
+                    .ok_or_else(|| decoding("scan refers to an unknown component"))?;
+                let (dc, ac) = (usize::from(spec[1] >> 4), usize::from(spec[1] & 0xF));
+                if dc >= 4 || ac >= 4 {
+                    return Err(decoding("invalid Huffman table in scan header"));
+                }
+                Ok((index, dc, ac))
+            })
+            .collect::<ImageResult<Vec<_>>>()?;
+        if components.is_empty() {
+            return Err(decoding("scan without components"));
+        }
+        Ok(Scan { components })
+    }
+}
+
+struct Tables<'a> {
+    quantization: &'a [[u16; 64]; 4],
+    dc: &'a [Option<HuffmanTable>; 4],
+    ac: &'a [Option<HuffmanTable>; 4],
+}
+
+fn decode_scan(
+    reader: &mut BitReader,
+    frame: &Frame,
+    scan: &Scan,
+    tables: &Tables,
+    restart_interval: u16,
+    planes: &mut [Plane],
+) -> ImageResult<()> {
+    // A scan of a single component has one block per MCU, in the order of the blocks covering
+    // the component. Otherwise the MCUs cover the whole frame.
+    let single = match scan.components[..] {
+        [(index, _, _)] => Some((index, frame.component_blocks(&frame.components[index]))),
+        _ => None,
+    };
+    let (mcus_x, mcus_y) = match single {
+        Some((_, blocks)) => blocks,
+        None => frame.mcus(),
+    };
+    let basis = idct_basis();
+
+    for plane in planes.iter_mut() {
+        plane.prediction = 0;
+    }
+    // The blocks of the current MCU, as the component index and block coordinates.
+    let mut blocks = Vec::new();
+    for number in 0..mcus_x * mcus_y {
+        if restart_interval > 0 && number > 0 && number % usize::from(restart_interval) == 0 {
+            reader.restart()?;
+            for plane in planes.iter_mut() {
+                plane.prediction = 0;
+            }
+        }
+
+        let (mx, my) = (number % mcus_x, number / mcus_x);
+        blocks.clear();
+        if let Some((index, _)) = single {
+            blocks.push((index, mx, my));
+        } else {
+            for &(index, _, _) in &scan.components {
+                let component = &frame.components[index];
+                let (h, v) = (usize::from(component.h), usize::from(component.v));
+                for (y, x) in (0..v).flat_map(|y| (0..h).map(move |x| (y, x))) {
+                    blocks.push((index, mx * h + x, my * v + y));
+                }
+            }
+        }
+
+        for &(index, bx, by) in &blocks {
+            let &(_, dc, ac) = scan.components.iter().find(|c| c.0 == index).unwrap();
+            let dc = tables.dc[dc]
+                .as_ref()
+                .ok_or_else(|| decoding("missing DC Huffman table"))?;
+            let ac = tables.ac[ac]
+                .as_ref()
+                .ok_or_else(|| decoding("missing AC Huffman table"))?;
+            let quantization = &tables.quantization[usize::from(frame.components[index].tq)];
+
+            let plane = &mut planes[index];
+            let mut coefficients = [0.0f32; 64];
+            let size = dc.decode(reader)?;
+            if size > 15 {
+                return Err(decoding("invalid DC coefficient size"));
+            }
+            plane.prediction = plane.prediction.wrapping_add(reader.receive_extend(size));
+            coefficients[0] = plane.prediction as f32 * f32::from(quantization[0]);
+
+            let mut k = 1;
+            while k < 64 {
+                let symbol = ac.decode(reader)?;
+                let (run, size) = (usize::from(symbol >> 4), symbol & 0xF);
+                if size == 0 {
+                    if run == 15 {
+                        k += 16;
+                        continue;
+                    }
+                    break;
+                }
+                k += run;
+                if k > 63 {
+                    return Err(decoding("too many coefficients in block"));
+                }
+                let value = reader.receive_extend(size) as f32 * f32::from(quantization[k]);
+                coefficients[usize::from(UNZIGZAG[k])] = value;
+                k += 1;
+            }
+
+            let offset = by * 8 * plane.width + bx * 8;
+            inverse_dct(
+                &basis,
+                &coefficients,
+                &mut plane.samples[offset..],
+                plane.width,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The cosine basis of the inverse DCT, indexed by sample and then frequency.
+fn idct_basis() -> [[f32; 8]; 8] {
+    let mut basis = [[0.0f32; 8]; 8];
+    for (x, row) in basis.iter_mut().enumerate() {
+        for (u, value) in row.iter_mut().enumerate() {
+            let scale = if u == 0 { FRAC_1_SQRT_2 } else { 1.0 };
+            *value = scale / 2.0 * ((2 * x + 1) as f32 * u as f32 * PI / 16.0).cos();
+        }
+    }
+    basis
+}
+
+/// Transform a block of dequantized coefficients in natural order to level shifted samples.
+fn inverse_dct(basis: &[[f32; 8]; 8], coefficients: &[f32; 64], out: &mut [u16], stride: usize) {
+    let mut rows = [[0.0f32; 8]; 8];
+    for (v, row) in rows.iter_mut().enumerate() {
+        for (x, value) in row.iter_mut().enumerate() {
+            *value = (0..8).map(|u| basis[x][u] * coefficients[v * 8 + u]).sum();
+        }
+    }
+    for y in 0..8 {
+        for x in 0..8 {
+            let value: f32 = (0..8).map(|v| basis[y][v] * rows[v][x]).sum();
+            out[y * stride + x] = (value + 2048.0).round().clamp(0.0, 4095.0) as u16;
+        }
+    }
+}
+
+fn read_quantization_tables(mut segment: &[u8], tables: &mut [[u16; 64]; 4]) -> ImageResult<()> {
+    while let Some(&info) = segment.first() {
+        let (precision, index) = (info >> 4, usize::from(info & 0xF));
+        let size = if precision == 0 { 64 } else { 128 };
+        let values = segment
+            .get(1..1 + size)
+            .filter(|_| precision <= 1 && index < 4)
+            .ok_or_else(|| decoding("invalid quantization table"))?;
+        for (k, entry) in tables[index].iter_mut().enumerate() {
+            *entry = match precision {
+                0 => u16::from(values[k]),
+                _ => u16::from_be_bytes([values[2 * k], values[2 * k + 1]]),
+            };
+        }
+        segment = &segment[1 + size..];
+    }
+    Ok(())
+}
+
+fn read_huffman_tables(
+    mut segment: &[u8],
+    dc: &mut [Option<HuffmanTable>; 4],
+    ac: &mut [Option<HuffmanTable>; 4],
+) -> ImageResult<()> {
+    while let Some(&info) = segment.first() {
+        let (class, index) = (info >> 4, usize::from(info & 0xF));
+        let counts: [u8; 16] = segment
+            .get(1..17)
+            .and_then(|counts| counts.try_into().ok())
+            .ok_or_else(|| decoding("truncated Huffman table"))?;
+        let total: usize = counts.iter().map(|&c| usize::from(c)).sum();
+        let values = segment
+            .get(17..17 + total)
+            .filter(|_| class <= 1 && index < 4)
+            .ok_or_else(|| decoding("invalid Huffman table"))?;
+
+        let table = HuffmanTable::new(&counts, values.to_vec());
+        if class == 0 {
+            dc[index] = Some(table);
+        } else {
+            ac[index] = Some(table);
+        }
+        segment = &segment[17 + total..];
+    }
+    Ok(())
+}
+
+/// A Huffman table decoded code by code, as in Annex F.2.2.3 of the specification.
+struct HuffmanTable {
+    /// The largest code of each length, or `-1` if there is none.
+    max_code: [i32; 17],
+    /// The difference between the index of the first value and the first code of each length.
+    offset: [i32; 17],
+    values: Vec<u8>,
+}
+
+impl HuffmanTable {
+    fn new(counts: &[u8; 16], values: Vec<u8>) -> Self {
+        let mut max_code = [-1; 17];
+        let mut offset = [0; 17];
+        let (mut code, mut index) = (0i32, 0i32);
+        for (length, &count) in (1..=16).zip(counts) {
+            let count = i32::from(count);
+            if count > 0 {
+                offset[length] = index - code;
+                code += count;
+                index += count;
+                max_code[length] = code - 1;
+            }
+            code <<= 1;
+        }
+        HuffmanTable {
+            max_code,
+            offset,
+            values,
+        }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> ImageResult<u8> {
+        let mut code = 0i32;
+        for length in 1..=16 {
+            code = code << 1 | reader.read_bit() as i32;
+            if code <= self.max_code[length] {
+                let index = (code + self.offset[length]) as usize;
+                return self
+                    .values
+                    .get(index)
+                    .copied()
+                    .ok_or_else(|| decoding("invalid Huffman code"));
+            }
+        }
+        Err(decoding("invalid Huffman code"))
+    }
+}
+
+/// Reads the entropy coded data of a scan, removing stuffed bytes.
+///
+/// Once a marker is reached, the reader stays in front of it and yields zero bits.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    byte: u8,
+    bits: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            pos: 0,
+            byte: 0,
+            bits: 0,
+        }
+    }
+
+    fn read_bit(&mut self) -> u32 {
+        if self.bits == 0 {
+            self.byte = match self.data.get(self.pos..self.pos + 2) {
+                Some([0xFF, 0x00]) => {
+                    self.pos += 2;
+                    0xFF
+                }
+                Some([0xFF, _]) => 0,
+                _ => match self.data.get(self.pos) {
+                    Some(&byte) if byte != 0xFF => {
+                        self.pos += 1;
+                        byte
+                    }
+                    _ => 0,
+                },
+            };
+            self.bits = 8;
+        }
+        self.bits -= 1;
+        u32::from(self.byte >> self.bits & 1)
+    }
+
+    /// Read `size` bits and extend them to a signed value, as in Figure F.12.
+    fn receive_extend(&mut self, size: u8) -> i32 {
+        if size == 0 {
+            return 0;
+        }
+        let mut value = 0i32;
+        for _ in 0..size {
+            value = value << 1 | self.read_bit() as i32;
+        }
+        if value < 1 << (size - 1) {
+            value - (1 << size) + 1
+        } else {
+            value
+        }
+    }
+
+    /// Discard the remaining bits of the current byte and skip the expected restart marker.
+    fn restart(&mut self) -> ImageResult<()> {
+        self.bits = 0;
+        match self.data.get(self.pos..self.pos + 2) {
+            Some(&[0xFF, marker]) if (RST0..=RST7).contains(&marker) => {
+                self.pos += 2;
+                Ok(())
+            }
+            _ => Err(decoding("missing restart marker")),
+        }
+    }
+}
+
+fn div_ceil(a: usize, b: usize) -> usize {
+    (a + b - 1) / b
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::codecs::jpeg::JpegDecoder;
+    use crate::{ColorType, DynamicImage, ImageDecoder};
+
+    /// Writes the entropy coded data of a scan, stuffing a zero after every `0xFF`.
+    struct BitWriter {
+        out: Vec<u8>,
+        accumulator: u32,
+        bits: u8,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, value: u32, size: u8) {
+            for bit in (0..size).rev() {
+                self.accumulator = self.accumulator << 1 | (value >> bit & 1);
+                self.bits += 1;
+                if self.bits == 8 {
+                    self.out.push(self.accumulator as u8);
+                    if self.accumulator as u8 == 0xFF {
+                        self.out.push(0);
+                    }
+                    self.accumulator = 0;
+                    self.bits = 0;
+                }
+            }
+        }
+
+        fn flush(&mut self) {
+            if self.bits > 0 {
+                self.write(0x7F, 8 - self.bits);
+            }
+        }
+
+        fn coefficient(&mut self, table: &[u8], symbol_base: u8, value: i32) {
+            let size = (32 - value.unsigned_abs().leading_zeros()) as u8;
+            let symbol = symbol_base | size;
+            let code = table.iter().position(|&s| s == symbol).unwrap();
+            self.write(code as u32, if table.len() > 16 { 8 } else { 5 });
+            let bits = if value < 0 { value - 1 } else { value };
+            self.write(bits as u32 & ((1 << size) - 1), size);
+        }
+    }
+
+    fn segment(out: &mut Vec<u8>, marker: u8, data: &[u8]) {
+        out.extend([0xFF, marker]);
+        out.extend((data.len() as u16 + 2).to_be_bytes());
+        out.extend(data);
+    }
+
+    /// Encode 12-bit planes as an interleaved extended sequential JPEG without subsampling.
+    ///
+    /// All quantizers are one, so the only loss is the rounding of the DCT coefficients.
+    fn encode(width: u16, height: u16, ids: &[u8], planes: &[Vec<u16>], restart: u16) -> Vec<u8> {
+        // All codes of a table have the same length, such that the code of a symbol is its index.
+        let dc_symbols: Vec<u8> = (0..16).collect();
+        let mut ac_symbols = vec![0x00, 0xF0];
+        ac_symbols.extend((0..16).flat_map(|run| (1..15).map(move |size| run << 4 | size)));
+
+        let mut out = vec![0xFF, SOI];
+        let mut dqt = vec![0x10];
+        dqt.extend([0, 1].repeat(64));
+        segment(&mut out, DQT, &dqt);
+        let mut dht = vec![0x00, 0, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        dht.extend(&dc_symbols);
+        dht.extend([0x10, 0, 0, 0, 0, 0, 0, 0, ac_symbols.len() as u8]);
+        dht.extend([0; 8]);
+        dht.extend(&ac_symbols);
+        segment(&mut out, DHT, &dht);
+        segment(&mut out, DRI, &restart.to_be_bytes());
+
+        let mut sof = vec![12];
+        sof.extend(height.to_be_bytes());
+        sof.extend(width.to_be_bytes());
+        sof.push(ids.len() as u8);
+        let mut sos = vec![ids.len() as u8];
+        for &id in ids {
+            sof.extend([id, 0x11, 0]);
+            sos.extend([id, 0x00]);
+        }
+        sos.extend([0, 63, 0]);
+        segment(&mut out, SOF1, &sof);
+        segment(&mut out, SOS, &sos);
+
+        let (width, height) = (usize::from(width), usize::from(height));
+        let (mcus_x, mcus_y) = (div_ceil(width, 8), div_ceil(height, 8));
+        let mut writer = BitWriter {
+            out,
+            accumulator: 0,
+            bits: 0,
+        };
+        let mut predictions = vec![0; planes.len()];
+        for mcu in 0..mcus_x * mcus_y {
+            if restart > 0 && mcu > 0 && mcu % usize::from(restart) == 0 {
+                writer.flush();
+                let marker = RST0 + ((mcu / usize::from(restart) - 1) % 8) as u8;
+                writer.out.extend([0xFF, marker]);
+                predictions.iter_mut().for_each(|p| *p = 0);
+            }
+            let (bx, by) = (mcu % mcus_x * 8, mcu / mcus_x * 8);
+            for (plane, prediction) in planes.iter().zip(&mut predictions) {
+                // Replicate the last row and column into the padding of partial blocks.
+                let sample = |x: usize, y: usize| {
+                    let (x, y) = ((bx + x).min(width - 1), (by + y).min(height - 1));
+                    f32::from(plane[y * width + x]) - 2048.0
+                };
+                let mut block = [0i32; 64];
+                for (index, coefficient) in block.iter_mut().enumerate() {
+                    let (v, u) = (index / 8, index % 8);
+                    let mut sum = 0.0;
+                    for y in 0..8 {
+                        for x in 0..8 {
+                            let cu = ((2 * x + 1) as f32 * u as f32 * PI / 16.0).cos();
+                            let cv = ((2 * y + 1) as f32 * v as f32 * PI / 16.0).cos();
+                            sum += sample(x, y) * cu * cv;
+                        }
+                    }
+                    let cu = if u == 0 { FRAC_1_SQRT_2 } else { 1.0 };
+                    let cv = if v == 0 { FRAC_1_SQRT_2 } else { 1.0 };
+                    *coefficient = (sum * cu * cv / 4.0).round() as i32;
+                }
+
+                writer.coefficient(&dc_symbols, 0, block[0] - *prediction);
+                *prediction = block[0];
+                let mut run = 0;
+                for &k in &UNZIGZAG[1..] {
+                    let value = block[usize::from(k)];
+                    if value == 0 {
+                        run += 1;
+                        continue;
+                    }
+                    while run > 15 {
+                        writer.write(1, 8);
+                        run -= 16;
+                    }
+                    writer.coefficient(&ac_symbols, run << 4, value);
+                    run = 0;
+                }
+                if run > 0 {
+                    writer.write(0, 8);
+                }
+            }
+        }
+        writer.flush();
+        let mut out = writer.out;
+        out.extend([0xFF, EOI]);
+        out
+    }
+
+    fn decode_16(jpeg: &[u8]) -> (ColorType, Vec<u16>) {
+        let decoder = JpegDecoder::new(Cursor::new(jpeg)).unwrap();
+        let color = decoder.color_type();
+        let image = DynamicImage::from_decoder(decoder).unwrap();
+        let samples = match image {
+            DynamicImage::ImageLuma16(image) => image.into_raw(),
+            DynamicImage::ImageRgb16(image) => image.into_raw(),
+            _ => panic!("expected a 16-bit image"),
+        };
+        (color, samples)
+    }
+
+    #[test]
+    fn twelve_bit_grayscale() {
+        let (width, height) = (20u16, 12u16);
+        let plane: Vec<u16> = (0..width * height)
+            .map(|i| (i % width) * 190 + (i / width) * 25 + 100)
+            .collect();
+        let jpeg = encode(width, height, &[1], std::slice::from_ref(&plane), 2);
+
+        let decoder = JpegDecoder::new(Cursor::new(&jpeg)).unwrap();
+        assert_eq!(decoder.dimensions(), (20, 12));
+        let (color, decoded) = decode_16(&jpeg);
+        assert_eq!(color, ColorType::L16);
+
+        for (&expected, &sample) in plane.iter().zip(&decoded) {
+            // Samples are scaled by repeating their top bits.
+            assert_eq!(sample & 0xF, sample >> 12);
+            let value = sample >> 4;
+            assert!(value.abs_diff(expected) <= 2, "{value} != {expected}");
+        }
+        // The full precision survives, including values well above 8 bits.
+        assert!(plane.iter().any(|&v| v > 0xF00));
+        assert!(decoded.iter().any(|&v| v >> 4 > 0xF00));
+    }
+
+    #[test]
+    fn twelve_bit_color() {
+        let (width, height) = (9u16, 9u16);
+        let luma: Vec<u16> = (0..81).map(|i| 1000 + (i % 9) * 50).collect();
+        let cb = vec![2048; 81];
+        let cr = vec![2548; 81];
+        let jpeg = encode(width, height, &[1, 2, 3], &[luma.clone(), cb, cr], 0);
+
+        let (color, decoded) = decode_16(&jpeg);
+        assert_eq!(color, ColorType::Rgb16);
+        for (pixel, &y) in decoded.chunks_exact(3).zip(&luma) {
+            let y = f32::from(y);
+            let expected = [y + 701.0, y - 357.07, y];
+            for (&sample, expected) in pixel.iter().zip(expected) {
+                let value = f32::from(sample >> 4);
+                assert!((value - expected).abs() <= 2.0, "{value} != {expected}");
+            }
+        }
+
+        // With the components marked as RGB, they are not converted.
+        let jpeg = encode(
+            width,
+            height,
+            b"RGB",
+            &[luma.clone(), luma.clone(), luma],
+            3,
+        );
+        let (_, decoded) = decode_16(&jpeg);
+        assert!(decoded
+            .chunks_exact(3)
+            .all(|p| p[0] == p[1] && p[1] == p[2]));
+    }
+
+    #[test]
+    fn second_frame_header() {
+        let plane = vec![3000; 16 * 16];
+        let mut jpeg = encode(16, 16, &[1], &[plane], 0);
+
+        // Another, smaller frame after the scan would no longer match the output.
+        jpeg.truncate(jpeg.len() - 2);
+        segment(&mut jpeg, SOF1, &[12, 0, 8, 0, 8, 1, 1, 0x11, 0]);
+        jpeg.extend([0xFF, EOI]);
+
+        let mut out = vec![0; 16 * 16];
+        let error = decode(&jpeg, &mut out, &mut Limits::no_limits()).unwrap_err();
+        assert!(matches!(error, ImageError::Decoding(_)), "{error:?}");
+    }
+
+    #[test]
+    fn planes_count_against_limits() {
+        let plane = vec![3000; 16 * 16];
+        let jpeg = encode(16, 16, &[1], &[plane], 0);
+
+        let mut limits = Limits::no_limits();
+        limits.max_alloc = Some(511);
+        let error = decode(&jpeg, &mut [0; 16 * 16], &mut limits).unwrap_err();
+        assert!(matches!(error, ImageError::Limits(_)), "{error:?}");
+
+        let mut decoder = JpegDecoder::new(Cursor::new(&jpeg)).unwrap();
+        limits.max_alloc = Some(1023);
+        decoder.set_limits(limits).unwrap();
+        assert!(DynamicImage::from_decoder(decoder).is_err());
+    }
+}
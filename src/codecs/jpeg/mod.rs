@@ -1,7 +1,8 @@
 //! Decoding and Encoding of JPEG Images
 //!
 //! JPEG (Joint Photographic Experts Group) is an image format that supports lossy compression.
-//! This module implements the Baseline JPEG standard.
+//! This module implements the Baseline JPEG standard. Images with 12-bit samples are decoded to
+//! 16 bits per channel.
 //!
 //! # Related Links
 //! * <http://www.w3.org/Graphics/JPEG/itu-t81.pdf> - The JPEG specification
@@ -13,4 +14,5 @@ pub use self::encoder::{JpegEncoder, PixelDensity, PixelDensityUnit};
 mod decoder;
 mod encoder;
 mod entropy;
+mod extended;
 mod transform;
@@ -22,7 +22,7 @@
 //!     - (chroma) subsampling not supported yet by the exr library
 use exr::prelude::*;
 
-use crate::error::{DecodingError, EncodingError, ImageFormatHint};
+use crate::error::{DecodingError, ImageFormatHint};
 use crate::{
     ColorType, ExtendedColorType, ImageDecoder, ImageEncoder, ImageError, ImageFormat, ImageResult,
 };
@@ -271,13 +271,10 @@ fn write_buffer(
 
         // TODO other color types and channel types
         unsupported_color_type => {
-            return Err(ImageError::Encoding(EncodingError::new(
-                ImageFormatHint::Exact(ImageFormat::OpenExr),
-                format!(
-                    "writing color type {:?} not yet supported",
-                    unsupported_color_type
-                ),
-            )))
+            return Err(ImageError::unsupported_color(
+                ImageFormat::OpenExr,
+                unsupported_color_type,
+            ))
         }
     }
 
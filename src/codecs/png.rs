@@ -15,7 +15,7 @@ use crate::animation::{Delay, Frame, Frames, Ratio};
 use crate::color::{Blend, ColorType, ExtendedColorType};
 use crate::error::{
     DecodingError, EncodingError, ImageError, ImageResult, LimitError, LimitErrorKind,
-    ParameterError, ParameterErrorKind, UnsupportedError, UnsupportedErrorKind,
+    ParameterError, ParameterErrorKind,
 };
 use crate::image::{AnimationDecoder, ImageDecoder, ImageEncoder, ImageFormat};
 use crate::io::Limits;
@@ -40,11 +40,26 @@ impl<R: BufRead + Seek> PngDecoder<R> {
 
     /// Creates a new decoder that decodes from the stream ```r``` with the given limits.
     pub fn with_limits(r: R, limits: Limits) -> ImageResult<PngDecoder<R>> {
+        Self::with_limits_and_checksums(r, limits, true)
+    }
+
+    /// Creates a new decoder with the given limits, which may skip verifying chunk checksums.
+    ///
+    /// When `verify_checksums` is `true`, which is the default for the other constructors, the
+    /// CRC-32 of every chunk is validated and a mismatch is reported as a decoding error naming
+    /// the offending chunk. Passing `false` skips this validation, which allows recovering data
+    /// from files with damaged checksums at the risk of decoding garbage.
+    pub fn with_limits_and_checksums(
+        r: R,
+        limits: Limits,
+        verify_checksums: bool,
+    ) -> ImageResult<PngDecoder<R>> {
         limits.check_support(&crate::io::LimitSupport::default())?;
 
         let max_bytes = usize::try_from(limits.max_alloc.unwrap_or(u64::MAX)).unwrap_or(usize::MAX);
         let mut decoder = png::Decoder::new_with_limits(r, png::Limits { bytes: max_bytes });
         decoder.set_ignore_text_chunk(true);
+        decoder.ignore_checksums(!verify_checksums);
 
         let info = decoder.read_header_info().map_err(ImageError::from_png)?;
         limits.check_dimensions(info.width, info.height)?;
@@ -146,6 +161,66 @@ impl<R: BufRead + Seek> PngDecoder<R> {
         Ok(ApngDecoder::new(self))
     }
 
+    /// Decode the image like [`read_image`](ImageDecoder::read_image), recovering the rows which
+    /// precede truncated or corrupt image data.
+    ///
+    /// Rows which could not be decoded are filled with `fill`, converted to the color type of
+    /// the image. Returns the number of decoded rows if the image was incomplete, or `None` if it
+    /// was decoded in full. Interlaced images spread each row over the whole stream, so they are
+    /// only decoded when complete and otherwise fail as with `read_image`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf.len() != self.total_bytes()`.
+    pub fn read_image_best_effort(
+        mut self,
+        buf: &mut [u8],
+        fill: Rgba<u8>,
+    ) -> ImageResult<Option<u32>> {
+        if self.reader.info().interlaced {
+            return self.read_image(buf).map(|()| None);
+        }
+
+        assert_eq!(u64::try_from(buf.len()), Ok(self.total_bytes()));
+        let (width, height) = self.dimensions();
+        let line_size = self.reader.output_line_size(width);
+
+        let mut decoded = 0;
+        for line in buf.chunks_exact_mut(line_size) {
+            match self.reader.next_row() {
+                Ok(Some(row)) => line.copy_from_slice(row.data()),
+                Ok(None) => break,
+                Err(png::DecodingError::IoError(_) | png::DecodingError::Format(_)) => break,
+                Err(err) => return Err(ImageError::from_png(err)),
+            }
+            decoded += 1;
+        }
+
+        let (decoded_bytes, missing_bytes) = buf.split_at_mut(decoded as usize * line_size);
+        to_native_endian(self.color_type, decoded_bytes);
+        if decoded == height {
+            return Ok(None);
+        }
+
+        let fill = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, fill));
+        let fill = match self.color_type {
+            ColorType::L8 => fill.to_luma8().into(),
+            ColorType::La8 => fill.to_luma_alpha8().into(),
+            ColorType::Rgb8 => fill.to_rgb8().into(),
+            ColorType::L16 => fill.to_luma16().into(),
+            ColorType::La16 => fill.to_luma_alpha16().into(),
+            ColorType::Rgb16 => fill.to_rgb16().into(),
+            ColorType::Rgba16 => fill.to_rgba16().into(),
+            _ => fill,
+        }
+        .into_bytes();
+        for pixel in missing_bytes.chunks_exact_mut(fill.len()) {
+            pixel.copy_from_slice(&fill);
+        }
+
+        Ok(Some(decoded))
+    }
+
     /// Returns if the image contains an animation.
     ///
     /// Note that the file itself decides if the default image is considered to be part of the
@@ -157,11 +232,26 @@ impl<R: BufRead + Seek> PngDecoder<R> {
     }
 }
 
+/// PNG images are big endian. For 16 bit per channel and larger types, the buffer may need to be
+/// reordered to native endianness per the contract of `read_image`.
+fn to_native_endian(color_type: ColorType, buf: &mut [u8]) {
+    use byteorder::{BigEndian, ByteOrder, NativeEndian};
+
+    // TODO: assumes equal channel bit depth.
+    let bpc = color_type.bytes_per_pixel() / color_type.channel_count();
+
+    match bpc {
+        1 => (), // No reodering necessary for u8
+        2 => buf.chunks_exact_mut(2).for_each(|c| {
+            let v = BigEndian::read_u16(c);
+            NativeEndian::write_u16(c, v)
+        }),
+        _ => unreachable!(),
+    }
+}
+
 fn unsupported_color(ect: ExtendedColorType) -> ImageError {
-    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
-        ImageFormat::Png.into(),
-        UnsupportedErrorKind::Color(ect),
-    ))
+    ImageError::unsupported_color(ImageFormat::Png, ect)
 }
 
 impl<R: BufRead + Seek> ImageDecoder for PngDecoder<R> {
@@ -178,24 +268,9 @@ impl<R: BufRead + Seek> ImageDecoder for PngDecoder<R> {
     }
 
     fn read_image(mut self, buf: &mut [u8]) -> ImageResult<()> {
-        use byteorder::{BigEndian, ByteOrder, NativeEndian};
-
         assert_eq!(u64::try_from(buf.len()), Ok(self.total_bytes()));
         self.reader.next_frame(buf).map_err(ImageError::from_png)?;
-        // PNG images are big endian. For 16 bit per channel and larger types,
-        // the buffer may need to be reordered to native endianness per the
-        // contract of `read_image`.
-        // TODO: assumes equal channel bit depth.
-        let bpc = self.color_type().bytes_per_pixel() / self.color_type().channel_count();
-
-        match bpc {
-            1 => (), // No reodering necessary for u8
-            2 => buf.chunks_exact_mut(2).for_each(|c| {
-                let v = BigEndian::read_u16(c);
-                NativeEndian::write_u16(c, v)
-            }),
-            _ => unreachable!(),
-        }
+        to_native_endian(self.color_type, buf);
         Ok(())
     }
 
@@ -459,6 +534,11 @@ pub struct PngEncoder<W: Write> {
 }
 
 /// Compression level of a PNG encoder. The default setting is `Fast`.
+///
+/// `Fast` and levels up to `2` are compressed with `fdeflate`, all other settings with `flate2`.
+/// `flate2` uses the pure Rust `miniz_oxide` backend by default. A faster backend such as
+/// `zlib-ng` is selected by enabling the corresponding feature of `flate2` in the dependent
+/// crate, e.g. `flate2 = { version = "1", features = ["zlib-ng"] }`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[non_exhaustive]
 #[derive(Default)]
@@ -470,6 +550,11 @@ pub enum CompressionType {
     Fast,
     /// High compression level
     Best,
+    /// Numeric deflate level from `0` (fastest) to `9` (smallest output)
+    ///
+    /// Levels above `9` are treated as `9`. The backend currently supports fewer distinct
+    /// settings, so neighbouring levels may produce identical output.
+    Level(u8),
 }
 
 /// Filter algorithms used to process image data to improve compression.
@@ -492,6 +577,9 @@ pub enum FilterType {
     Paeth,
     /// Uses a heuristic to select one of the preceding filters for each
     /// scanline rather than one filter for the entire image
+    ///
+    /// Each scanline is filtered with every filter type and the one with the
+    /// minimum sum of absolute differences is kept.
     #[default]
     Adaptive,
 }
@@ -536,6 +624,18 @@ impl<W: Write> PngEncoder<W> {
         }
     }
 
+    /// Set the compression level used by this encoder.
+    pub fn with_compression(mut self, compression: CompressionType) -> PngEncoder<W> {
+        self.compression = compression;
+        self
+    }
+
+    /// Set the filter type used by this encoder.
+    pub fn with_filter(mut self, filter: FilterType) -> PngEncoder<W> {
+        self.filter = filter;
+        self
+    }
+
     fn encode_inner(
         self,
         data: &[u8],
@@ -552,19 +652,26 @@ impl<W: Write> PngEncoder<W> {
             ExtendedColorType::Rgb16 => (png::ColorType::Rgb, png::BitDepth::Sixteen),
             ExtendedColorType::Rgba8 => (png::ColorType::Rgba, png::BitDepth::Eight),
             ExtendedColorType::Rgba16 => (png::ColorType::Rgba, png::BitDepth::Sixteen),
-            _ => {
-                return Err(ImageError::Unsupported(
-                    UnsupportedError::from_format_and_kind(
-                        ImageFormat::Png.into(),
-                        UnsupportedErrorKind::Color(color),
-                    ),
-                ))
-            }
+            _ => return Err(ImageError::unsupported_color(ImageFormat::Png, color)),
         };
+        self.write_png(data, width, height, ct, bits, None)
+    }
+
+    fn write_png(
+        self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        ct: png::ColorType,
+        bits: png::BitDepth,
+        palette: Option<Vec<u8>>,
+    ) -> ImageResult<()> {
         let comp = match self.compression {
             CompressionType::Default => png::Compression::Default,
             CompressionType::Best => png::Compression::Best,
-            _ => png::Compression::Fast,
+            CompressionType::Fast | CompressionType::Level(0..=2) => png::Compression::Fast,
+            CompressionType::Level(3..=6) => png::Compression::Default,
+            CompressionType::Level(_) => png::Compression::Best,
         };
         let (filter, adaptive_filter) = match self.filter {
             FilterType::NoFilter => (
@@ -581,6 +688,9 @@ impl<W: Write> PngEncoder<W> {
         let mut encoder = png::Encoder::new(self.w, width, height);
         encoder.set_color(ct);
         encoder.set_depth(bits);
+        if let Some(palette) = palette {
+            encoder.set_palette(palette);
+        }
         encoder.set_compression(comp);
         encoder.set_filter(filter);
         encoder.set_adaptive_filter(adaptive_filter);
@@ -598,7 +708,7 @@ impl<W: Write> ImageEncoder for PngEncoder<W> {
     ///
     /// For color types with 16-bit per channel or larger, the contents of `buf` should be in
     /// native endian. PngEncoder will automatically convert to big endian as required by the
-    /// underlying PNG format.
+    /// underlying PNG format. `Bgr8` and `Bgra8` data is stored as RGB and RGBA.
     #[track_caller]
     fn write_image(
         self,
@@ -618,6 +728,10 @@ impl<W: Write> ImageEncoder for PngEncoder<W> {
             buf.len(),
         );
 
+        if let Some((rgb, color_type)) = color_type.swizzle_bgr(buf) {
+            return self.write_image(&rgb, width, height, color_type);
+        }
+
         // PNG images are big endian. For 16 bit per channel and larger types,
         // the buffer may need to be reordered to big endian per the
         // contract of `write_image`.
@@ -643,6 +757,158 @@ impl<W: Write> ImageEncoder for PngEncoder<W> {
             ))),
         }
     }
+
+    /// Write a paletted PNG image with 8 bits per index.
+    #[track_caller]
+    fn write_indexed_image(
+        self,
+        indices: &[u8],
+        width: u32,
+        height: u32,
+        palette: &[Rgb<u8>],
+    ) -> ImageResult<()> {
+        crate::image::check_indexed_image(indices, width, height, palette)?;
+        let palette = palette.iter().flat_map(|color| color.0).collect();
+        self.write_png(
+            indices,
+            width,
+            height,
+            png::ColorType::Indexed,
+            png::BitDepth::Eight,
+            Some(palette),
+        )
+    }
+}
+
+/// Animated PNG (APNG) encoder.
+///
+/// Frames are buffered in memory, the file is written by [`finish`]. Every frame is disposed to
+/// transparent black before the next one is drawn. The first frame is placed on the full canvas
+/// since it doubles as the default image shown by decoders without APNG support.
+///
+/// [`finish`]: ApngEncoder::finish
+pub struct ApngEncoder<W: Write> {
+    w: W,
+    width: u32,
+    height: u32,
+    frames: Vec<Frame>,
+}
+
+impl<W: Write> ApngEncoder<W> {
+    /// Create a new encoder for an animation with a canvas of `width` x `height` pixels.
+    ///
+    /// The animation loops forever.
+    pub fn new(w: W, width: u32, height: u32) -> Self {
+        ApngEncoder {
+            w,
+            width,
+            height,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Encode one frame of animation.
+    ///
+    /// The frame must lie within the canvas.
+    pub fn encode_frame(&mut self, frame: Frame) -> ImageResult<()> {
+        let (width, height) = frame.buffer().dimensions();
+        let fits = |offset: u32, length: u32, canvas: u32| {
+            length > 0
+                && offset
+                    .checked_add(length)
+                    .map_or(false, |end| end <= canvas)
+        };
+        if !fits(frame.left(), width, self.width) || !fits(frame.top(), height, self.height) {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            )));
+        }
+
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    /// Encodes Frames.
+    pub fn encode_frames<F>(&mut self, frames: F) -> ImageResult<()>
+    where
+        F: IntoIterator<Item = Frame>,
+    {
+        for frame in frames {
+            self.encode_frame(frame)?;
+        }
+        Ok(())
+    }
+
+    /// Write the animation, consisting of all frames encoded so far.
+    pub fn finish(self) -> ImageResult<()> {
+        if self.frames.is_empty() {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::Generic("an animation needs at least one frame".into()),
+            )));
+        }
+        let num_frames = u32::try_from(self.frames.len()).map_err(|_| {
+            ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            ))
+        })?;
+
+        let mut encoder = png::Encoder::new(self.w, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(num_frames, 0)
+            .map_err(|e| ImageError::IoError(e.into()))?;
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| ImageError::IoError(e.into()))?;
+
+        for (index, frame) in self.frames.into_iter().enumerate() {
+            let (left, top) = (frame.left(), frame.top());
+            let (delay_num, delay_den) = apng_delay(frame.delay());
+            let mut buffer = frame.into_straight_alpha().into_buffer();
+            if index == 0 && buffer.dimensions() != (self.width, self.height) {
+                let mut canvas = RgbaImage::new(self.width, self.height);
+                canvas.copy_from(&buffer, left, top)?;
+                buffer = canvas;
+            }
+            let (left, top) = if index == 0 { (0, 0) } else { (left, top) };
+            let (width, height) = buffer.dimensions();
+
+            // The position and dimension are validated against each other, so move the previous
+            // frame region to the origin first.
+            (|| {
+                writer.set_frame_position(0, 0)?;
+                writer.set_frame_dimension(width, height)?;
+                writer.set_frame_position(left, top)?;
+                writer.set_frame_delay(delay_num, delay_den)?;
+                writer.set_dispose_op(DisposeOp::Background)?;
+                writer.set_blend_op(BlendOp::Source)?;
+                writer.write_image_data(buffer.as_raw())
+            })()
+            .map_err(|e| ImageError::IoError(e.into()))?;
+        }
+
+        writer.finish().map_err(|e| ImageError::IoError(e.into()))
+    }
+}
+
+/// Convert a delay to a fraction of seconds with 16-bit numerator and denominator.
+fn apng_delay(delay: Delay) -> (u16, u16) {
+    let (numer, denom) = delay.numer_denom_ms();
+    if let (Ok(numer), Some(Ok(denom))) = (
+        u16::try_from(numer),
+        denom.checked_mul(1000).map(u16::try_from),
+    ) {
+        return (numer, denom);
+    }
+
+    // Round to milliseconds, or to hundredths of a second for long delays.
+    let millis = (f64::from(numer) / f64::from(denom)).round();
+    if millis <= f64::from(u16::MAX) {
+        (millis as u16, 1000)
+    } else {
+        ((millis / 10.0).round().min(f64::from(u16::MAX)) as u16, 100)
+    }
 }
 
 impl ImageError {
@@ -687,6 +953,137 @@ mod tests {
     use super::*;
     use std::io::{BufReader, Cursor, Read};
 
+    #[cfg(feature = "jpeg")]
+    fn encoded_len(image: &crate::RgbImage, filter: FilterType) -> usize {
+        let mut buf = Vec::new();
+        PngEncoder::new(&mut buf)
+            .with_compression(CompressionType::Level(6))
+            .with_filter(filter)
+            .write_image(
+                image.as_raw(),
+                image.width(),
+                image.height(),
+                ExtendedColorType::Rgb8,
+            )
+            .unwrap();
+        buf.len()
+    }
+
+    #[test]
+    #[cfg(feature = "jpeg")]
+    fn adaptive_filter_beats_no_filter() {
+        let image = crate::open("tests/images/jpg/progressive/cat.jpg")
+            .unwrap()
+            .into_rgb8();
+
+        let adaptive = encoded_len(&image, FilterType::Adaptive);
+        let unfiltered = encoded_len(&image, FilterType::NoFilter);
+        assert!(
+            adaptive < unfiltered,
+            "adaptive: {adaptive}, no filter: {unfiltered}"
+        );
+    }
+
+    #[test]
+    fn best_effort_truncated() {
+        // Incompressible data without filtering, so that the stream maps evenly to rows.
+        let image = crate::RgbImage::from_fn(32, 16, |x, y| {
+            let v = (x * 31 + y * 17).wrapping_mul(2_654_435_761) >> 7;
+            Rgb([v as u8, (v >> 8) as u8, (v >> 16) as u8])
+        });
+        let mut buf = Vec::new();
+        PngEncoder::new(&mut buf)
+            .with_filter(FilterType::NoFilter)
+            .write_image(image.as_raw(), 32, 16, ExtendedColorType::Rgb8)
+            .unwrap();
+        buf.truncate(buf.len() / 2);
+
+        let fill = Rgba([10, 20, 30, 255]);
+        let decoder = PngDecoder::new(Cursor::new(&buf)).unwrap();
+        let mut decoded = vec![0; decoder.total_bytes() as usize];
+        let rows = decoder
+            .read_image_best_effort(&mut decoded, fill)
+            .unwrap()
+            .expect("the image is truncated");
+        assert!((4..12).contains(&rows), "decoded {rows} rows");
+
+        let decoded = crate::RgbImage::from_raw(32, 16, decoded).unwrap();
+        for (x, y, pixel) in decoded.enumerate_pixels() {
+            if y < rows {
+                assert_eq!(pixel, image.get_pixel(x, y));
+            } else {
+                assert_eq!(pixel, &Rgb([10, 20, 30]));
+            }
+        }
+
+        let decoder = PngDecoder::new(Cursor::new(&buf)).unwrap();
+        let mut decoded = vec![0; decoder.total_bytes() as usize];
+        assert!(decoder.read_image(&mut decoded).is_err());
+    }
+
+    #[test]
+    fn compression_levels_roundtrip() {
+        let data: Vec<u8> = (0..16 * 16 * 3).map(|i| (i % 251) as u8).collect();
+        for level in [0, 5, 9, 42] {
+            let mut buf = Vec::new();
+            PngEncoder::new(&mut buf)
+                .with_compression(CompressionType::Level(level))
+                .write_image(&data, 16, 16, ExtendedColorType::Rgb8)
+                .unwrap();
+
+            let decoder = PngDecoder::new(Cursor::new(buf)).unwrap();
+            let decoded = crate::image::decoder_to_vec::<u8>(decoder).unwrap();
+            assert_eq!(decoded, data, "level {level}");
+        }
+    }
+
+    #[test]
+    fn sixteen_bit_roundtrip() {
+        let image = ImageBuffer::<Luma<u16>, _>::from_fn(16, 4, |x, y| {
+            Luma([0x1234 + (x * 0x0101 + y * 0x1000) as u16])
+        });
+        let mut buf = Vec::new();
+        PngEncoder::new(&mut buf)
+            .write_image(
+                bytemuck::cast_slice(image.as_raw()),
+                16,
+                4,
+                ExtendedColorType::L16,
+            )
+            .unwrap();
+
+        // The stored samples are big endian, regardless of the host.
+        let mut reader = png::Decoder::new(Cursor::new(&buf)).read_info().unwrap();
+        assert_eq!(
+            reader.info().bit_depth,
+            png::BitDepth::Sixteen,
+            "samples were narrowed"
+        );
+        let mut stored = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut stored).unwrap();
+        assert_eq!(stored[..4], [0x12, 0x34, 0x13, 0x35]);
+
+        let decoder = PngDecoder::new(Cursor::new(&buf)).unwrap();
+        assert_eq!(decoder.color_type(), ColorType::L16);
+        let decoded = crate::image::decoder_to_vec::<u16>(decoder).unwrap();
+        assert_eq!(decoded, image.into_raw());
+
+        let rgba = ImageBuffer::<Rgba<u16>, _>::from_fn(3, 2, |x, y| {
+            Rgba([0x1234, 0xabcd, (x * 0x4321) as u16, 0xfe00 | y as u16])
+        });
+        let mut buf = Vec::new();
+        PngEncoder::new(&mut buf)
+            .write_image(
+                bytemuck::cast_slice(rgba.as_raw()),
+                3,
+                2,
+                ExtendedColorType::Rgba16,
+            )
+            .unwrap();
+        let decoded = crate::load_from_memory(&buf).unwrap();
+        assert_eq!(decoded.into_rgba16(), rgba);
+    }
+
     #[test]
     fn ensure_no_decoder_off_by_one() {
         let dec = PngDecoder::new(BufReader::new(
@@ -729,6 +1126,36 @@ mod tests {
             .expect("Caused by a png error");
     }
 
+    #[test]
+    fn corrupt_chunk_crc() {
+        let mut png =
+            std::fs::read("tests/images/png/bugfixes/debug_triangle_corners_widescreen.png")
+                .unwrap();
+        let idat = png
+            .windows(4)
+            .position(|w| w == b"IDAT")
+            .expect("Image has an IDAT chunk");
+        // Corrupt the stored CRC of the chunk, which directly follows its data. Flipping a bit in
+        // the compressed data itself would usually be caught by zlib before the CRC is checked.
+        let len = u32::from_be_bytes(png[idat - 4..idat].try_into().unwrap()) as usize;
+        png[idat + 4 + len] ^= 1;
+
+        let decode = |verify_checksums| {
+            let decoder = PngDecoder::with_limits_and_checksums(
+                Cursor::new(&png),
+                Limits::no_limits(),
+                verify_checksums,
+            )?;
+            DynamicImage::from_decoder(decoder)
+        };
+
+        match decode(true) {
+            Err(ImageError::Decoding(err)) => assert!(err.to_string().contains("IDAT")),
+            other => panic!("expected a decoding error, got {:?}", other.map(|_| ())),
+        }
+        assert!(decode(false).is_ok());
+    }
+
     #[test]
     fn encode_bad_color_type() {
         // regression test for issue #1663
@@ -736,4 +1163,220 @@ mod tests {
         let mut target = Cursor::new(vec![]);
         let _ = image.write_to(&mut target, ImageFormat::Png);
     }
+
+    #[test]
+    fn encode_indexed() {
+        let palette = [Rgb([255, 0, 0]), Rgb([0, 255, 0]), Rgb([0, 0, 255])];
+        let indices = [0, 1, 2, 2, 1, 0];
+
+        let mut buf = Vec::new();
+        PngEncoder::new(&mut buf)
+            .write_indexed_image(&indices, 3, 2, &palette)
+            .unwrap();
+
+        let info = png::Decoder::new(Cursor::new(&buf)).read_info().unwrap();
+        assert_eq!(info.info().color_type, png::ColorType::Indexed);
+        assert_eq!(
+            info.info().palette.as_deref(),
+            Some(&[255, 0, 0, 0, 255, 0, 0, 0, 255][..])
+        );
+
+        let image = crate::load_from_memory_with_format(&buf, ImageFormat::Png)
+            .unwrap()
+            .into_rgb8();
+        for (pixel, &index) in image.pixels().zip(&indices) {
+            assert_eq!(*pixel, palette[usize::from(index)]);
+        }
+    }
+
+    #[test]
+    fn encode_indexed_out_of_range() {
+        let result = PngEncoder::new(Vec::new()).write_indexed_image(
+            &[0, 3],
+            2,
+            1,
+            &[Rgb([0, 0, 0]), Rgb([1, 1, 1])],
+        );
+        assert!(matches!(result, Err(ImageError::Parameter(_))));
+    }
+
+    #[test]
+    fn icc_profile() {
+        let profile: &[u8] = b"not really an ICC profile, but any bytes will do";
+
+        let mut info = png::Info::with_size(1, 1);
+        info.icc_profile = Some(profile.into());
+        let mut buf = Vec::new();
+        png::Encoder::with_info(&mut buf, info)
+            .unwrap()
+            .write_header()
+            .unwrap()
+            .write_image_data(&[0])
+            .unwrap();
+
+        let mut decoder = PngDecoder::new(Cursor::new(buf)).unwrap();
+        assert_eq!(decoder.icc_profile().unwrap().as_deref(), Some(profile));
+    }
+
+    #[test]
+    fn apng_roundtrip() {
+        let red = Rgba([255, 0, 0, 255]);
+        let green = Rgba([0, 255, 0, 128]);
+        let first = Frame::from_parts(
+            RgbaImage::from_pixel(4, 4, red),
+            0,
+            0,
+            Delay::from_numer_denom_ms(100, 1),
+        );
+        let second = Frame::from_parts(
+            RgbaImage::from_pixel(2, 1, green),
+            1,
+            2,
+            Delay::from_numer_denom_ms(40, 1),
+        );
+
+        let mut buf = Vec::new();
+        let mut encoder = ApngEncoder::new(&mut buf, 4, 4);
+        encoder.encode_frames(vec![first, second]).unwrap();
+        encoder.finish().unwrap();
+
+        let decoder = PngDecoder::new(Cursor::new(buf)).unwrap();
+        assert!(decoder.is_apng().unwrap());
+        let frames = decoder
+            .apng()
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].delay(), Delay::from_numer_denom_ms(100, 1));
+        assert_eq!(frames[1].delay(), Delay::from_numer_denom_ms(40, 1));
+        assert!(frames[0].buffer().pixels().all(|&p| p == red));
+
+        // The first frame has been disposed to transparent black.
+        let second = frames[1].buffer();
+        for (x, y, &pixel) in second.enumerate_pixels() {
+            let inside = (1..3).contains(&x) && y == 2;
+            assert_eq!(pixel, if inside { green } else { Rgba([0, 0, 0, 0]) });
+        }
+    }
+
+    #[test]
+    fn apng_blend_semi_transparent_edge() {
+        // A white frame, then a red square with a half transparent edge blended over it.
+        let edge = Rgba([255, 0, 0, 128]);
+        let square = RgbaImage::from_fn(4, 4, |x, y| {
+            let inner = (1..3).contains(&x) && (1..3).contains(&y);
+            if inner {
+                Rgba([255, 0, 0, 255])
+            } else {
+                edge
+            }
+        });
+
+        let mut buf = Vec::new();
+        let mut encoder = png::Encoder::new(&mut buf, 6, 6);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_animated(2, 0).unwrap();
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[255; 6 * 6 * 4]).unwrap();
+        writer.set_frame_dimension(4, 4).unwrap();
+        writer.set_frame_position(1, 1).unwrap();
+        writer.set_blend_op(BlendOp::Over).unwrap();
+        writer.write_image_data(square.as_raw()).unwrap();
+        writer.finish().unwrap();
+
+        let frames = PngDecoder::new(Cursor::new(buf))
+            .unwrap()
+            .apng()
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        let canvas = frames[1].buffer();
+
+        // Straight alpha over white: the edge is a lighter red, not darkened towards black.
+        let blended = canvas.get_pixel(1, 1);
+        assert!(blended[0] >= 254, "{blended:?}");
+        assert!((126..=128).contains(&blended[1]) && blended[1] == blended[2]);
+        assert_eq!(blended[3], 255);
+        assert_eq!(*canvas.get_pixel(2, 2), Rgba([255, 0, 0, 255]));
+        assert_eq!(*canvas.get_pixel(0, 0), Rgba([255; 4]));
+    }
+
+    #[test]
+    fn apng_premultiplied_frame() {
+        let mut straight = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 128]));
+        straight.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+        let mut premultiplied = straight.clone();
+        crate::imageops::premultiply_alpha(&mut premultiplied);
+        assert_eq!(*premultiplied.get_pixel(1, 1), Rgba([128, 0, 0, 128]));
+
+        let frame = Frame::new(premultiplied).with_premultiplied_alpha(true);
+        assert!(frame.is_premultiplied());
+        let mut buf = Vec::new();
+        let mut encoder = ApngEncoder::new(&mut buf, 2, 2);
+        encoder.encode_frame(frame).unwrap();
+        encoder.finish().unwrap();
+
+        let frames = PngDecoder::new(Cursor::new(buf))
+            .unwrap()
+            .apng()
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert!(!frames[0].is_premultiplied());
+        assert_eq!(*frames[0].buffer(), straight);
+    }
+
+    #[test]
+    fn apng_pads_first_frame() {
+        let frame = Frame::from_parts(
+            RgbaImage::from_pixel(1, 1, Rgba([1, 2, 3, 4])),
+            1,
+            1,
+            Delay::from_numer_denom_ms(0, 1),
+        );
+        let mut buf = Vec::new();
+        let mut encoder = ApngEncoder::new(&mut buf, 2, 2);
+        encoder.encode_frame(frame).unwrap();
+        encoder.finish().unwrap();
+
+        let decoder = PngDecoder::new(Cursor::new(buf)).unwrap();
+        let frames = decoder
+            .apng()
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames[0].buffer().get_pixel(1, 1), &Rgba([1, 2, 3, 4]));
+        assert_eq!(frames[0].buffer().get_pixel(0, 0), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn apng_frame_outside_canvas() {
+        let mut encoder = ApngEncoder::new(Vec::new(), 4, 4);
+        let frame = Frame::from_parts(RgbaImage::new(2, 2), 3, 0, Delay::from_numer_denom_ms(0, 1));
+        assert!(encoder.encode_frame(frame).is_err());
+        assert!(encoder.finish().is_err());
+
+        // The end of the frame overflows rather than exceeding the canvas.
+        let mut encoder = ApngEncoder::new(Vec::new(), u32::MAX, 4);
+        let frame = Frame::from_parts(RgbaImage::new(2, 2), u32::MAX, 0, Delay::from_millis(0));
+        let error = encoder.encode_frame(frame).unwrap_err();
+        assert!(
+            matches!(&error, ImageError::Parameter(e) if e.kind() == ParameterErrorKind::DimensionMismatch),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn apng_delay_fraction() {
+        assert_eq!(apng_delay(Delay::from_numer_denom_ms(100, 3)), (100, 3000));
+        assert_eq!(
+            apng_delay(Delay::from_numer_denom_ms(100_000, 1)),
+            (10000, 100)
+        );
+    }
 }
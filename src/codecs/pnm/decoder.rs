@@ -1,6 +1,7 @@
 use std::error;
 use std::fmt::{self, Display};
 use std::io::{self, Read};
+use std::mem::size_of;
 use std::num::ParseIntError;
 use std::str::{self, FromStr};
 
@@ -235,7 +236,7 @@ trait Sample {
 
     /// Representation size in bytes
     fn sample_size() -> u32 {
-        std::mem::size_of::<Self::Representation>() as u32
+        size_of::<Self::Representation>() as u32
     }
     fn bytelen(width: u32, height: u32, samples: u32) -> ImageResult<usize> {
         Ok((width * height * samples * Self::sample_size()) as usize)
@@ -928,6 +929,24 @@ impl DecodableImageHeader for ArbitraryHeader {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn total_bytes_from_header() {
+        let decoder = PnmDecoder::new(&b"P6 3 2 65535\n"[..]).unwrap();
+        assert_eq!(decoder.color_type(), ColorType::Rgb16);
+        assert_eq!(decoder.total_bytes(), 3 * 2 * 6);
+
+        // The size is computed in 64 bits, it does not wrap around at 4 GiB.
+        let header = b"P6 70000 70000 65535\n";
+        let decoder = PnmDecoder::new(&header[..]).unwrap();
+        assert_eq!(decoder.total_bytes(), 29_400_000_000);
+
+        // So the allocation limit rejects the image before a buffer is allocated for it.
+        let mut reader = crate::io::Reader::new(io::Cursor::new(&header[..]));
+        reader.set_format(ImageFormat::Pnm);
+        assert!(matches!(reader.decode(), Err(ImageError::Limits(_))));
+    }
+
     /// Tests reading of a valid blackandwhite pam
     #[test]
     fn pam_blackandwhite() {
@@ -183,14 +183,7 @@ impl<W: Write> PnmEncoder<W> {
             ExtendedColorType::Rgb16 => (0xffff, ArbitraryTuplType::RGB),
             ExtendedColorType::Rgba8 => (0xff, ArbitraryTuplType::RGBAlpha),
             ExtendedColorType::Rgba16 => (0xffff, ArbitraryTuplType::RGBAlpha),
-            _ => {
-                return Err(ImageError::Unsupported(
-                    UnsupportedError::from_format_and_kind(
-                        ImageFormat::Pnm.into(),
-                        UnsupportedErrorKind::Color(color),
-                    ),
-                ))
-            }
+            _ => return Err(ImageError::unsupported_color(ImageFormat::Pnm, color)),
         };
 
         let header = PnmHeader {
@@ -470,12 +463,7 @@ impl<'a> CheckedHeaderColor<'a> {
             | ExtendedColorType::Rgba16 => 0xffff,
             _ => {
                 // Unsupported target color type.
-                return Err(ImageError::Unsupported(
-                    UnsupportedError::from_format_and_kind(
-                        ImageFormat::Pnm.into(),
-                        UnsupportedErrorKind::Color(self.color),
-                    ),
-                ));
+                return Err(ImageError::unsupported_color(ImageFormat::Pnm, self.color));
             }
         };
 
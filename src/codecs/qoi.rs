@@ -74,13 +74,10 @@ impl<W: Write> ImageEncoder for QoiEncoder<W> {
         color_type: ExtendedColorType,
     ) -> ImageResult<()> {
         if !matches!(
-            color_type,
-            ExtendedColorType::Rgba8 | ExtendedColorType::Rgb8
+            color_type.to_color_type(),
+            Some(ColorType::Rgba8 | ColorType::Rgb8)
         ) {
-            return Err(ImageError::Encoding(EncodingError::new(
-                ImageFormat::Qoi.into(),
-                format!("unsupported color type {color_type:?}. Supported are Rgba8 and Rgb8."),
-            )));
+            return Err(ImageError::unsupported_color(ImageFormat::Qoi, color_type));
         }
 
         let expected_buffer_len = color_type.buffer_size(width, height);
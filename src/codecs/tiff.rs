@@ -119,10 +119,7 @@ fn check_sample_format(sample_format: u16) -> Result<(), ImageError> {
 }
 
 fn err_unknown_color_type(value: u8) -> ImageError {
-    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
-        ImageFormat::Tiff.into(),
-        UnsupportedErrorKind::Color(ExtendedColorType::Unknown(value)),
-    ))
+    ImageError::unsupported_color(ImageFormat::Tiff, ExtendedColorType::Unknown(value))
 }
 
 impl ImageError {
@@ -366,14 +363,7 @@ impl<W: Write + Seek> TiffEncoder<W> {
                 height,
                 u8_slice_as_u16(buf)?,
             ),
-            _ => {
-                return Err(ImageError::Unsupported(
-                    UnsupportedError::from_format_and_kind(
-                        ImageFormat::Tiff.into(),
-                        UnsupportedErrorKind::Color(color_type),
-                    ),
-                ))
-            }
+            _ => return Err(ImageError::unsupported_color(ImageFormat::Tiff, color_type)),
         }
         .map_err(ImageError::from_tiff_encode)?;
 
@@ -0,0 +1,111 @@
+//! Writing of the bit-packed "VP8L" bitstream.
+
+/// Collects bits least significant first, as the lossless "VP8L" bitstream requires.
+///
+/// Each value is appended right after the previous one, starting with its lowest bit. Bytes are
+/// filled from their lowest bit up, so the first value written ends up in the low bits of the
+/// first byte. This is the building block for writing the headers, transforms and prefix codes
+/// of a "VP8L" chunk.
+///
+/// ```
+/// use image::codecs::webp::BitWriter;
+///
+/// let mut w = BitWriter::new();
+/// w.write_bits(0b1, 1);
+/// w.write_bits(0b10, 2);
+/// w.write_bits(0b11111, 5);
+/// w.write_bits(0x3ab, 10);
+/// assert_eq!(w.into_bytes(), [0b1111_1101, 0xab, 0b11]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    nbits: u8,
+}
+
+impl BitWriter {
+    /// Create a writer without any bits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the lowest `nbits` bits of `bits`, least significant first. Any higher bits of
+    /// `bits` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nbits` is larger than 32.
+    pub fn write_bits(&mut self, bits: u32, nbits: u8) {
+        assert!(nbits <= 32, "can not write more than 32 bits at once");
+        // Less than 64 bits, so the shift can not overflow.
+        let bits = u64::from(bits) & ((1 << nbits) - 1);
+        self.buffer |= bits << self.nbits;
+        self.nbits += nbits;
+        while self.nbits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.nbits -= 8;
+        }
+    }
+
+    /// Pad the last byte with zero bits, so that the next value starts on a byte boundary.
+    pub fn flush(&mut self) {
+        if self.nbits > 0 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer = 0;
+            self.nbits = 0;
+        }
+    }
+
+    /// Return the bytes written, with the last one padded by zero bits.
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        self.flush();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitWriter;
+
+    #[test]
+    fn least_significant_bit_first() {
+        let mut w = BitWriter::new();
+        for bit in [1, 0, 0, 0, 0, 0, 0, 0, 1, 1] {
+            w.write_bits(bit, 1);
+        }
+        assert_eq!(w.into_bytes(), [0x01, 0x03]);
+
+        // The signature and size fields of a "VP8L" header of a 400x300 image with alpha.
+        let mut w = BitWriter::new();
+        w.write_bits(0x2f, 8);
+        w.write_bits(399, 14);
+        w.write_bits(299, 14);
+        w.write_bits(1, 1);
+        w.write_bits(0, 3);
+        assert_eq!(w.into_bytes(), [0x2f, 0x8f, 0xc1, 0x4a, 0x10]);
+    }
+
+    #[test]
+    fn wide_values_and_flush() {
+        let mut w = BitWriter::new();
+        w.write_bits(0b101, 3);
+        w.write_bits(0xdead_beef, 32);
+        w.flush();
+        w.write_bits(0x5, 4);
+        w.flush();
+        w.flush();
+        assert_eq!(w.into_bytes(), [0x7d, 0xf7, 0x6d, 0xf5, 0x06, 0x05]);
+        assert!(BitWriter::new().into_bytes().is_empty());
+    }
+
+    #[test]
+    fn high_bits_are_ignored() {
+        let mut w = BitWriter::new();
+        w.write_bits(0xffff_fffa, 3);
+        w.write_bits(u32::MAX, 0);
+        w.write_bits(0xff, 5);
+        assert_eq!(w.into_bytes(), [0xfa]);
+    }
+}
@@ -1,24 +1,91 @@
-use std::io::{BufRead, Read, Seek};
+use std::io::{BufRead, Cursor, Read, Seek, SeekFrom};
 
 use crate::buffer::ConvertBuffer;
 use crate::error::{DecodingError, ImageError, ImageResult};
 use crate::image::{ImageDecoder, ImageFormat};
+use crate::io::Limits;
 use crate::{AnimationDecoder, ColorType, Delay, Frame, Frames, RgbImage, Rgba, RgbaImage};
 
-/// WebP Image format decoder. Currently only supports lossy RGB images or lossless RGBA images.
+/// WebP Image format decoder.
+///
+/// Supports lossy and lossless images as well as the extended format. The alpha channel of lossy
+/// images, stored in a separate `ALPH` chunk, is decoded and combined with the color data into
+/// `Rgba8` output.
 pub struct WebPDecoder<R> {
     inner: image_webp::WebPDecoder<R>,
+    still_frame: Option<StillFrame>,
+    features: WebPFeatures,
+}
+
+/// The properties of a WebP image as given by its headers, see [`WebPDecoder::features`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct WebPFeatures {
+    /// The width of the canvas in pixels.
+    pub width: u32,
+    /// The height of the canvas in pixels.
+    pub height: u32,
+    /// Whether the image has an alpha channel.
+    pub has_alpha: bool,
+    /// Whether the image is animated.
+    pub has_animation: bool,
+    /// Whether the image, or any frame of it, is stored in the lossy format. Otherwise it is
+    /// lossless.
+    pub is_lossy: bool,
+}
+
+/// The frame of a still extended image that is smaller than the canvas of the `VP8X` chunk.
+///
+/// The frame is decoded on its own, from a copy of its chunks, and then placed at the top-left
+/// corner of the otherwise blank canvas.
+struct StillFrame {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
 }
 
 impl<R: BufRead + Seek> WebPDecoder<R> {
     /// Create a new WebPDecoder from the Reader ```r```.
     /// This function takes ownership of the Reader.
+    ///
+    /// Returns an error if the RIFF header claims more data than the reader holds. Bytes after
+    /// the end of the RIFF chunk, which some tools append to images, are ignored.
+    ///
+    /// Still images in the extended format may declare a canvas larger than their frame. The
+    /// output then has the size of the canvas, with the frame at its top-left corner and the rest
+    /// left transparent, or black for images without alpha.
     pub fn new(r: R) -> ImageResult<Self> {
+        Self::with_limits(r, Limits::default())
+    }
+
+    /// Create a new WebPDecoder from the Reader ```r``` with the given limits.
+    ///
+    /// The copy of the frame that [`WebPDecoder::new`] makes for a canvas larger than its frame
+    /// is counted against `limits`.
+    pub fn with_limits(mut r: R, mut limits: Limits) -> ImageResult<Self> {
+        check_riff_size(&mut r)?;
+        let still_frame = read_still_frame(&mut r, &mut limits)?;
+        let mut inner = image_webp::WebPDecoder::new(r).map_err(ImageError::from_webp_decode)?;
+        let (width, height) = inner.dimensions();
+        let features = WebPFeatures {
+            width,
+            height,
+            has_alpha: inner.has_alpha(),
+            has_animation: inner.is_animated(),
+            is_lossy: inner.is_lossy(),
+        };
         Ok(Self {
-            inner: image_webp::WebPDecoder::new(r).map_err(ImageError::from_webp_decode)?,
+            inner,
+            still_frame,
+            features,
         })
     }
 
+    /// Returns the properties of the image, which are known without decoding it.
+    pub fn features(&self) -> WebPFeatures {
+        self.features
+    }
+
     /// Returns true if the image as described by the bitstream is animated.
     pub fn has_animation(&self) -> bool {
         self.inner.is_animated()
@@ -32,6 +99,144 @@ impl<R: BufRead + Seek> WebPDecoder<R> {
     }
 }
 
+/// Check that the reader holds as much data as the RIFF header of the file claims.
+///
+/// The reader is left at the position it started at. Headers that are not RIFF headers are left
+/// for the decoder to reject.
+fn check_riff_size<R: Read + Seek>(r: &mut R) -> ImageResult<()> {
+    let start = r.stream_position()?;
+    let mut header = [0; 8];
+    let read = r.read_exact(&mut header);
+    let end = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(start))?;
+
+    if read.is_err() || &header[..4] != b"RIFF" {
+        return Ok(());
+    }
+    let riff_size = u64::from(u32::from_le_bytes([
+        header[4], header[5], header[6], header[7],
+    ]));
+    let available = end.saturating_sub(start + 8);
+    if riff_size > available {
+        return Err(ImageError::Decoding(DecodingError::new(
+            ImageFormat::WebP.into(),
+            format!(
+                "the RIFF header claims {riff_size} bytes of data but only {available} are present"
+            ),
+        )));
+    }
+    Ok(())
+}
+
+/// Find the frame of a still extended image if it is smaller than the canvas.
+///
+/// The reader is left at the position it started at. Files that can not be examined are left for
+/// the decoder to reject.
+fn read_still_frame<R: Read + Seek>(
+    r: &mut R,
+    limits: &mut Limits,
+) -> ImageResult<Option<StillFrame>> {
+    let start = r.stream_position()?;
+    let frame = find_still_frame(r, start, limits);
+    r.seek(SeekFrom::Start(start))?;
+    frame
+}
+
+fn find_still_frame<R: Read + Seek>(
+    r: &mut R,
+    start: u64,
+    limits: &mut Limits,
+) -> ImageResult<Option<StillFrame>> {
+    const ANIMATION: u8 = 0x02;
+    const ALPHA: u8 = 0x10;
+
+    let le = |bytes: &[u8]| bytes.iter().rev().fold(0, |v, &b| v << 8 | u32::from(b));
+    let mut header = [0; 30];
+    if r.read_exact(&mut header).is_err()
+        || &header[..4] != b"RIFF"
+        || &header[8..16] != b"WEBPVP8X"
+        || le(&header[16..20]) != 10
+        || header[20] & ANIMATION != 0
+    {
+        return Ok(None);
+    }
+    let (canvas_width, canvas_height) = (le(&header[24..27]) + 1, le(&header[27..30]) + 1);
+
+    // Find the first alpha and image chunks, the same ones the decoder uses, as offsets and
+    // lengths including their chunk headers. Of the image chunk only the start is read.
+    let riff_end = 8 + u64::from(le(&header[4..8]));
+    let mut position = header.len() as u64;
+    let (mut alpha, mut image) = (None, None);
+    let mut bits = Vec::with_capacity(10);
+    while position + 8 <= riff_end && image.is_none() {
+        let mut chunk = [0; 8];
+        if r.read_exact(&mut chunk).is_err() {
+            break;
+        }
+        let size = le(&chunk[4..8]);
+        let padded = u64::from(size) + u64::from(size & 1);
+        let found = (position, 8 + padded);
+        position += 8 + padded;
+        let mut skip = padded;
+        match &chunk[..4] {
+            b"ALPH" => alpha = alpha.or(Some(found)),
+            b"VP8 " | b"VP8L" => {
+                r.by_ref().take(padded.min(10)).read_to_end(&mut bits)?;
+                skip -= bits.len() as u64;
+                image = Some((chunk, found));
+            }
+            _ => {}
+        }
+        r.seek(SeekFrom::Current(skip as i64))?;
+    }
+
+    let Some((chunk, image)) = image else {
+        return Ok(None);
+    };
+    let (width, height) = match (&chunk[..4], bits.get(..10)) {
+        (b"VP8 ", Some(bits)) if bits[3..6] == [0x9d, 0x01, 0x2a] => {
+            (le(&bits[6..8]) & 0x3fff, le(&bits[8..10]) & 0x3fff)
+        }
+        (b"VP8L", Some(bits)) if bits[0] == 0x2f => {
+            let bits = le(&bits[1..5]);
+            ((bits & 0x3fff) + 1, (bits >> 14 & 0x3fff) + 1)
+        }
+        _ => return Ok(None),
+    };
+    if (width, height) == (canvas_width, canvas_height) {
+        return Ok(None);
+    }
+    if width > canvas_width || height > canvas_height {
+        return Err(ImageError::Decoding(DecodingError::new(
+            ImageFormat::WebP.into(),
+            format!(
+                "the {width}x{height} frame does not fit the {canvas_width}x{canvas_height} canvas"
+            ),
+        )));
+    }
+
+    // An extended image of just the frame, without metadata.
+    let chunks_len = alpha.map_or(0, |(_, len)| len) + image.1;
+    limits.reserve(30 + chunks_len)?;
+    let mut data = Vec::with_capacity(30 + chunks_len as usize);
+    data.extend_from_slice(b"RIFF");
+    data.extend_from_slice(&((22 + chunks_len) as u32).to_le_bytes());
+    data.extend_from_slice(b"WEBPVP8X");
+    data.extend_from_slice(&10u32.to_le_bytes());
+    data.extend_from_slice(&[header[20] & ALPHA, 0, 0, 0]);
+    data.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+    data.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+    for (offset, len) in alpha.into_iter().chain([image]) {
+        r.seek(SeekFrom::Start(start + offset))?;
+        r.by_ref().take(len).read_to_end(&mut data)?;
+    }
+    Ok(Some(StillFrame {
+        width,
+        height,
+        data,
+    }))
+}
+
 impl<R: BufRead + Seek> ImageDecoder for WebPDecoder<R> {
     fn dimensions(&self) -> (u32, u32) {
         self.inner.dimensions()
@@ -48,15 +253,47 @@ impl<R: BufRead + Seek> ImageDecoder for WebPDecoder<R> {
     fn read_image(mut self, buf: &mut [u8]) -> ImageResult<()> {
         assert_eq!(u64::try_from(buf.len()), Ok(self.total_bytes()));
 
-        self.inner
-            .read_image(buf)
-            .map_err(ImageError::from_webp_decode)
+        let Some(frame) = self.still_frame.take() else {
+            return self
+                .inner
+                .read_image(buf)
+                .map_err(ImageError::from_webp_decode);
+        };
+
+        let mut decoder = image_webp::WebPDecoder::new(Cursor::new(frame.data))
+            .map_err(ImageError::from_webp_decode)?;
+        let bytes_per_pixel = usize::from(self.color_type().bytes_per_pixel());
+        let row_len = frame.width as usize * bytes_per_pixel;
+        let mut pixels = vec![0; row_len * frame.height as usize];
+        decoder
+            .read_image(&mut pixels)
+            .map_err(ImageError::from_webp_decode)?;
+
+        let canvas_row_len = self.inner.dimensions().0 as usize * bytes_per_pixel;
+        buf.fill(0);
+        for (row, canvas_row) in pixels
+            .chunks_exact(row_len)
+            .zip(buf.chunks_exact_mut(canvas_row_len))
+        {
+            canvas_row[..row_len].copy_from_slice(row);
+        }
+        Ok(())
     }
 
     fn read_image_boxed(self: Box<Self>, buf: &mut [u8]) -> ImageResult<()> {
         (*self).read_image(buf)
     }
 
+    fn set_limits(&mut self, mut limits: Limits) -> ImageResult<()> {
+        limits.check_support(&crate::io::LimitSupport::default())?;
+        let (width, height) = self.dimensions();
+        limits.check_dimensions(width, height)?;
+        if let Some(frame) = &self.still_frame {
+            limits.reserve_usize(frame.data.len())?;
+        }
+        Ok(())
+    }
+
     fn icc_profile(&mut self) -> ImageResult<Option<Vec<u8>>> {
         self.inner
             .icc_profile()
@@ -79,12 +316,14 @@ impl<'a, R: 'a + Read + Seek> AnimationDecoder<'a> for WebPDecoder<R> {
                     let mut img = RgbaImage::new(width, height);
                     match self.decoder.inner.read_frame(&mut img) {
                         Ok(delay) => (img, delay),
+                        Err(image_webp::DecodingError::NoMoreFrames) => return None,
                         Err(e) => return Some(Err(ImageError::from_webp_decode(e))),
                     }
                 } else {
                     let mut img = RgbImage::new(width, height);
                     match self.decoder.inner.read_frame(&mut img) {
                         Ok(delay) => (img.convert(), delay),
+                        Err(image_webp::DecodingError::NoMoreFrames) => return None,
                         Err(e) => return Some(Err(ImageError::from_webp_decode(e))),
                     }
                 };
@@ -102,6 +341,49 @@ impl<'a, R: 'a + Read + Seek> AnimationDecoder<'a> for WebPDecoder<R> {
     }
 }
 
+/// Read the canvas size of a WebP image from the start of its header.
+///
+/// Only the file header and the start of the first chunk are read, at most 30 bytes, so this
+/// also works on an image that is truncated or still being received. The size is taken from the
+/// `VP8X` chunk of extended images, or else from the bitstream of simple lossy (`VP8 `) or
+/// lossless (`VP8L`) images. Nothing is validated beyond what is needed to find the size.
+pub fn read_dimensions<R: Read>(mut reader: R) -> ImageResult<(u32, u32)> {
+    let error = |message: &'static str| {
+        ImageError::Decoding(DecodingError::new(ImageFormat::WebP.into(), message))
+    };
+
+    let mut header = [0; 20];
+    reader.read_exact(&mut header)?;
+    if &header[..4] != b"RIFF" || &header[8..12] != b"WEBP" {
+        return Err(error("missing RIFF WEBP signature"));
+    }
+
+    let le = |bytes: &[u8]| bytes.iter().rev().fold(0, |v, &b| v << 8 | u32::from(b));
+    let mut chunk = [0; 10];
+    match &header[12..16] {
+        b"VP8 " => {
+            reader.read_exact(&mut chunk)?;
+            if chunk[3..6] != [0x9d, 0x01, 0x2a] {
+                return Err(error("missing VP8 start code"));
+            }
+            Ok((le(&chunk[6..8]) & 0x3fff, le(&chunk[8..10]) & 0x3fff))
+        }
+        b"VP8L" => {
+            reader.read_exact(&mut chunk[..5])?;
+            if chunk[0] != 0x2f {
+                return Err(error("missing VP8L signature"));
+            }
+            let bits = le(&chunk[1..5]);
+            Ok(((bits & 0x3fff) + 1, (bits >> 14 & 0x3fff) + 1))
+        }
+        b"VP8X" => {
+            reader.read_exact(&mut chunk)?;
+            Ok((le(&chunk[4..7]) + 1, le(&chunk[7..10]) + 1))
+        }
+        _ => Err(error("first chunk is not an image")),
+    }
+}
+
 impl ImageError {
     fn from_webp_decode(e: image_webp::DecodingError) -> Self {
         match e {
@@ -115,6 +397,184 @@ impl ImageError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn trailing_bytes_are_ignored() {
+        let img = RgbaImage::from_fn(5, 3, |x, y| Rgba([x as u8 * 50, y as u8 * 80, 7, 200]));
+        let mut simple = Vec::new();
+        image_webp::WebPEncoder::new(&mut simple)
+            .encode(&img, 5, 3, image_webp::ColorType::Rgba8)
+            .unwrap();
+        let mut extended = Vec::new();
+        let mut encoder = image_webp::WebPEncoder::new(&mut extended);
+        encoder.set_icc_profile(b"profile".to_vec());
+        encoder
+            .encode(&img, 5, 3, image_webp::ColorType::Rgba8)
+            .unwrap();
+
+        for mut data in [simple, extended] {
+            data.extend((0..100u8).map(|i| i.wrapping_mul(37)));
+            let decoder = WebPDecoder::new(Cursor::new(data)).unwrap();
+            let decoded = crate::DynamicImage::from_decoder(decoder).unwrap();
+            assert_eq!(decoded.into_rgba8(), img);
+        }
+    }
+
+    #[test]
+    fn canvas_larger_than_still_frame() {
+        let frame = RgbaImage::from_fn(3, 2, |x, y| Rgba([x as u8 * 80, y as u8 * 90, 5, 255]));
+        let mut simple = Vec::new();
+        image_webp::WebPEncoder::new(&mut simple)
+            .encode(&frame, 3, 2, image_webp::ColorType::Rgba8)
+            .unwrap();
+
+        // Move the VP8L chunk into an extended image with a 5x4 canvas.
+        let vp8l = &simple[12..];
+        let mut data = b"RIFF".to_vec();
+        data.extend_from_slice(&(22 + vp8l.len() as u32).to_le_bytes());
+        data.extend_from_slice(b"WEBPVP8X");
+        data.extend_from_slice(&[10, 0, 0, 0, 0x10, 0, 0, 0, 4, 0, 0, 3, 0, 0]);
+        data.extend_from_slice(vp8l);
+
+        let decoder = WebPDecoder::new(Cursor::new(data.clone())).unwrap();
+        assert_eq!(decoder.dimensions(), (5, 4));
+        let decoded = crate::DynamicImage::from_decoder(decoder)
+            .unwrap()
+            .into_rgba8();
+        let mut expected = RgbaImage::new(5, 4);
+        crate::imageops::replace(&mut expected, &frame, 0, 0);
+        assert_eq!(decoded, expected);
+
+        // The copy of the frame counts against the limits.
+        let mut limits = Limits::no_limits();
+        limits.max_alloc = Some(30 + vp8l.len() as u64 - 1);
+        let error = WebPDecoder::with_limits(Cursor::new(data.clone()), limits.clone())
+            .err()
+            .unwrap();
+        assert!(matches!(error, ImageError::Limits(_)), "{error:?}");
+        limits.max_alloc = Some(30 + vp8l.len() as u64);
+        assert!(WebPDecoder::with_limits(Cursor::new(data.clone()), limits.clone()).is_ok());
+        let mut decoder = WebPDecoder::new(Cursor::new(data.clone())).unwrap();
+        limits.max_alloc = Some(1);
+        assert!(decoder.set_limits(limits).is_err());
+
+        // A canvas smaller than the frame is an error.
+        data[24..30].copy_from_slice(&[1, 0, 0, 1, 0, 0]);
+        let error = WebPDecoder::new(Cursor::new(data)).err().unwrap();
+        assert!(matches!(error, ImageError::Decoding(_)), "{error:?}");
+    }
+
+    #[test]
+    fn features() {
+        let features = |path: &str| {
+            let data = std::fs::read(format!("tests/images/webp/{path}")).unwrap();
+            let features = WebPDecoder::new(Cursor::new(&data)).unwrap().features();
+            let dimensions = read_dimensions(&data[..]).unwrap();
+            assert_eq!((features.width, features.height), dimensions, "{path}");
+            features
+        };
+
+        let lossy_alpha = features("extended_images/lossy_alpha.webp");
+        assert!(lossy_alpha.is_lossy && lossy_alpha.has_alpha && !lossy_alpha.has_animation);
+
+        let lossy = features("lossy_images/simple-rgb.webp");
+        assert!(lossy.is_lossy && !lossy.has_alpha && !lossy.has_animation);
+
+        let lossless = features("lossless_images/simple.webp");
+        assert!(!lossless.is_lossy && !lossless.has_alpha && !lossless.has_animation);
+
+        // The frames are lossy with alpha.
+        let animated = features("extended_images/anim.webp");
+        assert!(animated.is_lossy && animated.has_alpha && animated.has_animation);
+    }
+
+    #[test]
+    fn riff_size_beyond_data() {
+        let mut data = Vec::new();
+        image_webp::WebPEncoder::new(&mut data)
+            .encode(&[0; 12], 2, 2, image_webp::ColorType::Rgb8)
+            .unwrap();
+        let riff_size = u32::from_le_bytes(data[4..8].try_into().unwrap());
+        data[4..8].copy_from_slice(&(riff_size + 100).to_le_bytes());
+
+        let error = WebPDecoder::new(Cursor::new(data)).err().unwrap();
+        assert!(matches!(error, ImageError::Decoding(_)), "{error:?}");
+        assert!(error.to_string().contains("RIFF"), "{error}");
+    }
+
+    #[test]
+    fn icc_profile() {
+        let profile = b"not really an ICC profile, but any bytes will do".to_vec();
+
+        let mut buf = Vec::new();
+        let mut encoder = image_webp::WebPEncoder::new(&mut buf);
+        encoder.set_icc_profile(profile.clone());
+        encoder
+            .encode(&[0; 3], 1, 1, image_webp::ColorType::Rgb8)
+            .unwrap();
+
+        let mut decoder = WebPDecoder::new(Cursor::new(buf)).unwrap();
+        assert_eq!(decoder.icc_profile().unwrap(), Some(profile));
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn lossy_with_alpha_chunk() {
+        let file = std::fs::File::open("tests/images/webp/extended_images/lossy_alpha.webp")
+            .map(std::io::BufReader::new)
+            .unwrap();
+        let decoder = WebPDecoder::new(file).unwrap();
+        assert_eq!(decoder.color_type(), ColorType::Rgba8);
+
+        let decoded = crate::DynamicImage::from_decoder(decoder)
+            .unwrap()
+            .into_rgba8();
+        assert!(decoded.pixels().any(|p| p[3] != 255));
+
+        // The ALPH chunk is compressed losslessly so the alpha channel must match exactly, even
+        // though the color channels are lossy.
+        let reference =
+            crate::open("tests/reference/webp/extended_images/lossy_alpha.webp.73837b57.png")
+                .unwrap()
+                .into_rgba8();
+        assert_eq!(decoded.dimensions(), reference.dimensions());
+        for (p, q) in decoded.pixels().zip(reference.pixels()) {
+            assert_eq!(p[3], q[3]);
+        }
+    }
+
+    #[test]
+    fn read_dimensions_from_truncated_header() {
+        for (path, chunk) in [
+            ("lossy_images/simple-rgb.webp", b"VP8 "),
+            ("lossy_images/simple-gray.webp", b"VP8 "),
+            ("lossless_images/simple.webp", b"VP8L"),
+            ("lossless_images/multi-color.webp", b"VP8L"),
+            ("extended_images/lossy_alpha.webp", b"VP8X"),
+            ("extended_images/anim.webp", b"VP8X"),
+        ] {
+            let data = std::fs::read(format!("tests/images/webp/{path}")).unwrap();
+            assert_eq!(&data[12..16], chunk, "{path}");
+            let decoder = WebPDecoder::new(Cursor::new(&data)).unwrap();
+
+            let dimensions = read_dimensions(&data[..30]).unwrap();
+            assert_eq!(dimensions, decoder.dimensions(), "{path}");
+        }
+
+        // The 24-bit canvas size of an extended image, larger than a bitstream can hold.
+        let mut vp8x = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\x10\0\0\0".to_vec();
+        vp8x.extend_from_slice(&[0x3f, 0x42, 0x0f, 0xff, 0xff, 0x00]);
+        assert_eq!(read_dimensions(&vp8x[..]).unwrap(), (1_000_000, 65536));
+
+        assert!(matches!(
+            read_dimensions(&vp8x[..25]),
+            Err(ImageError::IoError(_))
+        ));
+        assert!(matches!(
+            read_dimensions(&b"RIFF\0\0\0\0WEBPALPH\0\0\0\0\0\0\0\0\0\0"[..]),
+            Err(ImageError::Decoding(_))
+        ));
+    }
+
     #[test]
     fn add_with_overflow_size() {
         let bytes = vec![
@@ -126,7 +586,7 @@ mod tests {
             0x49, 0x54, 0x55, 0x50, 0x4c, 0x54, 0x59, 0x50, 0x45, 0x33, 0x37, 0x44, 0x4d, 0x46,
         ];
 
-        let data = std::io::Cursor::new(bytes);
+        let data = Cursor::new(bytes);
 
         let _ = WebPDecoder::new(data);
     }
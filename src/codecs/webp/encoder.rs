@@ -1,15 +1,23 @@
 //! Encoding of WebP images.
 
-use std::io::Write;
+use std::io::{self, Write};
+
+use super::palette;
 
 use crate::{
-    error::{EncodingError, UnsupportedError, UnsupportedErrorKind},
-    ExtendedColorType, ImageEncoder, ImageError, ImageFormat, ImageResult,
+    animation::Frame,
+    error::{EncodingError, ParameterError, ParameterErrorKind},
+    ColorType, ExtendedColorType, ImageEncoder, ImageError, ImageFormat, ImageResult, Rgba,
 };
 
 /// WebP Encoder.
+///
+/// Only the lossless "VP8L" format is written, in which the alpha channel is always stored
+/// exactly. There is thus no separate quality setting for alpha as in lossy libwebp encoding.
 pub struct WebPEncoder<W> {
-    inner: image_webp::WebPEncoder<W>,
+    w: W,
+    color_indexing: bool,
+    dither: bool,
 }
 
 impl<W: Write> WebPEncoder<W> {
@@ -18,13 +26,40 @@ impl<W: Write> WebPEncoder<W> {
     /// Uses "VP8L" lossless encoding.
     pub fn new_lossless(w: W) -> Self {
         Self {
-            inner: image_webp::WebPEncoder::new(w),
+            w,
+            color_indexing: false,
+            dither: false,
         }
     }
 
+    /// Store images with at most 256 distinct colors as a color table and packed indices.
+    ///
+    /// This uses the color-indexing transform of the lossless format, which usually shrinks
+    /// such images considerably. Images with more colors are encoded as usual. Disabled by
+    /// default, which keeps the output identical to earlier versions.
+    pub fn with_color_indexing(mut self, enabled: bool) -> Self {
+        self.color_indexing = enabled;
+        self
+    }
+
+    /// Use ordered dithering when reducing 16 bit images to the 8 bits per channel of WebP.
+    ///
+    /// Smooth gradients lose most of their levels in the reduction and show visible bands.
+    /// Dithering spreads the rounding error over a fine, regular pattern instead, so that the
+    /// average over a few pixels stays close to the original. Only the color channels are
+    /// dithered, alpha is always rounded. Disabled by default, which rounds every sample to
+    /// the nearest 8 bit value.
+    pub fn with_dithering(mut self, enabled: bool) -> Self {
+        self.dither = enabled;
+        self
+    }
+
     /// Encode image data with the indicated color type.
     ///
-    /// The encoder requires image data be Rgb8 or Rgba8.
+    /// The encoder requires image data be L8, La8, Rgb8 or Rgba8. Bgr8 and Bgra8 data is
+    /// reordered before encoding. L16, La16, Rgb16 and Rgba16 data, with samples in native
+    /// endianness, is reduced to 8 bits per channel first, see
+    /// [`with_dithering`](Self::with_dithering).
     ///
     /// # Panics
     ///
@@ -45,24 +80,91 @@ impl<W: Write> WebPEncoder<W> {
             buf.len(),
         );
 
-        let color_type = match color_type {
-            ExtendedColorType::L8 => image_webp::ColorType::L8,
-            ExtendedColorType::La8 => image_webp::ColorType::La8,
-            ExtendedColorType::Rgb8 => image_webp::ColorType::Rgb8,
-            ExtendedColorType::Rgba8 => image_webp::ColorType::Rgba8,
-            _ => {
-                return Err(ImageError::Unsupported(
-                    UnsupportedError::from_format_and_kind(
-                        ImageFormat::WebP.into(),
-                        UnsupportedErrorKind::Color(color_type),
-                    ),
-                ))
+        if let Some((rgb, color_type)) = color_type.swizzle_bgr(buf) {
+            return self.encode(&rgb, width, height, color_type);
+        }
+
+        if let Some((reduced, color_type)) = self.reduce_16bit(buf, width, color_type) {
+            return self.encode(&reduced, width, height, color_type);
+        }
+
+        let color_type = match color_type.to_color_type() {
+            Some(ColorType::L8) => image_webp::ColorType::L8,
+            Some(ColorType::La8) => image_webp::ColorType::La8,
+            Some(ColorType::Rgb8) => image_webp::ColorType::Rgb8,
+            Some(ColorType::Rgba8) => image_webp::ColorType::Rgba8,
+            _ => return Err(ImageError::unsupported_color(ImageFormat::WebP, color_type)),
+        };
+
+        if self.color_indexing {
+            let rgba: Vec<u8> = match color_type {
+                image_webp::ColorType::L8 => buf.iter().flat_map(|&l| [l, l, l, 0xff]).collect(),
+                image_webp::ColorType::La8 => buf
+                    .chunks_exact(2)
+                    .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                    .collect(),
+                image_webp::ColorType::Rgb8 => buf
+                    .chunks_exact(3)
+                    .flat_map(|p| [p[0], p[1], p[2], 0xff])
+                    .collect(),
+                image_webp::ColorType::Rgba8 => buf.to_vec(),
+            };
+            if let Some(bitstream) = palette::encode_indexed(&rgba, width, height) {
+                let mut w = CountingWriter::new(self.w);
+                let riff_size = 4 + 8 + bitstream.len() + bitstream.len() % 2;
+                w.stage("RIFF header", |w| write_riff_header(w, riff_size as u32))?;
+                w.stage("VP8L chunk", |w| write_chunk(w, b"VP8L", &bitstream))?;
+                return Ok(());
             }
+        }
+
+        let mut w = CountingWriter::new(self.w);
+        match image_webp::WebPEncoder::new(&mut w).encode(buf, width, height, color_type) {
+            Err(image_webp::EncodingError::IoError(e)) => Err(w.error("image", e)),
+            result => result.map_err(ImageError::from_webp_encode),
+        }
+    }
+}
+
+impl<W> WebPEncoder<W> {
+    /// Reduce 16 bit samples to 8 bits, returning `None` for any other color type.
+    fn reduce_16bit(
+        &self,
+        buf: &[u8],
+        width: u32,
+        color_type: ExtendedColorType,
+    ) -> Option<(Vec<u8>, ExtendedColorType)> {
+        const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+        let (reduced, has_alpha) = match color_type {
+            ExtendedColorType::L16 => (ExtendedColorType::L8, false),
+            ExtendedColorType::La16 => (ExtendedColorType::La8, true),
+            ExtendedColorType::Rgb16 => (ExtendedColorType::Rgb8, false),
+            ExtendedColorType::Rgba16 => (ExtendedColorType::Rgba8, true),
+            _ => return None,
         };
+        let channels = usize::from(color_type.channel_count());
+        let width = width as usize;
+
+        let samples = buf
+            .chunks_exact(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .enumerate()
+            .map(|(index, sample)| {
+                let (pixel, channel) = (index / channels, index % channels);
+                let is_alpha = has_alpha && channel == channels - 1;
+                if !self.dither || is_alpha {
+                    return ((u32::from(sample) + 128) / 257) as u8;
+                }
+                // Offset each sample by a threshold from the matrix before cutting off the
+                // fraction, which rounds up for a share of pixels matching the fraction.
+                let threshold = BAYER[pixel / width % 4][pixel % width % 4];
+                let level = f32::from(sample) / 257.0 + (f32::from(threshold) + 0.5) / 16.0;
+                level.floor().min(255.0) as u8
+            })
+            .collect();
 
-        self.inner
-            .encode(buf, width, height, color_type)
-            .map_err(ImageError::from_webp_encode)
+        Some((samples, reduced))
     }
 }
 
@@ -79,6 +181,215 @@ impl<W: Write> ImageEncoder for WebPEncoder<W> {
     }
 }
 
+/// Animated WebP encoder.
+///
+/// Frames are encoded losslessly and buffered in memory, the file is written by [`finish`].
+/// Every frame is disposed to the background color before the next one is drawn.
+///
+/// [`finish`]: AnimatedWebPEncoder::finish
+pub struct AnimatedWebPEncoder<W> {
+    w: W,
+    width: u32,
+    height: u32,
+    background_color: Rgba<u8>,
+    frames: Vec<u8>,
+}
+
+impl<W: Write> AnimatedWebPEncoder<W> {
+    /// Create a new encoder for an animation with a canvas of `width` x `height` pixels.
+    ///
+    /// The background color defaults to transparent black and the animation loops forever.
+    pub fn new(w: W, width: u32, height: u32) -> Self {
+        Self {
+            w,
+            width,
+            height,
+            background_color: Rgba([0, 0, 0, 0]),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Set the color the canvas is cleared to when a frame is disposed.
+    pub fn set_background_color(&mut self, color: Rgba<u8>) {
+        self.background_color = color;
+    }
+
+    /// Encode one frame of animation.
+    ///
+    /// The frame offsets must be even, as required by the WebP format, and the frame must lie
+    /// within the canvas.
+    pub fn encode_frame(&mut self, frame: Frame) -> ImageResult<()> {
+        let (left, top) = (frame.left(), frame.top());
+        let delay = frame.delay().into_ratio().to_integer();
+        let buffer = frame.into_straight_alpha().into_buffer();
+        let (width, height) = buffer.dimensions();
+
+        let fits = |offset: u32, length: u32, canvas: u32| {
+            offset % 2 == 0
+                && length > 0
+                && offset
+                    .checked_add(length)
+                    .map_or(false, |end| end <= canvas)
+        };
+        if !fits(left, width, self.width) || !fits(top, height, self.height) {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            )));
+        }
+
+        let mut image = Vec::new();
+        image_webp::WebPEncoder::new(&mut image)
+            .encode(buffer.as_raw(), width, height, image_webp::ColorType::Rgba8)
+            .map_err(ImageError::from_webp_encode)?;
+        // Skip the RIFF header of the simple file format, keeping the complete `VP8L` chunk.
+        let bitstream = &image[12..];
+
+        let mut anmf = Vec::with_capacity(16 + bitstream.len());
+        anmf.extend_from_slice(&u24_field(left / 2, "frame offset")?);
+        anmf.extend_from_slice(&u24_field(top / 2, "frame offset")?);
+        anmf.extend_from_slice(&u24_field(width - 1, "frame size")?);
+        anmf.extend_from_slice(&u24_field(height - 1, "frame size")?);
+        anmf.extend_from_slice(&u24_field(delay.min(0xff_ffff), "frame delay")?);
+        // Alpha blending, dispose to background.
+        anmf.push(0b01);
+        anmf.extend_from_slice(bitstream);
+
+        if u32::try_from(anmf.len()).is_err() {
+            return Err(too_large("frame"));
+        }
+        write_chunk(&mut self.frames, b"ANMF", &anmf)?;
+        Ok(())
+    }
+
+    /// Encodes Frames.
+    pub fn encode_frames<F>(&mut self, frames: F) -> ImageResult<()>
+    where
+        F: IntoIterator<Item = Frame>,
+    {
+        for frame in frames {
+            self.encode_frame(frame)?;
+        }
+        Ok(())
+    }
+
+    /// Try to encode a collection of `ImageResult<Frame>` objects.
+    /// Use this function to encode an `animation::Frames` like iterator, such as the frames of a
+    /// decoded GIF or APNG, which keep their delays.
+    /// Whenever an `Err` item is encountered, that value is returned without further actions.
+    pub fn try_encode_frames<F>(&mut self, frames: F) -> ImageResult<()>
+    where
+        F: IntoIterator<Item = ImageResult<Frame>>,
+    {
+        for frame in frames {
+            self.encode_frame(frame?)?;
+        }
+        Ok(())
+    }
+
+    /// Write the animation, consisting of all frames encoded so far.
+    pub fn finish(mut self) -> ImageResult<()> {
+        if self.frames.is_empty() {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::Generic("an animation needs at least one frame".into()),
+            )));
+        }
+
+        let mut vp8x = Vec::with_capacity(10);
+        // Animation and alpha flags.
+        vp8x.extend_from_slice(&[0b0001_0010, 0, 0, 0]);
+        vp8x.extend_from_slice(&u24_field(self.width - 1, "canvas size")?);
+        vp8x.extend_from_slice(&u24_field(self.height - 1, "canvas size")?);
+
+        // The background color is stored in BGRA order, followed by the loop count.
+        let Rgba([r, g, b, a]) = self.background_color;
+        let anim = [b, g, r, a, 0, 0];
+
+        let riff_size = 4 + (8 + vp8x.len()) + (8 + anim.len()) + self.frames.len();
+        let riff_size = u32::try_from(riff_size).map_err(|_| too_large("animation"))?;
+
+        let mut w = CountingWriter::new(&mut self.w);
+        w.stage("RIFF header", |w| write_riff_header(w, riff_size))?;
+        w.stage("VP8X chunk", |w| write_chunk(w, b"VP8X", &vp8x))?;
+        w.stage("ANIM chunk", |w| write_chunk(w, b"ANIM", &anim))?;
+        w.stage("animation frames", |w| w.write_all(&self.frames))
+    }
+}
+
+/// Counts the bytes written so far, to report how far the output got when writing fails.
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        CountingWriter { inner, written: 0 }
+    }
+
+    /// Write one part of the file, naming it in the error if that fails.
+    fn stage(
+        &mut self,
+        stage: &str,
+        write: impl FnOnce(&mut Self) -> io::Result<()>,
+    ) -> ImageResult<()> {
+        write(self).map_err(|e| self.error(stage, e))
+    }
+
+    /// An error that keeps the kind of `e` but says which part of the file was being written.
+    fn error(&self, stage: &str, e: io::Error) -> ImageError {
+        let message = format!(
+            "writing the WebP {stage} failed after {} bytes: {e}",
+            self.written
+        );
+        ImageError::IoError(io::Error::new(e.kind(), message))
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn write_riff_header<W: Write>(mut w: W, riff_size: u32) -> io::Result<()> {
+    w.write_all(b"RIFF")?;
+    w.write_all(&riff_size.to_le_bytes())?;
+    w.write_all(b"WEBP")
+}
+
+/// Write a RIFF chunk, callers check that the size of `data` fits into its 32 bit field.
+fn write_chunk<W: Write>(mut w: W, name: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let size =
+        u32::try_from(data.len()).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+    w.write_all(name)?;
+    w.write_all(&size.to_le_bytes())?;
+    w.write_all(data)?;
+    if data.len() % 2 == 1 {
+        w.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+/// The 24 bit little endian field used for offsets and sizes in animations.
+fn u24_field(value: u32, what: &str) -> ImageResult<[u8; 3]> {
+    match value.to_le_bytes() {
+        [a, b, c, 0] => Ok([a, b, c]),
+        _ => Err(too_large(what)),
+    }
+}
+
+fn too_large(what: &str) -> ImageError {
+    ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::Generic(
+        format!("the {what} is too large for a WebP file"),
+    )))
+}
+
 impl ImageError {
     fn from_webp_encode(e: image_webp::EncodingError) -> Self {
         match e {
@@ -90,7 +401,8 @@ impl ImageError {
 
 #[cfg(test)]
 mod tests {
-    use crate::{ImageEncoder, RgbaImage};
+    use crate::{AnimationDecoder, Delay, Frame, Rgba};
+    use crate::{ImageEncoder, ImageError, RgbaImage};
 
     #[test]
     fn write_webp() {
@@ -112,4 +424,366 @@ mod tests {
 
         assert_eq!(img, img2);
     }
+
+    #[test]
+    fn write_webp_bgra() {
+        let img = RgbaImage::from_raw(10, 6, (0..240).collect()).unwrap();
+        let bgra: Vec<u8> = img
+            .pixels()
+            .flat_map(|&Rgba([r, g, b, a])| [b, g, r, a])
+            .collect();
+
+        let mut output = Vec::new();
+        super::WebPEncoder::new_lossless(&mut output)
+            .write_image(
+                &bgra,
+                img.width(),
+                img.height(),
+                crate::ExtendedColorType::Bgra8,
+            )
+            .unwrap();
+
+        let img2 = crate::load_from_memory_with_format(&output, crate::ImageFormat::WebP)
+            .unwrap()
+            .to_rgba8();
+
+        assert_eq!(img, img2);
+    }
+
+    #[test]
+    fn webp_lossless_deterministic() {
+        let img = RgbaImage::from_fn(67, 41, |x, y| {
+            Rgba([(x * 3) as u8, (y * 5) as u8, (x ^ y) as u8, (x + y) as u8])
+        });
+
+        let encode = || {
+            let mut output = Vec::new();
+            super::WebPEncoder::new_lossless(&mut output)
+                .write_image(
+                    img.inner_pixels(),
+                    img.width(),
+                    img.height(),
+                    crate::ExtendedColorType::Rgba8,
+                )
+                .unwrap();
+            output
+        };
+
+        let first = encode();
+        assert_eq!(first, encode());
+
+        let decoded = crate::load_from_memory_with_format(&first, crate::ImageFormat::WebP)
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(img, decoded);
+    }
+
+    fn encode_rgba16(img: &crate::buffer_::Rgba16Image, dither: bool) -> RgbaImage {
+        let mut output = Vec::new();
+        super::WebPEncoder::new_lossless(&mut output)
+            .with_dithering(dither)
+            .write_image(
+                bytemuck::cast_slice(img.as_raw()),
+                img.width(),
+                img.height(),
+                crate::ExtendedColorType::Rgba16,
+            )
+            .unwrap();
+        crate::load_from_memory_with_format(&output, crate::ImageFormat::WebP)
+            .unwrap()
+            .to_rgba8()
+    }
+
+    #[test]
+    fn write_webp_16bit_dithered() {
+        // A shallow gradient covering only a handful of 8 bit levels across its width.
+        let gradient = crate::buffer_::Rgba16Image::from_fn(256, 16, |x, _| {
+            let v = 0x4000 + 4 * x as u16;
+            Rgba([v, v, 0x8000 - v, 0xffff])
+        });
+
+        let plain = encode_rgba16(&gradient, false);
+        let dithered = encode_rgba16(&gradient, true);
+        assert_eq!(
+            plain,
+            crate::DynamicImage::ImageRgba16(gradient.clone()).to_rgba8()
+        );
+        assert!(dithered.pixels().all(|p| p[3] == 255));
+
+        // Rounding turns the gradient into flat bands, so the error of averages over small
+        // blocks follows a sawtooth of up to half a level. Dithering keeps them on the gradient.
+        let block_error = |image: &RgbaImage, x0: u32, c: usize| {
+            let (mut sum, mut expected) = (0.0, 0.0);
+            for (x, y) in (x0..x0 + 4).flat_map(|x| (0..16).map(move |y| (x, y))) {
+                sum += f32::from(image.get_pixel(x, y)[c]);
+                expected += f32::from(gradient.get_pixel(x, y)[c]) / 257.0;
+            }
+            (sum - expected).abs() / 64.0
+        };
+        for c in 0..3 {
+            let max_error = |image| {
+                (0..256)
+                    .step_by(4)
+                    .map(|x| block_error(image, x, c))
+                    .fold(0.0, f32::max)
+            };
+            let (plain_error, dithered_error) = (max_error(&plain), max_error(&dithered));
+            assert!(plain_error > 0.3, "{plain_error}");
+            assert!(dithered_error < 0.1, "{dithered_error}");
+        }
+    }
+
+    fn encode_indexed(img: &RgbaImage, color_indexing: bool) -> Vec<u8> {
+        let mut output = Vec::new();
+        super::WebPEncoder::new_lossless(&mut output)
+            .with_color_indexing(color_indexing)
+            .write_image(
+                img.inner_pixels(),
+                img.width(),
+                img.height(),
+                crate::ExtendedColorType::Rgba8,
+            )
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn webp_color_indexing() {
+        let colors = [
+            Rgba([255, 0, 0, 255]),
+            Rgba([0, 128, 0, 255]),
+            Rgba([10, 20, 30, 0]),
+            Rgba([250, 250, 250, 255]),
+        ];
+        let img = RgbaImage::from_fn(123, 77, |x, y| {
+            colors[((x * 7 + y * 13 + x * y) % 17 % 4) as usize]
+        });
+
+        let plain = encode_indexed(&img, false);
+        let indexed = encode_indexed(&img, true);
+        assert!(
+            indexed.len() * 2 < plain.len(),
+            "{} {}",
+            indexed.len(),
+            plain.len()
+        );
+
+        let decoded = crate::load_from_memory_with_format(&indexed, crate::ImageFormat::WebP)
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(img, decoded);
+    }
+
+    quickcheck! {
+        fn fuzz_webp_valid_image(width: u8, height: u8, palette: Vec<u32>, indices: Vec<u16>) -> bool {
+            // Images with a palette of up to 100 colors, to exercise both the color-indexing
+            // path and the general one.
+            let (width, height) = (u32::from(width % 40) + 1, u32::from(height % 40) + 1);
+            let palette = if palette.is_empty() { vec![0xff00_00ff] } else { palette };
+            let indices = if indices.is_empty() { vec![0] } else { indices };
+            let img = RgbaImage::from_fn(width, height, |x, y| {
+                let i = indices[(x + y * width) as usize % indices.len()];
+                Rgba(palette[usize::from(i) % palette.len()].to_le_bytes())
+            });
+
+            [false, true].into_iter().all(|color_indexing| {
+                let decoded = crate::load_from_memory_with_format(
+                    &encode_indexed(&img, color_indexing),
+                    crate::ImageFormat::WebP,
+                );
+                decoded.map_or(false, |decoded| decoded.to_rgba8() == img)
+            })
+        }
+    }
+
+    #[test]
+    fn webp_color_indexing_table_sizes() {
+        for count in [1, 2, 3, 5, 16, 17, 200, 256] {
+            let img = RgbaImage::from_fn(37, 9, |x, y| {
+                let i = (x + y * 37) % count;
+                Rgba([(i * 3) as u8, (i * 7) as u8, i as u8, 255])
+            });
+            let decoded = crate::load_from_memory_with_format(
+                &encode_indexed(&img, true),
+                crate::ImageFormat::WebP,
+            )
+            .unwrap()
+            .to_rgba8();
+            assert_eq!(img, decoded, "{count} colors");
+        }
+
+        // Images with too many colors are encoded as usual.
+        let img = RgbaImage::from_fn(37, 9, |x, y| Rgba([x as u8, y as u8, 0, 255]));
+        assert_eq!(encode_indexed(&img, true), encode_indexed(&img, false));
+    }
+
+    fn encode_animation(background: Rgba<u8>, frames: Vec<Frame>) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut encoder = super::AnimatedWebPEncoder::new(&mut output, 4, 4);
+        encoder.set_background_color(background);
+        encoder.encode_frames(frames).unwrap();
+        encoder.finish().unwrap();
+        output
+    }
+
+    #[test]
+    #[cfg(feature = "gif")]
+    fn transcode_gif_animation() {
+        use crate::codecs::gif::{GifDecoder, GifEncoder};
+        use std::io::Cursor;
+
+        let delays = [100, 250].map(|ms| Delay::from_numer_denom_ms(ms, 1));
+        let colors = [Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255])];
+        let mut gif = Vec::new();
+        GifEncoder::new(&mut gif)
+            .encode_frames(colors.iter().zip(delays).map(|(&color, delay)| {
+                Frame::from_parts(RgbaImage::from_pixel(6, 4, color), 0, 0, delay)
+            }))
+            .unwrap();
+
+        let frames = GifDecoder::new(Cursor::new(gif)).unwrap().into_frames();
+        let mut webp = Vec::new();
+        let mut encoder = super::AnimatedWebPEncoder::new(&mut webp, 6, 4);
+        encoder.try_encode_frames(frames).unwrap();
+        encoder.finish().unwrap();
+
+        let decoder = super::super::WebPDecoder::new(Cursor::new(webp)).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 2);
+        for ((frame, delay), color) in frames.iter().zip(delays).zip(colors) {
+            assert_eq!(frame.delay(), delay);
+            assert!(frame.buffer().pixels().all(|p| *p == color));
+        }
+    }
+
+    #[test]
+    fn animation_background_chunk() {
+        let frame = Frame::new(RgbaImage::from_pixel(4, 4, Rgba([1, 2, 3, 255])));
+        let output = encode_animation(Rgba([10, 20, 30, 40]), vec![frame]);
+
+        let anim = output
+            .windows(4)
+            .position(|chunk| chunk == b"ANIM")
+            .unwrap();
+        assert_eq!(&output[anim + 4..anim + 8], &6u32.to_le_bytes());
+        assert_eq!(&output[anim + 8..anim + 14], &[30, 20, 10, 40, 0, 0]);
+    }
+
+    #[test]
+    fn animation_dispose_to_background() {
+        // The decoder does not reorder the channels of the background color, so pick one that
+        // reads the same in RGBA and BGRA order.
+        let background = Rgba([200, 50, 200, 255]);
+        let delay = Delay::from_numer_denom_ms(100, 1);
+        let first = Frame::from_parts(
+            RgbaImage::from_pixel(4, 4, Rgba([0, 255, 0, 255])),
+            0,
+            0,
+            delay,
+        );
+        let second = Frame::from_parts(
+            RgbaImage::from_pixel(2, 2, Rgba([0, 0, 255, 255])),
+            2,
+            2,
+            delay,
+        );
+        let output = encode_animation(background, vec![first, second]);
+
+        let decoder = super::super::WebPDecoder::new(std::io::Cursor::new(output)).unwrap();
+        assert!(decoder.has_animation());
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].delay(), delay);
+
+        let canvas = frames[1].buffer();
+        assert_eq!(*canvas.get_pixel(0, 0), background);
+        assert_eq!(*canvas.get_pixel(1, 3), background);
+        assert_eq!(*canvas.get_pixel(3, 3), Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn animation_rejects_odd_offsets() {
+        let mut encoder = super::AnimatedWebPEncoder::new(Vec::new(), 4, 4);
+        let frame = Frame::from_parts(RgbaImage::new(2, 2), 1, 0, Delay::from_numer_denom_ms(0, 1));
+        assert!(encoder.encode_frame(frame).is_err());
+    }
+
+    #[test]
+    fn animation_rejects_frames_past_the_end() {
+        let mut encoder = super::AnimatedWebPEncoder::new(Vec::new(), u32::MAX, 4);
+        let frame = Frame::from_parts(RgbaImage::new(2, 2), u32::MAX - 1, 0, Delay::from_millis(0));
+        let error = encoder.encode_frame(frame).unwrap_err();
+        assert!(
+            matches!(&error, ImageError::Parameter(e) if e.kind() == crate::error::ParameterErrorKind::DimensionMismatch),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn animation_rejects_fields_too_large() {
+        let frame = |left| Frame::from_parts(RgbaImage::new(2, 2), left, 0, Delay::from_millis(0));
+
+        let mut encoder = super::AnimatedWebPEncoder::new(Vec::new(), u32::MAX, 4);
+        assert!(encoder.encode_frame(frame(1 << 24)).is_ok());
+        let error = encoder.encode_frame(frame(1 << 25)).unwrap_err();
+        assert!(matches!(error, ImageError::Parameter(_)), "{error:?}");
+
+        let mut encoder = super::AnimatedWebPEncoder::new(Vec::new(), (1 << 24) + 1, 4);
+        encoder.encode_frame(frame(0)).unwrap();
+        let error = encoder.finish().unwrap_err();
+        assert!(matches!(error, ImageError::Parameter(_)), "{error:?}");
+    }
+
+    /// Accepts a fixed number of bytes, then fails every write.
+    struct FailAfter {
+        remaining: usize,
+    }
+
+    impl std::io::Write for FailAfter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(std::io::ErrorKind::BrokenPipe.into());
+            }
+            let written = buf.len().min(self.remaining);
+            self.remaining -= written;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn assert_failed_stage(result: crate::ImageResult<()>, expected: &str) {
+        match result {
+            Err(ImageError::IoError(e)) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::BrokenPipe);
+                let message = e.to_string();
+                assert!(message.contains(expected), "{message}");
+            }
+            other => panic!("expected an io error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn write_failure_names_the_stage() {
+        let img = RgbaImage::from_pixel(4, 4, Rgba([1, 2, 3, 255]));
+        let encode = |remaining, color_indexing| {
+            super::WebPEncoder::new_lossless(FailAfter { remaining })
+                .with_color_indexing(color_indexing)
+                .write_image(img.inner_pixels(), 4, 4, crate::ExtendedColorType::Rgba8)
+        };
+        assert_failed_stage(encode(5, false), "WebP image failed after 5 bytes");
+        assert_failed_stage(encode(5, true), "WebP RIFF header failed after 5 bytes");
+        assert_failed_stage(encode(14, true), "WebP VP8L chunk failed after 14 bytes");
+
+        let animate = |remaining| {
+            let mut encoder = super::AnimatedWebPEncoder::new(FailAfter { remaining }, 4, 4);
+            encoder.encode_frame(Frame::new(img.clone())).unwrap();
+            encoder.finish()
+        };
+        assert_failed_stage(animate(20), "WebP VP8X chunk failed after 20 bytes");
+        assert_failed_stage(animate(40), "WebP ANIM chunk failed after 40 bytes");
+        assert_failed_stage(animate(50), "WebP animation frames failed after 50 bytes");
+    }
 }
@@ -1,7 +1,10 @@
 //! Decoding and Encoding of WebP Images
 
+mod bit_writer;
 mod decoder;
 mod encoder;
+mod palette;
 
-pub use self::decoder::WebPDecoder;
-pub use self::encoder::WebPEncoder;
+pub use self::bit_writer::BitWriter;
+pub use self::decoder::{read_dimensions, WebPDecoder, WebPFeatures};
+pub use self::encoder::{AnimatedWebPEncoder, WebPEncoder};
@@ -0,0 +1,345 @@
+//! Lossless "VP8L" encoding of images with few colors through the color-indexing transform.
+//!
+//! The general purpose encoder of `image-webp` stores every pixel with its full color. Images with
+//! at most 256 colors can instead store a color table followed by an image of indices, with up to
+//! eight indices packed into the green channel of a single pixel.
+
+use std::collections::{BinaryHeap, HashMap};
+
+use super::BitWriter;
+
+/// Largest width or height of a "VP8L" image.
+const MAX_DIMENSION: u32 = 1 << 14;
+/// Longest backward reference supported by the format.
+const MAX_RUN: usize = 4096;
+/// Number of symbols of the green alphabet: literals followed by the length prefix codes.
+const GREEN_SYMBOLS: usize = 256 + 24;
+/// Order in which the lengths of the code length code are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    17, 18, 0, 1, 2, 3, 4, 5, 16, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+/// Encode `RGBA8` pixels as a "VP8L" bitstream using the color-indexing transform.
+///
+/// Returns `None` if the image has more than 256 distinct colors or dimensions that the format
+/// cannot represent.
+pub(super) fn encode_indexed(rgba: &[u8], width: u32, height: u32) -> Option<Vec<u8>> {
+    if width == 0 || width > MAX_DIMENSION || height == 0 || height > MAX_DIMENSION {
+        return None;
+    }
+
+    let pixels: Vec<u32> = rgba
+        .chunks_exact(4)
+        .map(|p| u32::from_be_bytes([p[3], p[0], p[1], p[2]]))
+        .collect();
+
+    let mut indices = HashMap::new();
+    for &pixel in &pixels {
+        if !indices.contains_key(&pixel) {
+            if indices.len() == 256 {
+                return None;
+            }
+            indices.insert(pixel, 0u8);
+        }
+    }
+    // Sorting the table keeps the differences between successive entries small.
+    let mut palette: Vec<u32> = indices.keys().copied().collect();
+    palette.sort_unstable();
+    for (index, color) in palette.iter().enumerate() {
+        indices.insert(*color, index as u8);
+    }
+
+    // Pack the indices of neighbouring pixels into the green channel, least significant first.
+    let width_bits = match palette.len() {
+        0..=2 => 3,
+        3..=4 => 2,
+        5..=16 => 1,
+        _ => 0,
+    };
+    let bits_per_index = 8 >> width_bits;
+    let packed_width = ((width + (1 << width_bits) - 1) >> width_bits) as usize;
+    let mut packed = vec![[0u8; 4]; packed_width * height as usize];
+    for (y, row) in pixels.chunks_exact(width as usize).enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            let shift = (x & ((1 << width_bits) - 1)) * bits_per_index;
+            packed[y * packed_width + (x >> width_bits)][2] |= indices[pixel] << shift;
+        }
+    }
+
+    let mut w = BitWriter::new();
+    w.write_bits(0x2f, 8);
+    w.write_bits(width - 1, 14);
+    w.write_bits(height - 1, 14);
+    let alpha_is_used = palette.iter().any(|&color| color >> 24 != 0xff);
+    w.write_bits(alpha_is_used.into(), 1);
+    w.write_bits(0, 3);
+
+    // The color-indexing transform, with its table coded as differences between entries.
+    w.write_bits(1, 1);
+    w.write_bits(3, 2);
+    w.write_bits(palette.len() as u32 - 1, 8);
+    let mut previous = [0u8; 4];
+    let table: Vec<[u8; 4]> = palette
+        .iter()
+        .map(|color| {
+            let color = color.to_be_bytes();
+            let delta = std::array::from_fn(|i| color[i].wrapping_sub(previous[i]));
+            previous = color;
+            delta
+        })
+        .collect();
+    // No color cache.
+    w.write_bits(0, 1);
+    write_entropy_coded_pixels(&mut w, &table);
+    // No further transforms.
+    w.write_bits(0, 1);
+
+    // No color cache and no meta prefix codes.
+    w.write_bits(0, 1);
+    w.write_bits(0, 1);
+    write_entropy_coded_pixels(&mut w, &packed);
+
+    Some(w.into_bytes())
+}
+
+/// A pixel literal or a copy of the preceding pixel, repeated a number of times.
+enum Token {
+    Literal([u8; 4]),
+    Run(usize),
+}
+
+/// Write the prefix codes and data of `ARGB` pixels, coding repeated pixels as backward
+/// references to the previous pixel.
+fn write_entropy_coded_pixels(w: &mut BitWriter, pixels: &[[u8; 4]]) {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < pixels.len() {
+        tokens.push(Token::Literal(pixels[i]));
+        let run = pixels[i + 1..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&p| p == pixels[i])
+            .count();
+        if run > 0 {
+            tokens.push(Token::Run(run));
+        }
+        i += 1 + run;
+    }
+
+    let mut green = vec![0u32; GREEN_SYMBOLS];
+    let mut red = vec![0u32; 256];
+    let mut blue = vec![0u32; 256];
+    let mut alpha = vec![0u32; 256];
+    for token in &tokens {
+        match *token {
+            Token::Literal([a, r, g, b]) => {
+                green[usize::from(g)] += 1;
+                red[usize::from(r)] += 1;
+                blue[usize::from(b)] += 1;
+                alpha[usize::from(a)] += 1;
+            }
+            Token::Run(length) => green[256 + prefix_code(length).0] += 1,
+        }
+    }
+
+    let green = PrefixCode::write(w, &green);
+    let red = PrefixCode::write(w, &red);
+    let blue = PrefixCode::write(w, &blue);
+    let alpha = PrefixCode::write(w, &alpha);
+    // Every backward reference uses distance code 2, which refers to the pixel to the left.
+    let mut distance = [0u32; 40];
+    distance[1] = 1;
+    let distance = PrefixCode::write(w, &distance);
+
+    for token in &tokens {
+        match *token {
+            Token::Literal([a, r, g, b]) => {
+                green.write_symbol(w, usize::from(g));
+                red.write_symbol(w, usize::from(r));
+                blue.write_symbol(w, usize::from(b));
+                alpha.write_symbol(w, usize::from(a));
+            }
+            Token::Run(length) => {
+                let (symbol, extra_bits, extra) = prefix_code(length);
+                green.write_symbol(w, 256 + symbol);
+                w.write_bits(extra, extra_bits);
+                distance.write_symbol(w, 1);
+            }
+        }
+    }
+}
+
+/// Split a backward reference length into its prefix symbol and extra bits.
+fn prefix_code(value: usize) -> (usize, u8, u32) {
+    let value = value as u32 - 1;
+    if value < 4 {
+        return (value as usize, 0, 0);
+    }
+    let highest_bit = 31 - value.leading_zeros();
+    let second_highest_bit = (value >> (highest_bit - 1)) & 1;
+    let extra_bits = highest_bit - 1;
+    let symbol = 2 * highest_bit + second_highest_bit;
+    (
+        symbol as usize,
+        extra_bits as u8,
+        value & ((1 << extra_bits) - 1),
+    )
+}
+
+/// A canonical prefix code, as the code length and the bit-reversed code of every symbol.
+struct PrefixCode {
+    lengths: Vec<u8>,
+    codes: Vec<u16>,
+}
+
+impl PrefixCode {
+    /// Build a code for the given symbol frequencies and write its description.
+    fn write(w: &mut BitWriter, frequencies: &[u32]) -> Self {
+        let mut used = frequencies.iter().enumerate().filter(|(_, &f)| f > 0);
+        let first = used.next().map_or(0, |(symbol, _)| symbol);
+        if used.next().is_none() && first < 256 {
+            // A simple code with a single symbol, which takes up no bits in the data.
+            w.write_bits(1, 1);
+            w.write_bits(0, 1);
+            if first <= 1 {
+                w.write_bits(0, 1);
+                w.write_bits(first as u32, 1);
+            } else {
+                w.write_bits(1, 1);
+                w.write_bits(first as u32, 8);
+            }
+            return PrefixCode {
+                lengths: vec![0; frequencies.len()],
+                codes: vec![0; frequencies.len()],
+            };
+        }
+
+        let lengths = code_lengths(frequencies, 15);
+
+        // Code the lengths themselves, collapsing runs of unused symbols.
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < lengths.len() {
+            let zeros = lengths[i..]
+                .iter()
+                .take(138)
+                .take_while(|&&l| l == 0)
+                .count();
+            if zeros >= 11 {
+                tokens.push((18, 7, zeros as u32 - 11));
+                i += zeros;
+            } else if zeros >= 3 {
+                tokens.push((17, 3, zeros as u32 - 3));
+                i += zeros;
+            } else {
+                tokens.push((usize::from(lengths[i]), 0, 0));
+                i += 1;
+            }
+        }
+
+        let mut length_frequencies = [0u32; 19];
+        for &(symbol, _, _) in &tokens {
+            length_frequencies[symbol] += 1;
+        }
+        // A code needs at least two symbols to be complete.
+        if length_frequencies.iter().filter(|&&f| f > 0).count() == 1 {
+            let unused = length_frequencies.iter().position(|&f| f == 0).unwrap();
+            length_frequencies[unused] = 1;
+        }
+        let length_code = PrefixCode::from_lengths(code_lengths(&length_frequencies, 7));
+
+        let count = CODE_LENGTH_ORDER
+            .iter()
+            .rposition(|&symbol| length_code.lengths[symbol] > 0)
+            .map_or(0, |i| i + 1)
+            .max(4);
+        w.write_bits(0, 1);
+        w.write_bits(count as u32 - 4, 4);
+        for &symbol in &CODE_LENGTH_ORDER[..count] {
+            w.write_bits(length_code.lengths[symbol].into(), 3);
+        }
+        // All symbols are described.
+        w.write_bits(0, 1);
+        for (symbol, extra_bits, extra) in tokens {
+            length_code.write_symbol(w, symbol);
+            w.write_bits(extra, extra_bits);
+        }
+
+        PrefixCode::from_lengths(lengths)
+    }
+
+    /// Assign canonical codes to the given code lengths.
+    fn from_lengths(lengths: Vec<u8>) -> Self {
+        let mut count = [0u16; 16];
+        for &length in &lengths {
+            count[usize::from(length)] += 1;
+        }
+        count[0] = 0;
+        let mut next = [0u16; 16];
+        for length in 1..16 {
+            next[length] = (next[length - 1] + count[length - 1]) << 1;
+        }
+
+        let codes = lengths
+            .iter()
+            .map(|&length| {
+                if length == 0 {
+                    return 0;
+                }
+                let code = next[usize::from(length)];
+                next[usize::from(length)] += 1;
+                code.reverse_bits() >> (16 - length)
+            })
+            .collect();
+        PrefixCode { lengths, codes }
+    }
+
+    fn write_symbol(&self, w: &mut BitWriter, symbol: usize) {
+        w.write_bits(self.codes[symbol].into(), self.lengths[symbol]);
+    }
+}
+
+/// Compute Huffman code lengths no longer than `limit` for at least two used symbols.
+///
+/// Frequencies are halved until the tree is shallow enough, which keeps the code complete.
+fn code_lengths(frequencies: &[u32], limit: u8) -> Vec<u8> {
+    let mut frequencies = frequencies.to_vec();
+    loop {
+        // Ties are broken by node index, which makes the code deterministic.
+        let mut heap: BinaryHeap<_> = frequencies
+            .iter()
+            .enumerate()
+            .filter(|(_, &f)| f > 0)
+            .map(|(symbol, &f)| std::cmp::Reverse((u64::from(f), symbol)))
+            .collect();
+        let mut parents = vec![usize::MAX; frequencies.len()];
+        while heap.len() > 1 {
+            let std::cmp::Reverse((f1, a)) = heap.pop().unwrap();
+            let std::cmp::Reverse((f2, b)) = heap.pop().unwrap();
+            let node = parents.len();
+            parents.push(usize::MAX);
+            parents[a] = node;
+            parents[b] = node;
+            heap.push(std::cmp::Reverse((f1 + f2, node)));
+        }
+
+        let mut depths = vec![0u8; parents.len()];
+        for node in (0..parents.len()).rev() {
+            if parents[node] != usize::MAX {
+                depths[node] = depths[parents[node]] + 1;
+            }
+        }
+
+        let lengths: Vec<u8> = frequencies
+            .iter()
+            .zip(&depths)
+            .map(|(&f, &depth)| if f > 0 { depth } else { 0 })
+            .collect();
+        if lengths.iter().all(|&length| length <= limit) {
+            return lengths;
+        }
+        for f in frequencies.iter_mut().filter(|f| **f > 0) {
+            *f = (*f / 2).max(1);
+        }
+    }
+}
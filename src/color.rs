@@ -6,6 +6,7 @@ use crate::traits::{Enlargeable, Pixel, Primitive};
 
 /// An enumeration over supported color types and bit depths
 #[derive(Copy, PartialEq, Eq, Debug, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum ColorType {
     /// Pixel is 8-bit luminance
@@ -87,6 +88,7 @@ impl ColorType {
 /// Another purpose is to advise users of a rough estimate of the accuracy and effort of the
 /// decoding from and encoding to such an image format.
 #[derive(Copy, PartialEq, Eq, Debug, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum ExtendedColorType {
     /// Pixel is 8-bit alpha
@@ -221,12 +223,51 @@ impl ExtendedColorType {
         }
     }
 
+    /// Returns the `ColorType` with the same layout, if there is one.
+    ///
+    /// This is the inverse of the conversion from `ColorType`. Types with fewer than 8 bits per
+    /// channel, a different channel order or an unknown layout have no equivalent.
+    pub fn to_color_type(self) -> Option<ColorType> {
+        Some(match self {
+            ExtendedColorType::L8 => ColorType::L8,
+            ExtendedColorType::La8 => ColorType::La8,
+            ExtendedColorType::Rgb8 => ColorType::Rgb8,
+            ExtendedColorType::Rgba8 => ColorType::Rgba8,
+            ExtendedColorType::L16 => ColorType::L16,
+            ExtendedColorType::La16 => ColorType::La16,
+            ExtendedColorType::Rgb16 => ColorType::Rgb16,
+            ExtendedColorType::Rgba16 => ColorType::Rgba16,
+            ExtendedColorType::Rgb32F => ColorType::Rgb32F,
+            ExtendedColorType::Rgba32F => ColorType::Rgba32F,
+            _ => return None,
+        })
+    }
+
     /// Returns the number of bytes required to hold a width x height image of this color type.
     pub(crate) fn buffer_size(self, width: u32, height: u32) -> u64 {
         let bpp = self.bits_per_pixel() as u64;
         let row_pitch = (width as u64 * bpp + 7) / 8;
         row_pitch.saturating_mul(height as u64)
     }
+
+    /// Reorder `Bgr8` and `Bgra8` data to `Rgb8` and `Rgba8`, for encoders which only accept the
+    /// latter.
+    ///
+    /// Returns `None` for all other color types, whose data can be used as is.
+    #[cfg(any(feature = "jpeg", feature = "png", feature = "webp"))]
+    pub(crate) fn swizzle_bgr(self, buf: &[u8]) -> Option<(Vec<u8>, ExtendedColorType)> {
+        let (channels, color) = match self {
+            ExtendedColorType::Bgr8 => (3, ExtendedColorType::Rgb8),
+            ExtendedColorType::Bgra8 => (4, ExtendedColorType::Rgba8),
+            _ => return None,
+        };
+
+        let mut swizzled = buf.to_vec();
+        for pixel in swizzled.chunks_exact_mut(channels) {
+            pixel.swap(0, 2);
+        }
+        Some((swizzled, color))
+    }
 }
 impl From<ColorType> for ExtendedColorType {
     fn from(c: ColorType) -> Self {
@@ -394,6 +435,23 @@ impl<T> From<[T; $channels]> for $ident<T> {
     }
 }
 
+impl<T> $ident<T> {
+    /// The channels of this pixel as a fixed size array.
+    ///
+    /// Unlike [`Pixel::channels`] the length is known at compile time, so loops over the
+    /// channels need no bounds checks and are easily unrolled.
+    #[inline(always)]
+    pub fn to_array(self) -> [T; $channels] {
+        self.0
+    }
+
+    /// Create a pixel from a fixed size array of its channels.
+    #[inline(always)]
+    pub const fn from_array(channels: [T; $channels]) -> Self {
+        Self(channels)
+    }
+}
+
 )* // END Structure definitions
 
     }
@@ -413,6 +471,113 @@ define_colors! {
     pub struct LumaA<T: Primitive>([T; 2, 1]) = "YA";
 }
 
+impl<T> Rgb<T> {
+    /// Create a color from its red, green and blue channels.
+    pub const fn new(r: T, g: T, b: T) -> Self {
+        Rgb([r, g, b])
+    }
+}
+
+impl<T> Rgba<T> {
+    /// Create a color from its red, green, blue and alpha channels.
+    pub const fn new(r: T, g: T, b: T, a: T) -> Self {
+        Rgba([r, g, b, a])
+    }
+}
+
+impl<T> Luma<T> {
+    /// Create a color from its luminance.
+    pub const fn new(l: T) -> Self {
+        Luma([l])
+    }
+}
+
+impl<T> LumaA<T> {
+    /// Create a color from its luminance and alpha channels.
+    pub const fn new(l: T, a: T) -> Self {
+        LumaA([l, a])
+    }
+}
+
+impl Rgb<u8> {
+    /// Black, `[0, 0, 0]`.
+    pub const BLACK: Self = Rgb([0, 0, 0]);
+    /// White, `[255, 255, 255]`.
+    pub const WHITE: Self = Rgb([255, 255, 255]);
+    /// Red, `[255, 0, 0]`.
+    pub const RED: Self = Rgb([255, 0, 0]);
+    /// Green, `[0, 255, 0]`.
+    pub const GREEN: Self = Rgb([0, 255, 0]);
+    /// Blue, `[0, 0, 255]`.
+    pub const BLUE: Self = Rgb([0, 0, 255]);
+
+    /// Pack this color into the 16 bits of RGB565, as used by many small displays.
+    ///
+    /// Red takes the five most significant bits, followed by six bits of green and five bits of
+    /// blue. Every channel is rounded to the nearest level it can represent.
+    pub const fn to_rgb565(self) -> u16 {
+        let [r, g, b] = self.0;
+        let r = (r as u32 * 31 + 127) / 255;
+        let g = (g as u32 * 63 + 127) / 255;
+        let b = (b as u32 * 31 + 127) / 255;
+        (r << 11 | g << 5 | b) as u16
+    }
+
+    /// Unpack a color from RGB565, the inverse of [`Rgb::to_rgb565`] up to its rounding.
+    ///
+    /// The high bits of every channel are repeated in its low bits, which scales the levels to
+    /// the full range such that black and white are exact.
+    pub const fn from_rgb565(packed: u16) -> Self {
+        let (r, g, b) = (
+            (packed >> 11) as u8,
+            (packed >> 5 & 0x3f) as u8,
+            (packed & 0x1f) as u8,
+        );
+        Rgb([r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2])
+    }
+}
+
+impl Rgba<u8> {
+    /// Opaque black, `[0, 0, 0, 255]`.
+    pub const BLACK: Self = Rgba([0, 0, 0, 255]);
+    /// Opaque white, `[255, 255, 255, 255]`.
+    pub const WHITE: Self = Rgba([255, 255, 255, 255]);
+    /// Opaque red, `[255, 0, 0, 255]`.
+    pub const RED: Self = Rgba([255, 0, 0, 255]);
+    /// Opaque green, `[0, 255, 0, 255]`.
+    pub const GREEN: Self = Rgba([0, 255, 0, 255]);
+    /// Opaque blue, `[0, 0, 255, 255]`.
+    pub const BLUE: Self = Rgba([0, 0, 255, 255]);
+    /// Fully transparent black, `[0, 0, 0, 0]`.
+    pub const TRANSPARENT: Self = Rgba([0, 0, 0, 0]);
+
+    /// Unpack a color from a `u32` written as `0xRRGGBBAA`, with red in the most significant
+    /// byte and alpha in the least significant one.
+    pub const fn from_u32_rgba(packed: u32) -> Self {
+        Rgba(packed.to_be_bytes())
+    }
+
+    /// Pack this color into a `u32` written as `0xRRGGBBAA`, the inverse of
+    /// [`Rgba::from_u32_rgba`].
+    pub const fn to_u32_rgba(self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+
+    /// Unpack a color from a `u32` written as `0xBBGGRRAA`, with blue in the most significant
+    /// byte and alpha in the least significant one.
+    pub const fn from_u32_bgra(packed: u32) -> Self {
+        let [b, g, r, a] = packed.to_be_bytes();
+        Rgba([r, g, b, a])
+    }
+
+    /// Pack this color into a `u32` written as `0xBBGGRRAA`, the inverse of
+    /// [`Rgba::from_u32_bgra`].
+    pub const fn to_u32_bgra(self) -> u32 {
+        let [r, g, b, a] = self.0;
+        u32::from_be_bytes([b, g, r, a])
+    }
+}
+
 /// Convert from one pixel component type to another. For example, convert from `u8` to `f32` pixel values.
 pub trait FromPrimitive<Component> {
     /// Converts from any pixel component type to this type.
@@ -751,7 +916,7 @@ impl<T: Primitive> Blend for LumaA<T> {
             fg_a.to_f32().unwrap() / max_t,
         );
 
-        let alpha_final = bg_a + fg_a - bg_a * fg_a;
+        let alpha_final = bg_a + fg_a * (1.0 - bg_a);
         if alpha_final == 0.0 {
             return;
         };
@@ -804,8 +969,8 @@ impl<T: Primitive> Blend for Rgba<T> {
             fg_a.to_f32().unwrap() / max_t,
         );
 
-        // Work out what the final alpha level will be
-        let alpha_final = bg_a + fg_a - bg_a * fg_a;
+        // Work out what the final alpha level will be, exactly one over an opaque background
+        let alpha_final = bg_a + fg_a * (1.0 - bg_a);
         if alpha_final == 0.0 {
             return;
         };
@@ -896,7 +1061,198 @@ impl<T: Primitive> Invert for Rgb<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::{Luma, LumaA, Pixel, Rgb, Rgba};
+    use super::{ExtendedColorType, Luma, LumaA, Pixel, Rgb, Rgba};
+    #[cfg(feature = "benchmarks")]
+    use test;
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        use super::ColorType;
+
+        let json = serde_json::to_string(&ColorType::Rgba16).unwrap();
+        assert_eq!(json, "\"Rgba16\"");
+        assert_eq!(
+            serde_json::from_str::<ColorType>(&json).unwrap(),
+            ColorType::Rgba16
+        );
+
+        for color in [ExtendedColorType::L1, ExtendedColorType::Unknown(12)] {
+            let json = serde_json::to_string(&color).unwrap();
+            assert_eq!(
+                serde_json::from_str::<ExtendedColorType>(&json).unwrap(),
+                color
+            );
+        }
+        assert!(serde_json::from_str::<ColorType>("\"Rgb4\"").is_err());
+    }
+
+    #[test]
+    fn alpha_accessors() {
+        let mut rgba = Rgba([10u8, 20, 30, 40]);
+        assert_eq!(rgba.alpha(), Some(40));
+        rgba.set_alpha(255);
+        assert_eq!(rgba, Rgba([10, 20, 30, 255]));
+
+        let mut luma_alpha = LumaA([1000u16, 2000]);
+        luma_alpha.set_alpha(3);
+        assert_eq!(luma_alpha.alpha(), Some(3));
+        assert_eq!(luma_alpha.0[0], 1000);
+
+        let mut rgb = Rgb([10u8, 20, 30]);
+        assert_eq!(rgb.alpha(), None);
+        rgb.set_alpha(0);
+        assert_eq!(rgb, Rgb([10, 20, 30]));
+        assert_eq!(Luma([0.5f32]).alpha(), None);
+    }
+
+    #[test]
+    fn from_slice_checked() {
+        assert_eq!(Rgba::from_slice_checked(&[1u8, 2, 3]), None);
+        assert_eq!(Rgba::from_slice_checked(&[1u8, 2, 3, 4, 5]), None);
+        assert_eq!(
+            Rgba::from_slice_checked(&[1u8, 2, 3, 4]),
+            Some(&Rgba([1, 2, 3, 4]))
+        );
+
+        let mut data = [1u16, 2, 3];
+        assert!(Rgba::from_slice_mut_checked(&mut data).is_none());
+        Rgb::from_slice_mut_checked(&mut data).unwrap().0[1] = 7;
+        assert_eq!(data, [1, 7, 3]);
+    }
+
+    #[test]
+    fn color_constants() {
+        assert_eq!(Rgba::TRANSPARENT, Rgba([0, 0, 0, 0]));
+        assert_eq!(Rgba::RED, Rgba::new(255, 0, 0, 255));
+        assert_eq!(Rgb::BLUE, Rgb::new(0, 0, 255));
+        assert_eq!(Rgba::WHITE.to_rgb(), Rgb::WHITE);
+        assert_eq!(Rgb::BLACK.to_rgba(), Rgba::BLACK);
+        assert_eq!(Luma::new(7u16), Luma([7]));
+        assert_eq!(LumaA::new(0.5f32, 1.0), LumaA([0.5, 1.0]));
+    }
+
+    #[test]
+    fn rgb565() {
+        let color = Rgb([200, 100, 50]);
+        let packed = color.to_rgb565();
+        assert_eq!(packed, 24 << 11 | 25 << 5 | 6);
+        // Red and blue keep 5 bits, green keeps 6.
+        assert_eq!(Rgb::from_rgb565(packed), Rgb([198, 101, 49]));
+
+        for color in [Rgb::BLACK, Rgb::WHITE, Rgb::RED, Rgb::GREEN, Rgb::BLUE] {
+            assert_eq!(Rgb::from_rgb565(color.to_rgb565()), color);
+        }
+        assert_eq!(Rgb::RED.to_rgb565(), 0xf800);
+        assert_eq!(Rgb::GREEN.to_rgb565(), 0x07e0);
+        assert_eq!(Rgb::BLUE.to_rgb565(), 0x001f);
+
+        for packed in 0..=u16::MAX {
+            assert_eq!(Rgb::from_rgb565(packed).to_rgb565(), packed);
+        }
+    }
+
+    #[test]
+    fn packed_u32() {
+        let color = Rgba([0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(color.to_u32_rgba(), 0x1234_5678);
+        assert_eq!(color.to_u32_bgra(), 0x5634_1278);
+        assert_eq!(Rgba::from_u32_rgba(0x1234_5678), color);
+        assert_eq!(Rgba::from_u32_bgra(0x5634_1278), color);
+        assert_eq!(Rgba::RED.to_u32_rgba(), 0xff00_00ff);
+        assert_eq!(Rgba::RED.to_u32_bgra(), 0x0000_ffff);
+
+        for packed in [0, 1, 0xdead_beef, 0x8000_0000, u32::MAX] {
+            assert_eq!(Rgba::from_u32_rgba(packed).to_u32_rgba(), packed);
+            assert_eq!(Rgba::from_u32_bgra(packed).to_u32_bgra(), packed);
+        }
+    }
+
+    #[test]
+    fn array_round_trip() {
+        let pixel = Rgba([12u8, 34, 56, 78]);
+        let array = pixel.to_array();
+        assert_eq!(array, [12, 34, 56, 78]);
+        assert_eq!(Rgba::from_array(array), pixel);
+        assert_eq!(Rgba::from_array(array).channels(), pixel.channels());
+
+        const GRAY: Luma<u16> = Luma::from_array([1000]);
+        assert_eq!(GRAY.to_array(), [1000]);
+    }
+
+    #[cfg(feature = "benchmarks")]
+    fn bench_pixels() -> Vec<Rgba<u8>> {
+        (0..1 << 20u32)
+            .map(|i| Rgba([i as u8, (i >> 8) as u8, (i >> 16) as u8, 255]))
+            .collect()
+    }
+
+    #[bench]
+    #[cfg(feature = "benchmarks")]
+    fn bench_transform_channels(b: &mut test::Bencher) {
+        let mut pixels = bench_pixels();
+        b.iter(|| {
+            for pixel in &mut pixels {
+                let channels = pixel.channels_mut();
+                for c in 0..3 {
+                    channels[c] = channels[c].saturating_mul(2) ^ channels[3];
+                }
+            }
+            test::black_box(&pixels);
+        });
+    }
+
+    #[bench]
+    #[cfg(feature = "benchmarks")]
+    fn bench_transform_array(b: &mut test::Bencher) {
+        let mut pixels = bench_pixels();
+        b.iter(|| {
+            for pixel in &mut pixels {
+                let mut channels = pixel.to_array();
+                for c in 0..3 {
+                    channels[c] = channels[c].saturating_mul(2) ^ channels[3];
+                }
+                *pixel = Rgba::from_array(channels);
+            }
+            test::black_box(&pixels);
+        });
+    }
+
+    #[test]
+    fn extended_color_type_to_color_type() {
+        use ExtendedColorType::*;
+
+        let concrete = [
+            L8, La8, Rgb8, Rgba8, L16, La16, Rgb16, Rgba16, Rgb32F, Rgba32F,
+        ];
+        for color in concrete {
+            let color_type = color.to_color_type().unwrap();
+            assert_eq!(ExtendedColorType::from(color_type), color);
+        }
+
+        let extended = [
+            A8,
+            L1,
+            La1,
+            Rgb1,
+            Rgba1,
+            L2,
+            La2,
+            Rgb2,
+            Rgba2,
+            L4,
+            La4,
+            Rgb4,
+            Rgba4,
+            Bgr8,
+            Bgra8,
+            Cmyk8,
+            Unknown(8),
+        ];
+        for color in extended {
+            assert_eq!(color.to_color_type(), None, "{color:?}");
+        }
+    }
 
     #[test]
     fn test_apply_with_alpha_rgba() {
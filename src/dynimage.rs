@@ -16,7 +16,7 @@ use crate::error::{ImageError, ImageResult, ParameterError, ParameterErrorKind};
 use crate::flat::FlatSamples;
 use crate::image::{GenericImage, GenericImageView, ImageDecoder, ImageEncoder, ImageFormat};
 use crate::io::free_functions;
-use crate::math::resize_dimensions;
+use crate::math::{resize_dimensions, Rect};
 use crate::traits::Pixel;
 use crate::{image, Luma, LumaA};
 use crate::{imageops, ExtendedColorType};
@@ -358,6 +358,26 @@ impl DynamicImage {
         }
     }
 
+    /// Consume the image and return it converted to the color type `target`.
+    ///
+    /// If the image already has that color type, it is returned as is. Otherwise, a copy is
+    /// created, just like with the `into_*` method for that color type.
+    pub fn convert(self, target: color::ColorType) -> DynamicImage {
+        use color::ColorType::*;
+        match target {
+            L8 => DynamicImage::ImageLuma8(self.into_luma8()),
+            La8 => DynamicImage::ImageLumaA8(self.into_luma_alpha8()),
+            Rgb8 => DynamicImage::ImageRgb8(self.into_rgb8()),
+            Rgba8 => DynamicImage::ImageRgba8(self.into_rgba8()),
+            L16 => DynamicImage::ImageLuma16(self.into_luma16()),
+            La16 => DynamicImage::ImageLumaA16(self.into_luma_alpha16()),
+            Rgb16 => DynamicImage::ImageRgb16(self.into_rgb16()),
+            Rgba16 => DynamicImage::ImageRgba16(self.into_rgba16()),
+            Rgb32F => DynamicImage::ImageRgb32F(self.into_rgb32f()),
+            Rgba32F => DynamicImage::ImageRgba32F(self.into_rgba32f()),
+        }
+    }
+
     /// Return a cut-out of this image delimited by the bounding rectangle.
     ///
     /// Note: this method does *not* modify the object,
@@ -367,10 +387,26 @@ impl DynamicImage {
     }
 
     /// Return a cut-out of this image delimited by the bounding rectangle.
+    ///
+    /// To look at a part of the image without copying it, use [`GenericImageView::view`]. To copy
+    /// it into a new image faster, use [`crop_owned`].
+    ///
+    /// [`crop_owned`]: DynamicImage::crop_owned
     pub fn crop_imm(&self, x: u32, y: u32, width: u32, height: u32) -> DynamicImage {
         dynamic_map!(*self, ref p => imageops::crop_imm(p, x, y, width, height).to_image())
     }
 
+    /// Return a cut-out of this image delimited by the bounding rectangle, as a new image of the
+    /// same color type.
+    ///
+    /// The rectangle is clamped to the bounds of the image. Unlike [`crop_imm`], the rows of the
+    /// cut-out are copied directly instead of pixel by pixel.
+    ///
+    /// [`crop_imm`]: DynamicImage::crop_imm
+    pub fn crop_owned(&self, x: u32, y: u32, width: u32, height: u32) -> DynamicImage {
+        dynamic_map!(*self, ref p => crop_buffer(p, x, y, width, height))
+    }
+
     /// Return a reference to an 8bit RGB image
     pub fn as_rgb8(&self) -> Option<&RgbImage> {
         match *self {
@@ -532,33 +568,47 @@ impl DynamicImage {
     }
 
     /// Return a view on the raw sample buffer for 8 bit per channel images.
+    ///
+    /// The view carries the color type of the image as its `color_hint`. Returns `None` for
+    /// images with a different sample type, see [`as_flat_samples_u16`](Self::as_flat_samples_u16)
+    /// and [`as_flat_samples_f32`](Self::as_flat_samples_f32) for those.
     pub fn as_flat_samples_u8(&self) -> Option<FlatSamples<&[u8]>> {
-        match *self {
-            DynamicImage::ImageLuma8(ref p) => Some(p.as_flat_samples()),
-            DynamicImage::ImageLumaA8(ref p) => Some(p.as_flat_samples()),
-            DynamicImage::ImageRgb8(ref p) => Some(p.as_flat_samples()),
-            DynamicImage::ImageRgba8(ref p) => Some(p.as_flat_samples()),
-            _ => None,
-        }
+        let samples = match *self {
+            DynamicImage::ImageLuma8(ref p) => p.as_flat_samples(),
+            DynamicImage::ImageLumaA8(ref p) => p.as_flat_samples(),
+            DynamicImage::ImageRgb8(ref p) => p.as_flat_samples(),
+            DynamicImage::ImageRgba8(ref p) => p.as_flat_samples(),
+            _ => return None,
+        };
+        Some(self.hint_color(samples))
     }
 
     /// Return a view on the raw sample buffer for 16 bit per channel images.
     pub fn as_flat_samples_u16(&self) -> Option<FlatSamples<&[u16]>> {
-        match *self {
-            DynamicImage::ImageLuma16(ref p) => Some(p.as_flat_samples()),
-            DynamicImage::ImageLumaA16(ref p) => Some(p.as_flat_samples()),
-            DynamicImage::ImageRgb16(ref p) => Some(p.as_flat_samples()),
-            DynamicImage::ImageRgba16(ref p) => Some(p.as_flat_samples()),
-            _ => None,
-        }
+        let samples = match *self {
+            DynamicImage::ImageLuma16(ref p) => p.as_flat_samples(),
+            DynamicImage::ImageLumaA16(ref p) => p.as_flat_samples(),
+            DynamicImage::ImageRgb16(ref p) => p.as_flat_samples(),
+            DynamicImage::ImageRgba16(ref p) => p.as_flat_samples(),
+            _ => return None,
+        };
+        Some(self.hint_color(samples))
     }
 
     /// Return a view on the raw sample buffer for 32bit per channel images.
     pub fn as_flat_samples_f32(&self) -> Option<FlatSamples<&[f32]>> {
-        match *self {
-            DynamicImage::ImageRgb32F(ref p) => Some(p.as_flat_samples()),
-            DynamicImage::ImageRgba32F(ref p) => Some(p.as_flat_samples()),
-            _ => None,
+        let samples = match *self {
+            DynamicImage::ImageRgb32F(ref p) => p.as_flat_samples(),
+            DynamicImage::ImageRgba32F(ref p) => p.as_flat_samples(),
+            _ => return None,
+        };
+        Some(self.hint_color(samples))
+    }
+
+    fn hint_color<'a, T>(&self, samples: FlatSamples<&'a [T]>) -> FlatSamples<&'a [T]> {
+        FlatSamples {
+            color_hint: Some(self.color()),
+            ..samples
         }
     }
 
@@ -628,6 +678,28 @@ impl DynamicImage {
         dynamic_map!(*self, ref p, { p.height() })
     }
 
+    /// Returns the number of pixels of the underlying image, computed in `u64` so that it can
+    /// not overflow.
+    pub fn pixel_count(&self) -> u64 {
+        dynamic_map!(*self, ref p, p.pixel_count())
+    }
+
+    /// Returns whether the underlying image has no pixels.
+    pub fn is_empty(&self) -> bool {
+        dynamic_map!(*self, ref p, p.is_empty())
+    }
+
+    /// Returns the number of bytes in one row of pixels, which is also the stride between rows
+    /// of [`as_bytes`](DynamicImage::as_bytes).
+    pub fn bytes_per_row(&self) -> usize {
+        dynamic_map!(*self, ref p, p.bytes_per_row())
+    }
+
+    /// Returns the number of bytes of pixel data.
+    pub fn byte_len(&self) -> usize {
+        dynamic_map!(*self, ref p, p.byte_len())
+    }
+
     /// Return a grayscale version of this image.
     /// Returns `Luma` images in most cases. However, for `f32` images,
     /// this will return a grayscale `Rgb/Rgba` image instead.
@@ -714,6 +786,44 @@ impl DynamicImage {
         dynamic_map!(*self, ref p => imageops::thumbnail(p, nwidth, nheight))
     }
 
+    /// Scale this image down to fit within a specific size, using the specified filter algorithm.
+    /// Returns a new image. The image's aspect ratio is preserved.
+    ///
+    /// This computes the same dimensions as [`thumbnail`](DynamicImage::thumbnail) but resamples
+    /// with `filter`, trading speed for quality. For example, `FilterType::Lanczos3` gives
+    /// sharper previews with less aliasing than the fast default.
+    pub fn thumbnail_with_filter(
+        &self,
+        nwidth: u32,
+        nheight: u32,
+        filter: imageops::FilterType,
+    ) -> DynamicImage {
+        let (width2, height2) =
+            resize_dimensions(self.width(), self.height(), nwidth, nheight, false);
+        self.resize_exact(width2, height2, filter)
+    }
+
+    /// Resize this image using the specified filter algorithm.
+    /// Returns a new image. The image's aspect ratio is preserved.
+    /// The image is scaled to the minimum possible size that covers
+    /// the bounds specified by `nwidth` and `nheight`, so it is at
+    /// least as large as the bounds in both dimensions.
+    ///
+    /// This is the counterpart to [`resize`](DynamicImage::resize), which fits the image
+    /// within the bounds. Use [`resize_to_fill`](DynamicImage::resize_to_fill) to also crop
+    /// the result to the bounds.
+    pub fn resize_cover(
+        &self,
+        nwidth: u32,
+        nheight: u32,
+        filter: imageops::FilterType,
+    ) -> DynamicImage {
+        let (width2, height2) =
+            resize_dimensions(self.width(), self.height(), nwidth, nheight, true);
+
+        self.resize_exact(width2, height2, filter)
+    }
+
     /// Resize this image using the specified filter algorithm.
     /// Returns a new image. The image's aspect ratio is preserved.
     /// The image is scaled to the maximum possible size that fits
@@ -726,10 +836,7 @@ impl DynamicImage {
         nheight: u32,
         filter: imageops::FilterType,
     ) -> DynamicImage {
-        let (width2, height2) =
-            resize_dimensions(self.width(), self.height(), nwidth, nheight, true);
-
-        let mut intermediate = self.resize_exact(width2, height2, filter);
+        let mut intermediate = self.resize_cover(nwidth, nheight, filter);
         let (iwidth, iheight) = intermediate.dimensions();
         let ratio = u64::from(iwidth) * u64::from(nheight);
         let nratio = u64::from(nwidth) * u64::from(iheight);
@@ -741,12 +848,28 @@ impl DynamicImage {
         }
     }
 
+    /// Return a new image of the given size filled with copies of this image.
+    ///
+    /// See [`imageops::tiled`] for how the copies are laid out.
+    pub fn tiled(&self, width: u32, height: u32) -> DynamicImage {
+        dynamic_map!(*self, ref p => imageops::tiled(p, width, height))
+    }
+
     /// Performs a Gaussian blur on this image.
     /// `sigma` is a measure of how much to blur by.
     pub fn blur(&self, sigma: f32) -> DynamicImage {
         dynamic_map!(*self, ref p => imageops::blur(p, sigma))
     }
 
+    /// Performs a Gaussian blur on the part of this image within `rect`, in place.
+    ///
+    /// Pixels just outside the rectangle contribute to the blur, so its borders blend into the
+    /// surrounding image, but only the pixels within it are changed. The rectangle is clamped to
+    /// the bounds of the image.
+    pub fn blur_region(&mut self, rect: Rect, sigma: f32) {
+        dynamic_map!(*self, ref mut p, blur_buffer_region(p, rect, sigma))
+    }
+
     /// Performs an unsharpen mask on this image.
     /// `sigma` is the amount to blur the image by.
     /// `threshold` is a control of how much to sharpen.
@@ -787,6 +910,12 @@ impl DynamicImage {
         dynamic_map!(*self, ref p => imageops::huerotate(p, value))
     }
 
+    /// Find the `k` most prominent colors of this image, along with the fraction of the image
+    /// each covers. See [`imageops::dominant_colors`] for details.
+    pub fn dominant_colors(&self, k: usize) -> Vec<(color::Rgb<u8>, f32)> {
+        dynamic_map!(*self, ref p, imageops::dominant_colors(p, k))
+    }
+
     /// Flip this image vertically
     pub fn flipv(&self) -> DynamicImage {
         dynamic_map!(*self, ref p => imageops::flip_vertical(p))
@@ -814,6 +943,10 @@ impl DynamicImage {
 
     /// Encode this image and write it to ```w```.
     ///
+    /// Only the pixels are written. A `DynamicImage` holds no metadata, so EXIF (including GPS
+    /// positions), XMP and ICC profiles of the file it was decoded from never survive a
+    /// transcode through this method or [`DynamicImage::save`].
+    ///
     /// Assumes the writer is buffered. In most cases,
     /// you should wrap your writer in a `BufWriter` for best performance.
     pub fn write_to<W: Write + Seek>(&self, w: &mut W, format: ImageFormat) -> ImageResult<()> {
@@ -1008,6 +1141,56 @@ impl Default for DynamicImage {
     }
 }
 
+/// Copy the rows of a clamped rectangle out of an image buffer.
+fn crop_buffer<P: Pixel>(
+    image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    let (x, y, width, height) = imageops::crop_dimms(image, x, y, width, height);
+    let channels = usize::from(P::CHANNEL_COUNT);
+    let stride = image.width() as usize * channels;
+    let row_len = width as usize * channels;
+
+    let mut data = Vec::with_capacity(row_len * height as usize);
+    for row in y..y + height {
+        let start = row as usize * stride + x as usize * channels;
+        data.extend_from_slice(&image.as_raw()[start..start + row_len]);
+    }
+
+    ImageBuffer::from_raw(width, height, data).unwrap()
+}
+
+fn blur_buffer_region<P: Pixel + 'static>(
+    image: &mut ImageBuffer<P, Vec<P::Subpixel>>,
+    rect: Rect,
+    sigma: f32,
+) {
+    let (x, y, width, height) =
+        imageops::crop_dimms(image, rect.x, rect.y, rect.width, rect.height);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    // Include the support of the blur kernel around the rectangle.
+    let margin = (2.0 * if sigma <= 0.0 { 1.0 } else { sigma }).ceil() as u32;
+    let left = x.saturating_sub(margin);
+    let top = y.saturating_sub(margin);
+    let right = (x + width).saturating_add(margin).min(image.width());
+    let bottom = (y + height).saturating_add(margin).min(image.height());
+
+    let surrounding = crop_buffer(image, left, top, right - left, bottom - top);
+    let blurred = imageops::blur(&surrounding, sigma);
+    for dy in 0..height {
+        for dx in 0..width {
+            let pixel = *blurred.get_pixel(x - left + dx, y - top + dy);
+            image.put_pixel(x + dx, y + dy, pixel);
+        }
+    }
+}
+
 /// Decodes an image and stores it into a dynamic image
 fn decoder_to_image<I: ImageDecoder>(decoder: I) -> ImageResult<DynamicImage> {
     let (w, h) = decoder.dimensions();
@@ -1170,6 +1353,46 @@ pub fn write_buffer_with_format<W: Write + Seek>(
     free_functions::write_buffer_impl(buffered_writer, buf, width, height, color.into(), format)
 }
 
+/// The order of the bytes within a sample of a raw buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /// The most significant byte comes first.
+    Big,
+    /// The least significant byte comes first.
+    Little,
+}
+
+impl Endianness {
+    /// The byte order of the target platform, which is what encoders expect.
+    pub const NATIVE: Self = if cfg!(target_endian = "big") {
+        Endianness::Big
+    } else {
+        Endianness::Little
+    };
+}
+
+/// Reorder the bytes of the samples in `buf` from `endianness` to the native order.
+///
+/// Buffers passed to [`save_buffer`], [`write_buffer_with_format`] and the encoders hold samples
+/// of 16 and 32 bits in native byte order. Raw data from other sources, such as files written on
+/// another platform or network protocols, may use a fixed order instead and has to be converted
+/// with this function first. Buffers of color types with at most 8 bits per channel are left
+/// unchanged.
+pub fn buffer_to_native_endian(
+    buf: &mut [u8],
+    color: impl Into<ExtendedColorType>,
+    endianness: Endianness,
+) {
+    let color = color.into();
+    let bits = color.bits_per_pixel() / u16::from(color.channel_count());
+    if endianness == Endianness::NATIVE || bits <= 8 || bits % 8 != 0 {
+        return;
+    }
+    for sample in buf.chunks_exact_mut(usize::from(bits / 8)) {
+        sample.reverse();
+    }
+}
+
 /// Create a new image from a byte slice
 ///
 /// Makes an educated guess about the image format.
@@ -1212,6 +1435,61 @@ mod bench {
 #[cfg(test)]
 mod test {
     use crate::color::ColorType;
+    use crate::flat::FlatSamples;
+
+    #[test]
+    fn bytes_per_row() {
+        let image = super::DynamicImage::new(7, 2, ColorType::Rgba8);
+        assert_eq!(image.bytes_per_row(), 28);
+        assert_eq!(image.byte_len(), image.as_bytes().len());
+
+        let image = super::DynamicImage::new(7, 2, ColorType::Rgb32F);
+        assert_eq!(image.bytes_per_row(), 84);
+    }
+
+    #[test]
+    fn flat_samples_of_every_variant() {
+        fn check<T>(image: &super::DynamicImage, flat: Option<FlatSamples<&[T]>>) {
+            let color = image.color();
+            let flat = flat.unwrap_or_else(|| panic!("no flat samples for {color:?}"));
+            let channels = color.channel_count();
+            assert_eq!(flat.color_hint, Some(color));
+            assert_eq!(flat.layout.width, image.width());
+            assert_eq!(flat.layout.height, image.height());
+            assert_eq!(flat.layout.channels, channels);
+            assert_eq!(flat.layout.channel_stride, 1);
+            assert_eq!(flat.layout.width_stride, usize::from(channels));
+            assert_eq!(flat.layout.height_stride, 5 * usize::from(channels));
+            assert_eq!(flat.samples.len(), 5 * 3 * usize::from(channels));
+        }
+
+        for color in [
+            ColorType::L8,
+            ColorType::La8,
+            ColorType::Rgb8,
+            ColorType::Rgba8,
+            ColorType::L16,
+            ColorType::La16,
+            ColorType::Rgb16,
+            ColorType::Rgba16,
+            ColorType::Rgb32F,
+            ColorType::Rgba32F,
+        ] {
+            let image = super::DynamicImage::new(5, 3, color);
+            let (u8s, u16s, f32s) = (
+                image.as_flat_samples_u8(),
+                image.as_flat_samples_u16(),
+                image.as_flat_samples_f32(),
+            );
+            let available = [u8s.is_some(), u16s.is_some(), f32s.is_some()];
+            assert_eq!(available.iter().filter(|&&a| a).count(), 1, "{color:?}");
+            match color.bytes_per_pixel() / color.channel_count() {
+                1 => check(&image, u8s),
+                2 => check(&image, u16s),
+                _ => check(&image, f32s),
+            }
+        }
+    }
 
     #[test]
     fn test_empty_file() {
@@ -1226,6 +1504,47 @@ mod test {
         assert_eq!(dims, (320, 240));
     }
 
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn transcode_strips_exif() {
+        use std::io::Cursor;
+
+        /// The markers of all segments before the entropy coded data.
+        fn markers(jpeg: &[u8]) -> Vec<u8> {
+            let mut markers = Vec::new();
+            let mut at = 2;
+            while jpeg[at] == 0xff && jpeg[at + 1] != 0xda {
+                markers.push(jpeg[at + 1]);
+                at += 2 + usize::from(u16::from_be_bytes([jpeg[at + 2], jpeg[at + 3]]));
+            }
+            markers
+        }
+
+        let mut plain = Cursor::new(Vec::new());
+        super::DynamicImage::new_rgb8(8, 8)
+            .write_to(&mut plain, crate::ImageFormat::Jpeg)
+            .unwrap();
+        let plain = plain.into_inner();
+
+        // EXIF with a GPS IFD holding the latitude reference, inserted right after SOI.
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+        exif.extend([0, 1, 0x88, 0x25, 0, 4, 0, 0, 0, 1, 0, 0, 0, 26, 0, 0, 0, 0]);
+        exif.extend([0, 1, 0, 1, 0, 2, 0, 0, 0, 2, b'N', 0, 0, 0, 0, 0, 0, 0]);
+        let mut tagged = plain[..2].to_vec();
+        tagged.extend([0xff, 0xe1]);
+        tagged.extend((exif.len() as u16 + 2).to_be_bytes());
+        tagged.extend(&exif);
+        tagged.extend(&plain[2..]);
+        assert!(markers(&tagged).contains(&0xe1));
+
+        let image = super::load_from_memory(&tagged).unwrap();
+        let mut out = Cursor::new(Vec::new());
+        image.write_to(&mut out, crate::ImageFormat::Jpeg).unwrap();
+        let out = out.into_inner();
+        assert!(!markers(&out).contains(&0xe1), "{:x?}", markers(&out));
+        assert!(!out.windows(4).any(|w| w == b"Exif"));
+    }
+
     #[cfg(feature = "png")]
     #[test]
     fn open_16bpc_png() {
@@ -1339,4 +1658,279 @@ mod test {
         let bytes: Vec<u8> = img.into_bytes();
         assert_eq!(bytes, vec![0xFF; 64 * 64 * 2]);
     }
+
+    #[test]
+    fn into_rgba8_reuses_buffer() {
+        let img = super::DynamicImage::new_rgba8(4, 4);
+        let ptr = img.as_bytes().as_ptr();
+        let rgba = img.into_rgba8();
+        assert_eq!(rgba.as_raw().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn into_rgba8_converts() {
+        let rgb = crate::RgbImage::from_pixel(2, 2, crate::Rgb([10, 20, 30]));
+        let rgba = super::DynamicImage::ImageRgb8(rgb).into_rgba8();
+        assert!(rgba.pixels().all(|p| *p == crate::Rgba([10, 20, 30, 255])));
+    }
+
+    #[cfg(feature = "webp")]
+    #[test]
+    fn write_with_encoder_webp() {
+        use crate::codecs::webp::WebPEncoder;
+
+        for img in [
+            super::DynamicImage::new_rgb8(8, 4),
+            super::DynamicImage::new_rgba8(8, 4),
+        ] {
+            let mut output = Vec::new();
+            img.write_with_encoder(WebPEncoder::new_lossless(&mut output))
+                .unwrap();
+
+            let decoded =
+                super::load_from_memory_with_format(&output, crate::ImageFormat::WebP).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (8, 4));
+        }
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn write_with_encoder_png() {
+        use crate::codecs::png::PngEncoder;
+
+        for img in [
+            super::DynamicImage::new_rgb8(8, 4),
+            super::DynamicImage::new_rgba8(8, 4),
+        ] {
+            let mut output = Vec::new();
+            img.write_with_encoder(PngEncoder::new(&mut output))
+                .unwrap();
+
+            let decoded =
+                super::load_from_memory_with_format(&output, crate::ImageFormat::Png).unwrap();
+            assert_eq!(decoded, img);
+        }
+    }
+
+    #[test]
+    fn convert_to_color_type() {
+        let rgb = crate::RgbImage::from_fn(3, 2, |x, y| crate::Rgb([x as u8 * 100, y as u8, 50]));
+        let img = super::DynamicImage::ImageRgb8(rgb);
+
+        let luma = img.clone().convert(ColorType::L8);
+        assert_eq!(luma.color(), ColorType::L8);
+        assert_eq!(luma, super::DynamicImage::ImageLuma8(img.to_luma8()));
+
+        let rgba = img.clone().convert(ColorType::Rgba16);
+        assert_eq!(rgba.color(), ColorType::Rgba16);
+        assert_eq!(
+            rgba.as_rgba16().unwrap().get_pixel(2, 1).0,
+            [51400, 257, 12850, 65535]
+        );
+
+        assert_eq!(img.clone().convert(ColorType::Rgb8), img);
+    }
+
+    #[test]
+    fn crop_owned_clamps_to_bounds() {
+        let gray = crate::GrayImage::from_fn(6, 4, |x, y| crate::Luma([(y * 6 + x) as u8]));
+        let img = super::DynamicImage::ImageLuma8(gray);
+
+        let cropped = img.crop_owned(4, 1, 10, 10);
+        assert_eq!(cropped.color(), ColorType::L8);
+        assert_eq!((cropped.width(), cropped.height()), (2, 3));
+        assert_eq!(cropped.as_bytes(), &[10, 11, 16, 17, 22, 23]);
+        assert_eq!(cropped, img.crop_imm(4, 1, 10, 10));
+
+        let empty = img.crop_owned(8, 8, 2, 2);
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+
+        let nothing = super::DynamicImage::new_luma8(0, 3).crop_owned(0, 1, 2, 2);
+        assert_eq!((nothing.width(), nothing.height()), (0, 2));
+    }
+
+    #[test]
+    fn crop_owned_preserves_color_type() {
+        let img = super::DynamicImage::new_rgba16(5, 5);
+        let cropped = img.crop_owned(1, 1, 3, 2);
+        assert_eq!(cropped.color(), ColorType::Rgba16);
+        assert_eq!((cropped.width(), cropped.height()), (3, 2));
+    }
+
+    #[test]
+    fn crop_owned_of_every_variant() {
+        use super::DynamicImage;
+
+        let rgba = crate::RgbaImage::from_fn(7, 5, |x, y| {
+            crate::Rgba([
+                (x * 30) as u8,
+                (y * 50) as u8,
+                (x * y * 7) as u8,
+                255 - x as u8,
+            ])
+        });
+        let base = DynamicImage::ImageRgba8(rgba);
+        let variants = [
+            DynamicImage::ImageLuma8(base.to_luma8()),
+            DynamicImage::ImageLumaA8(base.to_luma_alpha8()),
+            DynamicImage::ImageRgb8(base.to_rgb8()),
+            base.clone(),
+            DynamicImage::ImageLuma16(base.to_luma16()),
+            DynamicImage::ImageLumaA16(base.to_luma_alpha16()),
+            DynamicImage::ImageRgb16(base.to_rgb16()),
+            DynamicImage::ImageRgba16(base.to_rgba16()),
+            DynamicImage::ImageRgb32F(base.to_rgb32f()),
+            DynamicImage::ImageRgba32F(base.to_rgba32f()),
+        ];
+
+        for image in &variants {
+            let cropped = image.crop_owned(2, 1, 4, 3);
+            assert_eq!(cropped.color(), image.color());
+            assert_eq!((cropped.width(), cropped.height()), (4, 3));
+            assert_eq!(cropped, image.crop_imm(2, 1, 4, 3), "{:?}", image.color());
+        }
+    }
+
+    #[test]
+    fn blur_region_changes_only_rect() {
+        use crate::GenericImageView;
+
+        let stripes = crate::GrayImage::from_fn(16, 16, |x, _| crate::Luma([(x % 2 * 255) as u8]));
+        let original = super::DynamicImage::ImageLuma8(stripes);
+        let rect = crate::math::Rect {
+            x: 4,
+            y: 5,
+            width: 6,
+            height: 7,
+        };
+
+        let mut img = original.clone();
+        img.blur_region(rect, 1.5);
+        let blurred = original.blur(1.5);
+
+        for (x, y, pixel) in img.pixels() {
+            let inside = (4..10).contains(&x) && (5..12).contains(&y);
+            if inside {
+                // The neighborhood of the rectangle is sampled like a full blur would.
+                assert_eq!(pixel, blurred.get_pixel(x, y));
+                assert!((64..192).contains(&pixel.0[0]));
+            } else {
+                assert_eq!(pixel, original.get_pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn blur_region_outside_image() {
+        let mut img = super::DynamicImage::new_rgb8(4, 4);
+        img.as_mut_rgb8()
+            .unwrap()
+            .put_pixel(3, 3, crate::Rgb([255, 0, 0]));
+        let original = img.clone();
+
+        let rect = crate::math::Rect {
+            x: 5,
+            y: 0,
+            width: 3,
+            height: 3,
+        };
+        img.blur_region(rect, 1.0);
+        assert_eq!(img, original);
+    }
+
+    #[test]
+    fn resize_fit_and_cover() {
+        use crate::imageops::FilterType;
+
+        let img = super::DynamicImage::new_rgb8(40, 30);
+        let dimensions = |img: super::DynamicImage| (img.width(), img.height());
+
+        assert_eq!(
+            dimensions(img.resize(20, 20, FilterType::Triangle)),
+            (20, 15)
+        );
+        assert_eq!(
+            dimensions(img.resize_cover(20, 20, FilterType::Triangle)),
+            (27, 20)
+        );
+        assert_eq!(
+            dimensions(img.resize_to_fill(20, 20, FilterType::Triangle)),
+            (20, 20)
+        );
+
+        let tall = super::DynamicImage::new_rgb8(30, 40);
+        assert_eq!(
+            dimensions(tall.resize(20, 20, FilterType::Triangle)),
+            (15, 20)
+        );
+        assert_eq!(
+            dimensions(tall.resize_cover(20, 20, FilterType::Triangle)),
+            (20, 27)
+        );
+    }
+
+    #[test]
+    fn thumbnail_with_filter_preserves_aspect() {
+        use crate::imageops::FilterType;
+        use crate::GenericImageView;
+
+        // A fine checkerboard, which the filters reproduce differently.
+        let img = super::DynamicImage::ImageLuma8(crate::GrayImage::from_fn(90, 60, |x, y| {
+            crate::Luma([if (x / 2 + y / 2) % 2 == 0 { 255 } else { 0 }])
+        }));
+
+        let fast = img.thumbnail(40, 40);
+        let lanczos = img.thumbnail_with_filter(40, 40, FilterType::Lanczos3);
+        assert_eq!(fast.dimensions(), (40, 27));
+        assert_eq!(lanczos.dimensions(), (40, 27));
+        assert_ne!(fast, lanczos);
+
+        let tall = super::DynamicImage::new_rgb8(30, 40);
+        let thumb = tall.thumbnail_with_filter(20, 20, FilterType::Lanczos3);
+        assert_eq!(thumb.dimensions(), (15, 20));
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn write_byte_swapped_buffer() {
+        let img = crate::ImageBuffer::from_fn(5, 3, |x, y| {
+            crate::Rgb([x as u16 * 4000, y as u16 * 20000 + 1, 0x1234])
+        });
+        let foreign = match super::Endianness::NATIVE {
+            super::Endianness::Big => super::Endianness::Little,
+            super::Endianness::Little => super::Endianness::Big,
+        };
+        let mut raw: Vec<u8> = img
+            .as_raw()
+            .iter()
+            .flat_map(|v| match foreign {
+                super::Endianness::Big => v.to_be_bytes(),
+                super::Endianness::Little => v.to_le_bytes(),
+            })
+            .collect();
+
+        super::buffer_to_native_endian(&mut raw, ColorType::Rgb16, foreign);
+        let mut png = std::io::Cursor::new(Vec::new());
+        super::write_buffer_with_format(
+            &mut png,
+            &raw,
+            5,
+            3,
+            ColorType::Rgb16,
+            crate::ImageFormat::Png,
+        )
+        .unwrap();
+        let decoded = crate::load_from_memory(png.get_ref()).unwrap();
+        assert_eq!(decoded.into_rgb16(), img);
+
+        // Samples of a single byte have no order.
+        let mut bytes = vec![1, 2, 3];
+        super::buffer_to_native_endian(&mut bytes, ColorType::Rgb8, foreign);
+        assert_eq!(bytes, [1, 2, 3]);
+        let floats = [1.5f32, -0.25, 1e6];
+        let mut raw: Vec<u8> = floats.iter().flat_map(|v| v.to_be_bytes()).collect();
+        super::buffer_to_native_endian(&mut raw, ColorType::Rgb32F, super::Endianness::Big);
+        let native: Vec<u8> = floats.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        assert_eq!(raw, native);
+    }
 }
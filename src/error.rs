@@ -286,12 +286,66 @@ impl LimitError {
     }
 }
 
+impl ImageError {
+    /// The error of an encoder or decoder for `format` which does not support `color`.
+    #[cfg(any(
+        feature = "bmp",
+        feature = "exr",
+        feature = "ff",
+        feature = "gif",
+        feature = "jpeg",
+        feature = "png",
+        feature = "pnm",
+        feature = "qoi",
+        feature = "tiff",
+        feature = "webp"
+    ))]
+    pub(crate) fn unsupported_color(format: ImageFormat, color: ExtendedColorType) -> Self {
+        ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+            format.into(),
+            UnsupportedErrorKind::Color(color),
+        ))
+    }
+}
+
 impl From<io::Error> for ImageError {
     fn from(err: io::Error) -> ImageError {
         ImageError::IoError(err)
     }
 }
 
+/// Convert into an `io::Error`, for use with interfaces that only know about those.
+///
+/// An [`ImageError::IoError`] is unwrapped. All other errors are wrapped, so that they can be
+/// recovered with [`io::Error::into_inner`] and their [`source`](Error::source) is preserved. The
+/// kind of the error is that of an underlying io error, if there is one, and otherwise the closest
+/// match for the variant, such as `InvalidData` for decoding errors.
+impl From<ImageError> for io::Error {
+    fn from(err: ImageError) -> io::Error {
+        if let ImageError::IoError(err) = err {
+            return err;
+        }
+
+        let kind = match &err {
+            ImageError::Decoding(_) => io::ErrorKind::InvalidData,
+            // Io errors were returned above.
+            ImageError::Encoding(_) | ImageError::IoError(_) => io::ErrorKind::Other,
+            ImageError::Parameter(_) => io::ErrorKind::InvalidInput,
+            ImageError::Limits(limit) => match limit.kind {
+                LimitErrorKind::InsufficientMemory => io::ErrorKind::OutOfMemory,
+                _ => io::ErrorKind::Other,
+            },
+            ImageError::Unsupported(_) => io::ErrorKind::Unsupported,
+        };
+
+        let kind = err
+            .source()
+            .and_then(|source| source.downcast_ref::<io::Error>())
+            .map_or(kind, io::Error::kind);
+        io::Error::new(kind, err)
+    }
+}
+
 impl From<ImageFormat> for ImageFormatHint {
     fn from(format: ImageFormat) -> Self {
         ImageFormatHint::Exact(format)
@@ -503,4 +557,58 @@ mod tests {
 
         assert_send_sync::<ImageError>();
     }
+
+    #[test]
+    fn io_caused_encoding_error() {
+        let io = io::Error::new(io::ErrorKind::BrokenPipe, "pipe closed");
+        let err = ImageError::Encoding(EncodingError::new(ImageFormat::Png.into(), io));
+
+        let source = err.source().expect("the io error is the source");
+        let source = source.downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.kind(), io::ErrorKind::BrokenPipe);
+
+        // Converting keeps the kind of the underlying error, and the chain down to it.
+        let converted = io::Error::from(err);
+        assert_eq!(converted.kind(), io::ErrorKind::BrokenPipe);
+        let inner = converted.get_ref().unwrap();
+        assert!(matches!(
+            inner.downcast_ref::<ImageError>(),
+            Some(ImageError::Encoding(_))
+        ));
+        let cause = inner.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(cause.to_string(), "pipe closed");
+    }
+
+    #[test]
+    fn into_io_error_kinds() {
+        let io = io::Error::new(io::ErrorKind::UnexpectedEof, "short");
+        assert_eq!(
+            io::Error::from(ImageError::IoError(io)).kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        let decoding = DecodingError::new(ImageFormatHint::Unknown, "bad header");
+        assert_eq!(
+            io::Error::from(ImageError::Decoding(decoding)).kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let parameter = ParameterError::from_kind(ParameterErrorKind::DimensionMismatch);
+        assert_eq!(
+            io::Error::from(ImageError::Parameter(parameter)).kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        let limits = LimitError::from_kind(LimitErrorKind::InsufficientMemory);
+        assert_eq!(
+            io::Error::from(ImageError::Limits(limits)).kind(),
+            io::ErrorKind::OutOfMemory
+        );
+
+        let unsupported = UnsupportedError::from(ImageFormatHint::Unknown);
+        assert_eq!(
+            io::Error::from(ImageError::Unsupported(unsupported)).kind(),
+            io::ErrorKind::Unsupported
+        );
+    }
 }
@@ -5,10 +5,10 @@ use std::ops::{Deref, DerefMut};
 use std::path::Path;
 use std::usize;
 
-use crate::color::{ColorType, ExtendedColorType};
+use crate::color::{ColorType, ExtendedColorType, Rgb};
 use crate::error::{
     ImageError, ImageFormatHint, ImageResult, LimitError, LimitErrorKind, ParameterError,
-    ParameterErrorKind,
+    ParameterErrorKind, UnsupportedError, UnsupportedErrorKind,
 };
 use crate::math::Rect;
 use crate::traits::Pixel;
@@ -19,6 +19,7 @@ use crate::animation::Frames;
 /// An enumeration of supported image formats.
 /// Not all formats support both encoding and decoding.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum ImageFormat {
     /// An Image in PNG Format
@@ -146,6 +147,9 @@ impl ImageFormat {
 
     /// Return the image format specified by a MIME type.
     ///
+    /// The value of a `Content-Type` header may be passed directly: the comparison is
+    /// case-insensitive and any parameters following the type are ignored.
+    ///
     /// # Example
     ///
     /// ```
@@ -153,12 +157,16 @@ impl ImageFormat {
     ///
     /// let format = ImageFormat::from_mime_type("image/png").unwrap();
     /// assert_eq!(format, ImageFormat::Png);
+    ///
+    /// let format = ImageFormat::from_mime_type("Image/WebP; charset=binary").unwrap();
+    /// assert_eq!(format, ImageFormat::WebP);
     /// ```
     pub fn from_mime_type<M>(mime_type: M) -> Option<Self>
     where
         M: AsRef<str>,
     {
-        match mime_type.as_ref() {
+        let essence = mime_type.as_ref().split(';').next()?.trim();
+        match essence.to_ascii_lowercase().as_str() {
             "image/avif" => Some(ImageFormat::Avif),
             "image/jpeg" => Some(ImageFormat::Jpeg),
             "image/png" => Some(ImageFormat::Png),
@@ -771,6 +779,143 @@ pub trait ImageEncoder {
         height: u32,
         color_type: ExtendedColorType,
     ) -> ImageResult<()>;
+
+    /// Writes paletted image data to the encoder.
+    ///
+    /// Each byte of `indices` is one pixel, referring to an entry of `palette` which may hold at
+    /// most 256 colors. Encoders without support for paletted images return an
+    /// `ImageError::Unsupported`, which is the default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width * height != indices.len()`.
+    fn write_indexed_image(
+        self,
+        indices: &[u8],
+        width: u32,
+        height: u32,
+        palette: &[Rgb<u8>],
+    ) -> ImageResult<()>
+    where
+        Self: Sized,
+    {
+        let _ = (indices, width, height, palette);
+        Err(ImageError::Unsupported(
+            UnsupportedError::from_format_and_kind(
+                ImageFormatHint::Unknown,
+                UnsupportedErrorKind::GenericFeature("paletted images".into()),
+            ),
+        ))
+    }
+
+    /// Sets the quality of lossy compression, from 1 for the smallest output to 100 for the
+    /// best fidelity. Larger values are treated as 100.
+    ///
+    /// This allows generic code to pick a quality without knowing the concrete encoder. It has
+    /// the same effect as the quality argument of the constructor of encoders that have one,
+    /// such as JPEG and AVIF. Lossless encoders ignore it, which is the default.
+    fn set_quality(&mut self, quality: u8) {
+        let _ = quality;
+    }
+}
+
+/// One coordinate of the pixels that [`GenericImage::draw_line`] steps through.
+///
+/// The line advances one pixel per step along its longer axis, and the coordinate on the shorter
+/// axis is rounded to the nearest pixel, halves away from the start. This is the same choice of
+/// pixels as Bresenham's algorithm, but any step can be computed directly.
+struct LineAxis {
+    start: i64,
+    sign: i64,
+    delta: i128,
+    steps: i128,
+}
+
+impl LineAxis {
+    fn new(start: i64, delta: i64, steps: i64) -> Self {
+        LineAxis {
+            start,
+            sign: delta.signum(),
+            delta: i128::from(delta.abs()),
+            steps: i128::from(steps),
+        }
+    }
+
+    /// The distance from the start after `step` steps, which never decreases.
+    fn offset(&self, step: i64) -> i64 {
+        if self.steps == 0 {
+            return 0;
+        }
+        ((2 * i128::from(step) * self.delta + self.steps) / (2 * self.steps)) as i64
+    }
+
+    fn at(&self, step: i64) -> i64 {
+        self.start + self.sign * self.offset(step)
+    }
+
+    /// The first and last step at which the coordinate lies in `0..size`, if there are any.
+    fn steps_within(&self, size: i64) -> Option<(i64, i64)> {
+        // The range of offsets from the start that lie within the image.
+        let (low, high) = if self.sign < 0 {
+            (self.start - (size - 1), self.start)
+        } else {
+            (-self.start, size - 1 - self.start)
+        };
+        let (low, high) = (i128::from(low.max(0)), i128::from(high).min(self.delta));
+        if low > high {
+            return None;
+        }
+        if self.delta == 0 {
+            return Some((0, self.steps as i64));
+        }
+
+        // Solve `offset(step) >= low` and `offset(step) <= high` for the step.
+        let (n, d) = (self.steps, self.delta);
+        let first = if low == 0 {
+            0
+        } else {
+            (2 * n * low - n + 2 * d - 1) / (2 * d)
+        };
+        let last = if high == d {
+            n
+        } else {
+            (2 * n * (high + 1) - n - 1) / (2 * d)
+        };
+        Some((first as i64, last as i64))
+    }
+}
+
+/// Check the arguments of [`ImageEncoder::write_indexed_image`].
+#[cfg(any(feature = "gif", feature = "png"))]
+#[track_caller]
+pub(crate) fn check_indexed_image(
+    indices: &[u8],
+    width: u32,
+    height: u32,
+    palette: &[Rgb<u8>],
+) -> ImageResult<()> {
+    let expected_len = u64::from(width) * u64::from(height);
+    assert_eq!(
+        expected_len,
+        indices.len() as u64,
+        "Invalid buffer length: expected {expected_len} got {} for {width}x{height} image",
+        indices.len(),
+    );
+
+    let message = if palette.is_empty() || palette.len() > 256 {
+        "palette must hold between 1 and 256 colors"
+    } else if indices
+        .iter()
+        .any(|&index| usize::from(index) >= palette.len())
+    {
+        "index out of range of the palette"
+    } else {
+        return Ok(());
+    };
+
+    Err(ImageError::Parameter(ParameterError::from_kind(
+        ParameterErrorKind::Generic(message.into()),
+    )))
 }
 
 /// Immutable pixel iterator
@@ -844,6 +989,23 @@ pub trait GenericImageView {
         x < width && y < height
     }
 
+    /// The area covered by this image, in the coordinates of the image it is a view into.
+    ///
+    /// For an image that holds its own pixels this is at the origin with the size of the image.
+    /// Views such as [`SubImage`] report their position within the underlying image. All other
+    /// methods, including [`in_bounds`], take coordinates relative to this image instead.
+    ///
+    /// [`in_bounds`]: #method.in_bounds
+    fn bounds(&self) -> Rect {
+        let (width, height) = self.dimensions();
+        Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
     /// Returns the pixel located at (x, y). Indexed from top left.
     ///
     /// # Panics
@@ -851,6 +1013,27 @@ pub trait GenericImageView {
     /// Panics if `(x, y)` is out of bounds.
     fn get_pixel(&self, x: u32, y: u32) -> Self::Pixel;
 
+    /// Returns the pixel located at (x, y), or `None` if the coordinates are out of bounds.
+    /// Indexed from top left.
+    ///
+    /// [`ImageBuffer`] has an inherent method of the same name that returns a reference instead,
+    /// and which is the one called by `buffer.get_pixel_checked(x, y)`. Call this one as
+    /// `GenericImageView::get_pixel_checked(&buffer, x, y)` to get the pixel by value.
+    ///
+    /// There is no mutable counterpart in [`GenericImage`], it would have to be built on the
+    /// deprecated [`GenericImage::get_pixel_mut`]. Check the coordinates with [`in_bounds`] and
+    /// then use [`GenericImage::put_pixel`], or [`ImageBuffer::get_pixel_mut_checked`] for
+    /// buffers.
+    ///
+    /// [`in_bounds`]: #method.in_bounds
+    fn get_pixel_checked(&self, x: u32, y: u32) -> Option<Self::Pixel> {
+        if self.in_bounds(x, y) {
+            Some(self.get_pixel(x, y))
+        } else {
+            None
+        }
+    }
+
     /// Returns the pixel located at (x, y). Indexed from top left.
     ///
     /// This function can be implemented in a way that ignores bounds checking.
@@ -1028,9 +1211,82 @@ pub trait GenericImage: GenericImageView {
         true
     }
 
+    /// Draws a straight line of `color` from `p0` to `p1`, both included.
+    ///
+    /// The pixels are chosen with Bresenham's algorithm, so the line is one pixel thick and
+    /// connected. The endpoints may lie outside of the image, the line is clipped to it first so
+    /// that only the pixels inside of it are visited.
+    fn draw_line(&mut self, p0: (i32, i32), p1: (i32, i32), color: Self::Pixel) {
+        let (x0, y0) = (i64::from(p0.0), i64::from(p0.1));
+        let (dx, dy) = (i64::from(p1.0) - x0, i64::from(p1.1) - y0);
+        let steps = dx.abs().max(dy.abs());
+        let (width, height) = (i64::from(self.width()), i64::from(self.height()));
+
+        let (x_axis, y_axis) = (LineAxis::new(x0, dx, steps), LineAxis::new(y0, dy, steps));
+
+        // Only iterate over the steps that lie within the image on both axes.
+        let (Some(x_steps), Some(y_steps)) =
+            (x_axis.steps_within(width), y_axis.steps_within(height))
+        else {
+            return;
+        };
+        for step in x_steps.0.max(y_steps.0)..=x_steps.1.min(y_steps.1) {
+            let (x, y) = (x_axis.at(step), y_axis.at(step));
+            self.put_pixel(x as u32, y as u32, color);
+        }
+    }
+
+    /// Draws a rectangle of `color`, either only its one pixel wide border or, if `filled`, its
+    /// entire area.
+    ///
+    /// Parts of the rectangle outside of the image are ignored.
+    fn draw_rect(&mut self, rect: Rect, color: Self::Pixel, filled: bool) {
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+        let (width, height) = self.dimensions();
+        let right = rect.x.saturating_add(rect.width).min(width);
+        let bottom = rect.y.saturating_add(rect.height).min(height);
+        let last_x = u64::from(rect.x) + u64::from(rect.width) - 1;
+        let last_y = u64::from(rect.y) + u64::from(rect.height) - 1;
+
+        for y in rect.y..bottom {
+            let edge_row = y == rect.y || u64::from(y) == last_y;
+            for x in rect.x..right {
+                let edge_column = x == rect.x || u64::from(x) == last_x;
+                if filled || edge_row || edge_column {
+                    self.put_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
     /// Returns a mutable subimage that is a view into this image.
     /// If you want an immutable subimage instead, use [`GenericImageView::view`]
     /// The coordinates set the position of the top left corner of the SubImage.
+    ///
+    /// Writes to the subimage go straight to this image. The view borrows the image mutably, so
+    /// at most one of them can be used at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rectangle does not lie within the image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use image::{GenericImage, GrayImage, Luma};
+    ///
+    /// let mut image = GrayImage::new(8, 8);
+    /// let mut region = image.sub_image(2, 2, 4, 4);
+    /// for y in 0..4 {
+    ///     for x in 0..4 {
+    ///         region.put_pixel(x, y, Luma([255]));
+    ///     }
+    /// }
+    /// assert_eq!(image.get_pixel(2, 5), &Luma([255]));
+    /// assert_eq!(image.get_pixel(6, 5), &Luma([0]));
+    /// ```
     fn sub_image(&mut self, x: u32, y: u32, width: u32, height: u32) -> SubImage<&mut Self>
     where
         Self: Sized,
@@ -1232,6 +1488,16 @@ where
         (self.xstride, self.ystride)
     }
 
+    fn bounds(&self) -> Rect {
+        let parent = self.image.bounds();
+        Rect {
+            x: parent.x + self.xoffset,
+            y: parent.y + self.yoffset,
+            width: self.xstride,
+            height: self.ystride,
+        }
+    }
+
     fn get_pixel(&self, x: u32, y: u32) -> Self::Pixel {
         self.image.get_pixel(x + self.xoffset, y + self.yoffset)
     }
@@ -1271,7 +1537,7 @@ mod tests {
     };
     use crate::color::Rgba;
     use crate::math::Rect;
-    use crate::{GrayImage, ImageBuffer};
+    use crate::{GrayImage, ImageBuffer, Luma};
 
     #[test]
     #[allow(deprecated)]
@@ -1308,6 +1574,24 @@ mod tests {
         assert!(!target.in_bounds(2, 2));
     }
 
+    #[test]
+    fn test_get_pixel_checked() {
+        let mut source = ImageBuffer::new(3, 2);
+        source.put_pixel(2, 1, Rgba([1u8, 2, 3, 4]));
+
+        let view = source.view(1, 0, 2, 2);
+        assert_eq!(view.get_pixel_checked(1, 1), Some(Rgba([1, 2, 3, 4])));
+        assert_eq!(view.get_pixel_checked(2, 1), None);
+        assert_eq!(view.get_pixel_checked(1, 2), None);
+
+        let image = crate::DynamicImage::ImageRgba8(source);
+        assert_eq!(
+            GenericImageView::get_pixel_checked(&image, 2, 1),
+            Some(Rgba([1, 2, 3, 4]))
+        );
+        assert_eq!(GenericImageView::get_pixel_checked(&image, 3, 0), None);
+    }
+
     #[test]
     fn test_can_subimage_clone_nonmut() {
         let mut source = ImageBuffer::new(3, 3);
@@ -1391,6 +1675,207 @@ mod tests {
         source.view(2, 2, 0, 0);
     }
 
+    #[test]
+    fn in_bounds_edges() {
+        let source = GrayImage::new(4, 3);
+        assert!(source.in_bounds(0, 0));
+        assert!(source.in_bounds(3, 2));
+        assert!(!source.in_bounds(4, 2));
+        assert!(!source.in_bounds(3, 3));
+        assert!(!GrayImage::new(0, 0).in_bounds(0, 0));
+
+        // Coordinates of a view are relative to its own corner.
+        let view = source.view(1, 1, 2, 2);
+        assert!(view.in_bounds(1, 1));
+        assert!(!view.in_bounds(2, 0));
+        assert!(!view.in_bounds(0, 2));
+    }
+
+    #[test]
+    fn sub_image_writes_through() {
+        let mut image = GrayImage::from_fn(6, 5, |x, y| Luma([(x + 6 * y) as u8]));
+        let original = image.clone();
+
+        let mut region = image.sub_image(1, 2, 3, 2);
+        for (x, y) in [(0, 0), (2, 0), (0, 1), (2, 1)] {
+            region.put_pixel(x, y, Luma([200]));
+        }
+        region.put_pixel(1, 1, Luma([100]));
+        let mut nested = region.sub_image(1, 0, 1, 1);
+        nested.put_pixel(0, 0, Luma([50]));
+
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let expected = match (x, y) {
+                (1 | 3, 2 | 3) => Luma([200]),
+                (2, 3) => Luma([100]),
+                (2, 2) => Luma([50]),
+                _ => *original.get_pixel(x, y),
+            };
+            assert_eq!(*pixel, expected, "({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn bounds_of_sub_images() {
+        let rect = |x, y, width, height| Rect {
+            x,
+            y,
+            width,
+            height,
+        };
+        let mut source = GrayImage::new(10, 8);
+        assert_eq!(source.bounds(), rect(0, 0, 10, 8));
+
+        let view = source.view(2, 3, 5, 4);
+        assert_eq!(view.bounds(), rect(2, 3, 5, 4));
+        // Both a flattened view and a view of the view report their place in the source.
+        assert_eq!(view.view(1, 1, 2, 2).bounds(), rect(3, 4, 2, 2));
+        assert_eq!(view.inner.view(1, 1, 2, 2).bounds(), rect(3, 4, 2, 2));
+
+        let mut sub = source.sub_image(1, 1, 6, 6);
+        sub.change_bounds(4, 2, 3, 3);
+        assert_eq!(sub.bounds(), rect(4, 2, 3, 3));
+    }
+
+    #[test]
+    fn draw_horizontal_line() {
+        let mut image = GrayImage::new(8, 4);
+        image.draw_line((1, 2), (5, 2), Luma([255]));
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let on_line = y == 2 && (1..=5).contains(&x);
+            assert_eq!(pixel[0] == 255, on_line, "({x}, {y})");
+        }
+
+        // Drawn backwards and reaching past both edges of the image.
+        let mut clipped = GrayImage::new(8, 4);
+        clipped.draw_line((20, 1), (-3, 1), Luma([255]));
+        for (x, y, pixel) in clipped.enumerate_pixels() {
+            assert_eq!(pixel[0] == 255, y == 1, "({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn draw_diagonal_line() {
+        let mut image = GrayImage::new(5, 5);
+        image.draw_line((4, 0), (0, 4), Luma([255]));
+        for (x, y, pixel) in image.enumerate_pixels() {
+            assert_eq!(pixel[0] == 255, x + y == 4, "({x}, {y})");
+        }
+
+        // A shallow line steps down exactly once per two pixels and keeps both endpoints.
+        let mut image = GrayImage::new(7, 4);
+        image.draw_line((0, 0), (6, 3), Luma([255]));
+        let lit: Vec<_> = image
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p[0] == 255)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(lit.len(), 7);
+        assert!(lit.contains(&(0, 0)) && lit.contains(&(6, 3)));
+    }
+
+    #[test]
+    fn draw_line_matches_bresenham() {
+        // Every pixel of the line, inside of the image or not.
+        fn bresenham(p0: (i32, i32), p1: (i32, i32)) -> Vec<(i32, i32)> {
+            let (mut x, mut y) = p0;
+            let (dx, dy) = ((p1.0 - x).abs(), -(p1.1 - y).abs());
+            let (step_x, step_y) = ((p1.0 - x).signum(), (p1.1 - y).signum());
+            let mut error = dx + dy;
+            let mut pixels = vec![(x, y)];
+            while (x, y) != p1 {
+                let doubled = 2 * error;
+                if doubled >= dy {
+                    error += dy;
+                    x += step_x;
+                }
+                if doubled <= dx {
+                    error += dx;
+                    y += step_y;
+                }
+                pixels.push((x, y));
+            }
+            pixels
+        }
+
+        let points = [-9, -4, -1, 0, 1, 3, 6, 7, 8, 13];
+        for &x0 in &points {
+            for &y0 in &points[1..8] {
+                for (&x1, &y1) in points.iter().zip(points.iter().rev()) {
+                    let mut image = GrayImage::new(8, 6);
+                    image.draw_line((x0, y0), (x1, y1), Luma([255]));
+                    let mut expected = GrayImage::new(8, 6);
+                    for (x, y) in bresenham((x0, y0), (x1, y1)) {
+                        if (0..8).contains(&x) && (0..6).contains(&y) {
+                            expected.put_pixel(x as u32, y as u32, Luma([255]));
+                        }
+                    }
+                    assert_eq!(image, expected, "({x0}, {y0}) to ({x1}, {y1})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn draw_line_far_outside() {
+        let mut image = GrayImage::new(4, 4);
+        image.draw_line((i32::MIN, i32::MIN), (i32::MAX, i32::MAX), Luma([255]));
+        let lit = image.pixels().filter(|p| p[0] == 255).count();
+        assert_eq!(lit, 4);
+        assert_eq!(image.get_pixel(0, 0)[0], 255);
+
+        let mut image = GrayImage::new(4, 4);
+        image.draw_line((i32::MIN, 2), (-1, i32::MAX), Luma([255]));
+        assert!(image.pixels().all(|p| p[0] == 0));
+    }
+
+    #[test]
+    fn draw_rect_border_and_filled() {
+        let rect = Rect {
+            x: 1,
+            y: 1,
+            width: 4,
+            height: 3,
+        };
+        let mut image = GrayImage::new(7, 6);
+        image.draw_rect(rect, Luma([255]), false);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let inside = (1..5).contains(&x) && (1..4).contains(&y);
+            let border = inside && (x == 1 || x == 4 || y == 1 || y == 3);
+            assert_eq!(pixel[0] == 255, border, "({x}, {y})");
+        }
+
+        let mut image = GrayImage::new(7, 6);
+        image.draw_rect(rect, Luma([255]), true);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let inside = (1..5).contains(&x) && (1..4).contains(&y);
+            assert_eq!(pixel[0] == 255, inside, "({x}, {y})");
+        }
+
+        // Only the top and left edges of a rectangle overlapping the corner are visible.
+        let mut image = GrayImage::new(4, 4);
+        let overlapping = Rect {
+            x: 2,
+            y: 2,
+            width: 10,
+            height: 10,
+        };
+        image.draw_rect(overlapping, Luma([255]), false);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let border = (x == 2 && y >= 2) || (y == 2 && x >= 2);
+            assert_eq!(pixel[0] == 255, border, "({x}, {y})");
+        }
+
+        let empty = Rect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 2,
+        };
+        image.draw_rect(empty, Luma([7]), true);
+        assert!(image.pixels().all(|p| p[0] != 7));
+    }
+
     #[test]
     fn test_copy_sub_image() {
         let source = ImageBuffer::from_pixel(3, 3, Rgba([255u8, 0, 0, 255]));
@@ -1802,6 +2287,60 @@ mod tests {
         assert!(all_formats.contains(&ImageFormat::Jpeg));
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        for format in ImageFormat::all() {
+            let json = serde_json::to_string(&format).unwrap();
+            assert_eq!(serde_json::from_str::<ImageFormat>(&json).unwrap(), format);
+        }
+        assert_eq!(
+            serde_json::to_string(&ImageFormat::WebP).unwrap(),
+            "\"WebP\""
+        );
+    }
+
+    #[test]
+    fn mime_type_roundtrip() {
+        for format in ImageFormat::all() {
+            let mime_type = format.to_mime_type();
+            if format == ImageFormat::Farbfeld {
+                assert_eq!(mime_type, "application/octet-stream");
+                assert_eq!(ImageFormat::from_mime_type(mime_type), None);
+            } else {
+                assert_eq!(ImageFormat::from_mime_type(mime_type), Some(format));
+            }
+        }
+    }
+
+    #[test]
+    fn mime_type_from_content_type() {
+        assert_eq!(
+            ImageFormat::from_mime_type("image/webp"),
+            Some(ImageFormat::WebP)
+        );
+        assert_eq!(
+            ImageFormat::from_mime_type("IMAGE/AVIF"),
+            Some(ImageFormat::Avif)
+        );
+        assert_eq!(
+            ImageFormat::from_mime_type(" image/x-icon ; foo=bar"),
+            Some(ImageFormat::Ico)
+        );
+        assert_eq!(ImageFormat::from_mime_type("image/svg+xml"), None);
+        assert_eq!(ImageFormat::from_mime_type(""), None);
+    }
+
+    #[test]
+    #[cfg(feature = "qoi")]
+    fn indexed_image_unsupported() {
+        use crate::{codecs::qoi::QoiEncoder, ImageEncoder, ImageError, Rgb};
+
+        let result =
+            QoiEncoder::new(Vec::new()).write_indexed_image(&[0; 4], 2, 2, &[Rgb([0, 0, 0])]);
+        assert!(matches!(result, Err(ImageError::Unsupported(_))));
+    }
+
     #[test]
     fn reading_enabled() {
         assert_eq!(cfg!(feature = "jpeg"), ImageFormat::Jpeg.reading_enabled());
@@ -231,6 +231,48 @@ pub fn rotate180_in_place<I: GenericImage>(image: &mut I) {
     }
 }
 
+/// Rotate a square image 90 degrees clockwise in place.
+///
+/// Returns an error if the image is not square, as its dimensions would have to be swapped.
+pub fn rotate90_in_place<I: GenericImage>(image: &mut I) -> crate::ImageResult<()> {
+    rotate_square_in_place(image, true)
+}
+
+/// Rotate a square image 270 degrees clockwise in place.
+///
+/// Returns an error if the image is not square, as its dimensions would have to be swapped.
+pub fn rotate270_in_place<I: GenericImage>(image: &mut I) -> crate::ImageResult<()> {
+    rotate_square_in_place(image, false)
+}
+
+/// Rotate a square image by a quarter turn, by cycling the four pixels that take each other's
+/// place in turn, one ring of the image after the other.
+fn rotate_square_in_place<I: GenericImage>(
+    image: &mut I,
+    clockwise: bool,
+) -> crate::ImageResult<()> {
+    let (width, height) = image.dimensions();
+    if width != height {
+        return Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::DimensionMismatch,
+        )));
+    }
+
+    let last = width.saturating_sub(1);
+    for y in 0..width / 2 {
+        for x in y..last - y {
+            let corners = [(x, y), (last - y, x), (last - x, last - y), (y, last - x)];
+            let pixels = corners.map(|(x, y)| image.get_pixel(x, y));
+            for (i, &(x, y)) in corners.iter().enumerate() {
+                // Clockwise, every pixel moves on to the next corner.
+                let from = (if clockwise { i + 3 } else { i + 1 }) % 4;
+                image.put_pixel(x, y, pixels[from]);
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Flip an image horizontally in place.
 pub fn flip_horizontal_in_place<I: GenericImage>(image: &mut I) {
     let (width, height) = image.dimensions();
@@ -265,7 +307,7 @@ pub fn flip_vertical_in_place<I: GenericImage>(image: &mut I) {
 mod test {
     use super::{
         flip_horizontal, flip_horizontal_in_place, flip_vertical, flip_vertical_in_place,
-        rotate180, rotate180_in_place, rotate270, rotate90,
+        rotate180, rotate180_in_place, rotate270, rotate270_in_place, rotate90, rotate90_in_place,
     };
     use crate::image::GenericImage;
     use crate::traits::Pixel;
@@ -348,6 +390,29 @@ mod test {
         assert_pixels_eq!(&image, &expected);
     }
 
+    #[test]
+    fn test_rotate_square_in_place() {
+        for size in [0u32, 1, 2, 3, 4, 5] {
+            let image = GrayImage::from_fn(size, size, |x, y| crate::Luma([(x * 16 + y) as u8]));
+
+            let mut clockwise = image.clone();
+            rotate90_in_place(&mut clockwise).unwrap();
+            assert_pixels_eq!(&clockwise, &rotate90(&image));
+
+            let mut counter = image.clone();
+            rotate270_in_place(&mut counter).unwrap();
+            assert_pixels_eq!(&counter, &rotate270(&image));
+
+            let mut half = image.clone();
+            rotate180_in_place(&mut half);
+            assert_pixels_eq!(&half, &rotate180(&image));
+        }
+
+        let mut wide: GrayImage = ImageBuffer::new(3, 2);
+        assert!(rotate90_in_place(&mut wide).is_err());
+        assert!(rotate270_in_place(&mut wide).is_err());
+    }
+
     #[test]
     fn test_flip_horizontal() {
         let image: GrayImage =
@@ -1,11 +1,11 @@
 //! Functions for altering and converting the color of pixelbufs
 
-use num_traits::NumCast;
+use num_traits::{NumCast, Zero};
 use std::f64::consts::PI;
 
-use crate::color::{FromColor, IntoColor, Luma, LumaA};
+use crate::color::{FromColor, FromPrimitive, IntoColor, Luma, LumaA, Rgba};
 use crate::image::{GenericImage, GenericImageView};
-use crate::traits::{Pixel, Primitive};
+use crate::traits::{Enlargeable, Pixel, Primitive};
 use crate::utils::clamp;
 use crate::ImageBuffer;
 
@@ -67,6 +67,10 @@ where
 
 /// Invert each pixel within the supplied image.
 /// This function operates in place.
+///
+/// Only the color channels are inverted, the alpha channel is left untouched. Each channel is
+/// subtracted from the maximum value of its type, which is `1.0` for floating point images. Use
+/// [`invert_with_max`] for floating point images with values outside of `0.0..=1.0`.
 pub fn invert<I: GenericImage>(image: &mut I) {
     // TODO find a way to use pixels?
     let (width, height) = image.dimensions();
@@ -81,6 +85,83 @@ pub fn invert<I: GenericImage>(image: &mut I) {
     }
 }
 
+/// Invert each pixel within the supplied image, relative to `max`.
+/// This function operates in place.
+///
+/// Each color channel is clamped to `0..=max` and then subtracted from `max`, the alpha channel
+/// is left untouched. This is mostly useful for HDR images, whose floating point channels may
+/// exceed `1.0`.
+///
+/// *[See also `invert`.][invert]*
+pub fn invert_with_max<I: GenericImage>(image: &mut I, max: Subpixel<I>) {
+    let zero = Subpixel::<I>::zero();
+    let (width, height) = image.dimensions();
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut p = image.get_pixel(x, y);
+            p.apply_without_alpha(|c| max - clamp(c, zero, max));
+
+            image.put_pixel(x, y, p);
+        }
+    }
+}
+
+/// Multiply the color channels of each pixel by its alpha value.
+/// This function operates in place.
+///
+/// Fully transparent pixels end up with all channels set to zero. Integer channels are rounded
+/// to the nearest representable value.
+///
+/// *[See also `unpremultiply_alpha`.][unpremultiply_alpha]*
+pub fn premultiply_alpha<I, S>(image: &mut I)
+where
+    I: GenericImage<Pixel = Rgba<S>>,
+    S: Primitive + Enlargeable + FromPrimitive<f32>,
+{
+    map_alpha_normalized(image, |c, a| c * a);
+}
+
+/// Divide the color channels of each pixel by its alpha value, undoing `premultiply_alpha`.
+/// This function operates in place.
+///
+/// Fully transparent pixels carry no color information and end up with all channels set to
+/// zero. Integer channels are rounded to the nearest representable value.
+///
+/// *[See also `premultiply_alpha`.][premultiply_alpha]*
+pub fn unpremultiply_alpha<I, S>(image: &mut I)
+where
+    I: GenericImage<Pixel = Rgba<S>>,
+    S: Primitive + Enlargeable + FromPrimitive<f32>,
+{
+    map_alpha_normalized(image, |c, a| if a == 0.0 { 0.0 } else { c / a });
+}
+
+/// Apply `f` to each color channel and the alpha of its pixel, both normalized to `0.0..=1.0`.
+fn map_alpha_normalized<I, S>(image: &mut I, f: impl Fn(f32, f32) -> f32)
+where
+    I: GenericImage<Pixel = Rgba<S>>,
+    S: Primitive + Enlargeable + FromPrimitive<f32>,
+{
+    let (width, height) = image.dimensions();
+    let max: f32 = NumCast::from(S::DEFAULT_MAX_VALUE).unwrap();
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut p = image.get_pixel(x, y);
+            let alpha: f32 = NumCast::from(p[3]).unwrap();
+            let alpha = alpha / max;
+
+            p.apply_without_alpha(|c| {
+                let c: f32 = NumCast::from(c).unwrap();
+                S::from_primitive(f(c / max, alpha))
+            });
+
+            image.put_pixel(x, y, p);
+        }
+    }
+}
+
 /// Adjust the contrast of the supplied image.
 /// ```contrast``` is the amount to adjust the contrast by.
 /// Negative values decrease the contrast and positive values increase the contrast.
@@ -347,6 +428,289 @@ where
     }
 }
 
+/// The channels whose histogram [`equalize`] flattens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Equalization {
+    /// Equalize each color channel independently. This maximizes the contrast of every channel
+    /// but may shift hues.
+    PerChannel,
+    /// Equalize the luminance and shift all color channels of a pixel by the same amount, which
+    /// roughly preserves hues.
+    Luminance,
+}
+
+/// Enhance the contrast of the supplied image by histogram equalization.
+/// This function operates in place.
+///
+/// Values are remapped through the cumulative distribution of their histogram, spreading them
+/// over the full range. The alpha channel is left unchanged. Channels consisting of a single
+/// value are left unchanged as well.
+pub fn equalize<I>(image: &mut I, mode: Equalization)
+where
+    I: GenericImage,
+    I::Pixel: Pixel<Subpixel = u8>,
+{
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    // Determine the position of the alpha channel, if any, by marking it.
+    let marked = image.get_pixel(0, 0).map_with_alpha(|_| 0, |_| 1);
+    let is_alpha: Vec<bool> = marked.channels().iter().map(|&c| c == 1).collect();
+
+    match mode {
+        Equalization::PerChannel => {
+            let mut histograms = vec![[0u64; 256]; is_alpha.len()];
+            for (_, _, p) in image.pixels() {
+                for (histogram, &c) in histograms.iter_mut().zip(p.channels()) {
+                    histogram[c as usize] += 1;
+                }
+            }
+            let luts: Vec<[u8; 256]> = histograms.iter().map(equalization_lut).collect();
+
+            for y in 0..height {
+                for x in 0..width {
+                    let mut p = image.get_pixel(x, y);
+                    for (i, c) in p.channels_mut().iter_mut().enumerate() {
+                        if !is_alpha[i] {
+                            *c = luts[i][*c as usize];
+                        }
+                    }
+                    image.put_pixel(x, y, p);
+                }
+            }
+        }
+        Equalization::Luminance => {
+            let mut histogram = [0u64; 256];
+            for (_, _, p) in image.pixels() {
+                histogram[p.to_luma().0[0] as usize] += 1;
+            }
+            let lut = equalization_lut(&histogram);
+
+            for y in 0..height {
+                for x in 0..width {
+                    let mut p = image.get_pixel(x, y);
+                    let luma = p.to_luma().0[0];
+                    let shift =
+                        <i16 as From<_>>::from(lut[luma as usize]) - <i16 as From<_>>::from(luma);
+                    for (i, c) in p.channels_mut().iter_mut().enumerate() {
+                        if !is_alpha[i] {
+                            *c = (<i16 as From<_>>::from(*c) + shift).clamp(0, 255) as u8;
+                        }
+                    }
+                    image.put_pixel(x, y, p);
+                }
+            }
+        }
+    }
+}
+
+/// Build the lookup table mapping each value to its equalized counterpart.
+fn equalization_lut(histogram: &[u64; 256]) -> [u8; 256] {
+    let mut lut = [0; 256];
+    for (i, entry) in lut.iter_mut().enumerate() {
+        *entry = i as u8;
+    }
+
+    let total: u64 = histogram.iter().sum();
+    let cdf_min = histogram.iter().copied().find(|&n| n > 0).unwrap_or(0);
+    if total == cdf_min {
+        return lut;
+    }
+
+    let mut cdf = 0;
+    for (entry, &n) in lut.iter_mut().zip(histogram) {
+        cdf += n;
+        let scaled = cdf.saturating_sub(cdf_min) as f64 / (total - cdf_min) as f64;
+        *entry = (scaled * 255.0).round() as u8;
+    }
+    lut
+}
+
+/// Stretch the tonal range of the supplied image to the full range, ignoring outliers.
+/// This function operates in place.
+///
+/// The darkest `low_pct` and the brightest `high_pct` percent of the pixels, by luminance, are
+/// clipped to black and white. The luminance range of the remaining pixels is then stretched over
+/// the full range, scaling all color channels of a pixel alike so that hues are roughly kept.
+/// A few stray black or white pixels thus don't prevent the bulk of the image from gaining
+/// contrast. The alpha channel is left unchanged, as are images whose remaining pixels all have
+/// the same luminance.
+pub fn autocontrast<I>(image: &mut I, low_pct: f32, high_pct: f32)
+where
+    I: GenericImage,
+    I::Pixel: Pixel<Subpixel = u8>,
+{
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let mut histogram = [0u64; 256];
+    for (_, _, p) in image.pixels() {
+        histogram[p.to_luma().0[0] as usize] += 1;
+    }
+
+    let low = cutoff(&histogram, 0..256, low_pct);
+    let high = cutoff(&histogram, (0..256).rev(), high_pct);
+    if high <= low {
+        return;
+    }
+
+    let scale = 255.0 / (high - low);
+    for y in 0..height {
+        for x in 0..width {
+            let p = image.get_pixel(x, y);
+            let p =
+                p.map_without_alpha(|c| ((c as f32 - low) * scale).round().clamp(0.0, 255.0) as u8);
+            image.put_pixel(x, y, p);
+        }
+    }
+}
+
+/// Find the first value, in the order given, past the `pct` percent of pixels to clip.
+fn cutoff(histogram: &[u64; 256], values: impl Iterator<Item = usize>, pct: f32) -> f32 {
+    let total: u64 = histogram.iter().sum();
+    let clipped = (total as f64 * pct.clamp(0.0, 100.0) as f64 / 100.0) as u64;
+    let mut count = 0;
+    let mut values = values.skip_while(|&v| {
+        count += histogram[v];
+        count <= clipped
+    });
+    values.next().unwrap_or(0) as f32
+}
+
+/// Map every color channel of the supplied image through the lookup table `lut`.
+/// This function operates in place.
+///
+/// Each channel value `c` is replaced by `lut[c]`, which can express any tone curve, such as
+/// those of film emulation looks. The alpha channel is left unchanged.
+///
+/// *[See also `apply_lut_rgb`.][apply_lut_rgb]*
+pub fn apply_lut<I>(image: &mut I, lut: &[u8; 256])
+where
+    I: GenericImage,
+    I::Pixel: Pixel<Subpixel = u8>,
+{
+    let (width, height) = image.dimensions();
+    for y in 0..height {
+        for x in 0..width {
+            let mut p = image.get_pixel(x, y);
+            p.apply_without_alpha(|c| lut[c as usize]);
+            image.put_pixel(x, y, p);
+        }
+    }
+}
+
+/// Map the red, green and blue channels of the supplied image through a lookup table each.
+/// This function operates in place.
+///
+/// Like [`apply_lut`], but with a separate table per channel in the order red, green, blue, which
+/// allows for color casts and split toning. The channel of gray images is mapped through the first
+/// table. The alpha channel is left unchanged.
+pub fn apply_lut_rgb<I>(image: &mut I, luts: &[[u8; 256]; 3])
+where
+    I: GenericImage,
+    I::Pixel: Pixel<Subpixel = u8>,
+{
+    let (width, height) = image.dimensions();
+    for y in 0..height {
+        for x in 0..width {
+            let mut p = image.get_pixel(x, y);
+            let mut luts = luts.iter();
+            p.apply_without_alpha(|c| luts.next().map_or(c, |lut| lut[c as usize]));
+            image.put_pixel(x, y, p);
+        }
+    }
+}
+
+/// Replace every pixel within `tolerance` of the color `from` by the color `to`.
+/// This function operates in place.
+///
+/// A pixel matches if none of its channels, including alpha, differs from the corresponding
+/// channel of `from` by more than `tolerance`. A tolerance of zero only replaces exact matches.
+/// All other pixels are left unchanged.
+pub fn replace_color<I>(image: &mut I, from: Rgba<u8>, to: Rgba<u8>, tolerance: u8)
+where
+    I: GenericImage<Pixel = Rgba<u8>>,
+{
+    let (width, height) = image.dimensions();
+
+    for y in 0..height {
+        for x in 0..width {
+            let p = image.get_pixel(x, y);
+            let matches =
+                p.0.iter()
+                    .zip(from.0)
+                    .all(|(&c, f)| c.abs_diff(f) <= tolerance);
+            if matches {
+                image.put_pixel(x, y, to);
+            }
+        }
+    }
+}
+
+/// Binarize the supplied image with a global threshold.
+///
+/// Pixels whose luminance is at least `level` become white, all others black.
+///
+/// *[See also `adaptive_threshold`.][adaptive_threshold]*
+pub fn threshold<I>(image: &I, level: u8) -> ImageBuffer<Luma<u8>, Vec<u8>>
+where
+    I: GenericImageView,
+    I::Pixel: Pixel<Subpixel = u8>,
+{
+    let (width, height) = image.dimensions();
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let luma = image.get_pixel(x, y).to_luma().0[0];
+        Luma([if luma >= level { 255 } else { 0 }])
+    })
+}
+
+/// Binarize the supplied image by comparing each pixel to the mean of its neighborhood.
+///
+/// The neighborhood is a square window extending `radius` pixels in each direction, cut off at
+/// the borders of the image. Pixels whose luminance exceeds the local mean minus `offset` become
+/// white, all others black. A positive `offset` keeps flat regions white, so that only features
+/// darker than their surroundings turn black. This copes with uneven lighting where a global
+/// threshold would not.
+///
+/// *[See also `threshold`.][threshold]*
+pub fn adaptive_threshold<I>(image: &I, radius: u32, offset: i16) -> ImageBuffer<Luma<u8>, Vec<u8>>
+where
+    I: GenericImageView,
+    I::Pixel: Pixel<Subpixel = u8>,
+{
+    let (width, height) = image.dimensions();
+    let (w, h) = (width as usize, height as usize);
+
+    // Summed-area table with an additional leading row and column of zeros.
+    let mut sums = vec![0u64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let mut row_sum = 0;
+        for x in 0..w {
+            row_sum += image.get_pixel(x as u32, y as u32).to_luma().0[0] as u64;
+            sums[(y + 1) * (w + 1) + x + 1] = sums[y * (w + 1) + x + 1] + row_sum;
+        }
+    }
+
+    let radius = radius as usize;
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let (x, y) = (x as usize, y as usize);
+        let (x0, x1) = (x.saturating_sub(radius), (x + radius + 1).min(w));
+        let (y0, y1) = (y.saturating_sub(radius), (y + radius + 1).min(h));
+
+        let sum = sums[y1 * (w + 1) + x1] + sums[y0 * (w + 1) + x0]
+            - sums[y0 * (w + 1) + x1]
+            - sums[y1 * (w + 1) + x0];
+        let mean = sum as f32 / ((x1 - x0) * (y1 - y0)) as f32;
+
+        let luma = image.get_pixel(x as u32, y as u32).to_luma().0[0] as f32;
+        Luma([if luma > mean - offset as f32 { 255 } else { 0 }])
+    })
+}
+
 /// A color map
 pub trait ColorMap {
     /// The color type on which the map operates on
@@ -435,9 +799,8 @@ impl ColorMap for BiLevel {
     }
 }
 
-#[cfg(feature = "color_quant")]
 impl ColorMap for color_quant::NeuQuant {
-    type Color = crate::color::Rgba<u8>;
+    type Color = Rgba<u8>;
 
     #[inline(always)]
     fn index_of(&self, color: &Self::Color) -> usize {
@@ -546,7 +909,134 @@ where
 mod test {
 
     use super::*;
-    use crate::GrayImage;
+    use crate::color::Rgb;
+    use crate::{GrayImage, Rgb32FImage, RgbImage, RgbaImage};
+
+    #[test]
+    fn test_equalize_stretches_range() {
+        // A low-contrast gradient using only values 100..=131.
+        let mut image = GrayImage::from_fn(32, 8, |x, _| Luma([100 + x as u8]));
+        equalize(&mut image, Equalization::PerChannel);
+
+        let min = image.pixels().map(|p| p.0[0]).min().unwrap();
+        let max = image.pixels().map(|p| p.0[0]).max().unwrap();
+        assert!(min < 10, "min: {min}");
+        assert_eq!(max, 255);
+
+        // The mapping is monotonic.
+        let row: Vec<u8> = (0..32).map(|x| image.get_pixel(x, 0).0[0]).collect();
+        assert!(row.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_equalize_luminance_keeps_alpha() {
+        let mut image: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(16, 1, |x, _| Rgba([110 + x as u8, 100 + x as u8, 90, 77]));
+        equalize(&mut image, Equalization::Luminance);
+
+        assert!(image.pixels().all(|p| p.0[3] == 77));
+        let first = image.get_pixel(0, 0).0;
+        let last = image.get_pixel(15, 0).0;
+        assert!(last[0] - first[0] > 100);
+        // Channels of a pixel are shifted together, unless they saturate.
+        let middle = image.get_pixel(8, 0).0;
+        assert_eq!(middle[0] - middle[1], 10);
+        assert_eq!(middle[0] - 118, middle[2] - 90);
+    }
+
+    #[test]
+    fn test_replace_color() {
+        let blue = Rgba([20, 40, 200, 255]);
+        let red = Rgba([255, 0, 0, 255]);
+        let colors = [
+            blue,
+            Rgba([22, 38, 203, 255]),
+            Rgba([20, 40, 200, 250]),
+            Rgba([20, 40, 196, 255]),
+            Rgba([20, 180, 20, 255]),
+            Rgba([0, 0, 0, 0]),
+        ];
+        let mut image: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(6, 2, |x, _| colors[x as usize]);
+        replace_color(&mut image, blue, red, 3);
+
+        let expected = [red, red, colors[2], colors[3], colors[4], colors[5]];
+        for (x, _, p) in image.enumerate_pixels() {
+            assert_eq!(*p, expected[x as usize], "pixel {x}");
+        }
+    }
+
+    #[test]
+    fn test_equalize_flat_image() {
+        let mut image = GrayImage::from_pixel(4, 4, Luma([42]));
+        equalize(&mut image, Equalization::PerChannel);
+        assert!(image.pixels().all(|p| p.0[0] == 42));
+    }
+
+    #[test]
+    fn test_autocontrast_ignores_outliers() {
+        // A gradient over 60..=159 with five black and five white pixels in its middle.
+        let mut image = GrayImage::from_fn(100, 10, |x, y| match (x, y) {
+            (50, 0..=4) => Luma([0]),
+            (51, 0..=4) => Luma([255]),
+            _ => Luma([60 + x as u8]),
+        });
+
+        // The outliers already span the full range, so nothing changes without clipping.
+        let original = image.clone();
+        autocontrast(&mut image, 0.0, 0.0);
+        assert_eq!(image, original);
+
+        // Clipping 1% on either side looks past them and stretches the gradient instead.
+        autocontrast(&mut image, 1.0, 1.0);
+        assert_eq!(image.get_pixel(0, 9).0, [0]);
+        assert_eq!(image.get_pixel(99, 9).0, [255]);
+        assert_eq!(image.get_pixel(33, 9).0, [85]);
+        assert_eq!(image.get_pixel(50, 0).0, [0]);
+        assert_eq!(image.get_pixel(51, 0).0, [255]);
+
+        // Colors are scaled alike and alpha is kept.
+        let colors = [[100, 100, 100, 7], [150, 150, 150, 7], [130, 120, 140, 7]];
+        let mut image = RgbaImage::from_fn(3, 1, |x, _| Rgba(colors[x as usize]));
+        autocontrast(&mut image, 0.0, 0.0);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 7]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 255, 255, 7]);
+        assert_eq!(image.get_pixel(2, 0).0, [153, 102, 204, 7]);
+    }
+
+    #[test]
+    fn test_apply_lut() {
+        let image = RgbaImage::from_fn(16, 16, |x, y| {
+            Rgba([(x * 16) as u8, (y * 16) as u8, (x * y) as u8, (x + y) as u8])
+        });
+
+        let mut identity = [0; 256];
+        for (i, entry) in identity.iter_mut().enumerate() {
+            *entry = i as u8;
+        }
+        let mut same = image.clone();
+        apply_lut(&mut same, &identity);
+        assert_eq!(same, image);
+
+        let mut inverted = image.clone();
+        apply_lut(&mut inverted, &identity.map(|v| 255 - v));
+        let mut expected = image.clone();
+        invert(&mut expected);
+        assert_eq!(inverted, expected);
+
+        // One table per channel, the alpha channel is kept.
+        let mut tinted = image.clone();
+        apply_lut_rgb(
+            &mut tinted,
+            &[identity.map(|v| 255 - v), identity, [7; 256]],
+        );
+        for (a, b) in tinted.pixels().zip(image.pixels()) {
+            assert_eq!(a.0, [255 - b[0], b[1], 7, b[3]]);
+        }
+        let mut gray = GrayImage::from_pixel(2, 2, Luma([10]));
+        apply_lut_rgb(&mut gray, &[[1; 256], [2; 256], [3; 256]]);
+        assert!(gray.pixels().all(|p| p.0 == [1]));
+    }
 
     macro_rules! assert_pixels_eq {
         ($actual:expr, $expected:expr) => {{
@@ -588,6 +1078,73 @@ mod test {
         assert_eq!(index_colors(&image, &cmap).into_raw(), vec![0, 1, 1, 0])
     }
 
+    /// The eight corners of the RGB cube, indexed by one bit per channel.
+    struct Cube;
+
+    impl ColorMap for Cube {
+        type Color = Rgb<u8>;
+
+        fn index_of(&self, color: &Rgb<u8>) -> usize {
+            color
+                .0
+                .iter()
+                .fold(0, |index, &c| index << 1 | (c > 127) as usize)
+        }
+
+        fn lookup(&self, index: usize) -> Option<Rgb<u8>> {
+            (index < 8)
+                .then(|| Rgb([2, 1, 0].map(|bit| if index >> bit & 1 == 1 { 255 } else { 0 })))
+        }
+
+        fn has_lookup(&self) -> bool {
+            true
+        }
+
+        fn map_color(&self, color: &mut Rgb<u8>) {
+            *color = self.lookup(self.index_of(color)).unwrap();
+        }
+    }
+
+    #[test]
+    fn custom_color_map() {
+        let colors = [
+            Rgb([10, 20, 30]),
+            Rgb([200, 20, 30]),
+            Rgb([10, 200, 30]),
+            Rgb([10, 20, 200]),
+            Rgb([250, 240, 230]),
+            Rgb([128, 127, 255]),
+        ];
+        let image = ImageBuffer::from_fn(6, 1, |x, _| colors[x as usize]);
+        let indices = index_colors(&image, &Cube);
+        assert_eq!(indices.into_raw(), vec![0, 4, 2, 1, 7, 5]);
+        for index in 0..8 {
+            assert_eq!(Cube.index_of(&Cube.lookup(index).unwrap()), index);
+        }
+
+        // Plain mapping turns a gradient into two flat bands, dithering follows it on average.
+        let gradient = ImageBuffer::from_fn(64, 32, |x, _| Rgb([x as u8 * 4; 3]));
+        let mut banded = gradient.clone();
+        banded.pixels_mut().for_each(|p| Cube.map_color(p));
+        let mut dithered = gradient.clone();
+        dither(&mut dithered, &Cube);
+        assert!(dithered
+            .pixels()
+            .all(|p| Cube.lookup(Cube.index_of(p)) == Some(*p)));
+
+        let column_error = |image: &RgbImage| -> f32 {
+            (0..64)
+                .map(|x| {
+                    let sum: u32 = (0..32).map(|y| image.get_pixel(x, y)[0] as u32).sum();
+                    (sum as f32 / 32.0 - gradient.get_pixel(x, 0)[0] as f32).abs()
+                })
+                .sum::<f32>()
+                / 64.0
+        };
+        let (banded, dithered) = (column_error(&banded), column_error(&dithered));
+        assert!(dithered * 4.0 < banded, "{dithered} vs {banded}");
+    }
+
     #[test]
     fn test_grayscale() {
         let image: GrayImage =
@@ -599,6 +1156,23 @@ mod test {
         assert_pixels_eq!(&grayscale(&image), &expected);
     }
 
+    #[test]
+    fn grayscale_alpha_preserves_alpha() {
+        let image: RgbaImage = ImageBuffer::from_pixel(2, 1, Rgba([200, 100, 50, 128]));
+        let gray = grayscale_alpha(&image);
+
+        // Rec. 709 luma: 0.2126 R + 0.7152 G + 0.0722 B, rounded down.
+        let luma = (0.2126f32 * 200.0 + 0.7152 * 100.0 + 0.0722 * 50.0) as u8;
+        assert_eq!(luma, 117);
+        assert!(gray.pixels().all(|&p| p == LumaA([luma, 128])));
+
+        let deep = ImageBuffer::from_pixel(1, 1, Rgba([0u16, 65535, 0, 1000]));
+        assert_eq!(
+            *grayscale_alpha(&deep).get_pixel(0, 0),
+            LumaA([46870, 1000])
+        );
+    }
+
     #[test]
     fn test_invert() {
         let mut image: GrayImage =
@@ -610,6 +1184,29 @@ mod test {
         invert(&mut image);
         assert_pixels_eq!(&image, &expected);
     }
+
+    #[test]
+    fn test_invert_preserves_alpha() {
+        let mut image: RgbaImage =
+            ImageBuffer::from_raw(2, 1, vec![0, 100, 255, 0, 10, 20, 30, 128]).unwrap();
+
+        invert(&mut image);
+        assert_eq!(image.into_raw(), vec![255, 155, 0, 0, 245, 235, 225, 128]);
+    }
+
+    #[test]
+    fn test_invert_with_max() {
+        let mut image: Rgb32FImage =
+            ImageBuffer::from_raw(2, 1, vec![0.25, 0.0, 1.0, 2.5, -1.0, 4.0]).unwrap();
+
+        invert_with_max(&mut image, 1.0);
+        assert_eq!(image.into_raw(), vec![0.75, 1.0, 0.0, 0.0, 1.0, 0.0]);
+
+        let mut image: Rgb32FImage = ImageBuffer::from_raw(1, 1, vec![0.25, 1.5, 4.0]).unwrap();
+
+        invert_with_max(&mut image, 4.0);
+        assert_eq!(image.into_raw(), vec![3.75, 2.5, 0.0]);
+    }
     #[test]
     fn test_brighten() {
         let image: GrayImage =
@@ -633,6 +1230,55 @@ mod test {
         assert_pixels_eq!(&image, &expected);
     }
 
+    #[test]
+    fn test_premultiply_alpha_roundtrip() {
+        let pixels = [
+            Rgba([200u8, 100, 50, 128]),
+            Rgba([255, 255, 255, 255]),
+            Rgba([90, 60, 30, 0]),
+            Rgba([10, 250, 128, 64]),
+        ];
+        let original = ImageBuffer::from_fn(2, 2, |x, y| pixels[(y * 2 + x) as usize]);
+
+        let mut image = original.clone();
+        premultiply_alpha(&mut image);
+        assert_eq!(image.get_pixel(0, 0), &Rgba([100, 50, 25, 128]));
+        assert_eq!(image.get_pixel(1, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(image.get_pixel(0, 1), &Rgba([0, 0, 0, 0]));
+
+        unpremultiply_alpha(&mut image);
+        for (p, q) in image.pixels().zip(original.pixels()) {
+            assert_eq!(p[3], q[3]);
+            if q[3] == 0 {
+                assert_eq!(p, &Rgba([0, 0, 0, 0]));
+            } else {
+                for c in 0..3 {
+                    assert!(p[c].abs_diff(q[c]) <= 2, "{:?} != {:?}", p, q);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_premultiply_alpha_float() {
+        let mut image = ImageBuffer::from_pixel(1, 1, Rgba([0.5f32, 1.0, 0.25, 0.5]));
+        premultiply_alpha(&mut image);
+        assert_eq!(image.get_pixel(0, 0), &Rgba([0.25, 0.5, 0.125, 0.5]));
+        unpremultiply_alpha(&mut image);
+        assert_eq!(image.get_pixel(0, 0), &Rgba([0.5, 1.0, 0.25, 0.5]));
+
+        let mut transparent = ImageBuffer::from_pixel(1, 1, Rgba([0.5f32, 1.0, 1.0, 0.0]));
+        unpremultiply_alpha(&mut transparent);
+        assert_eq!(transparent.get_pixel(0, 0), &Rgba([0.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_premultiply_alpha_u16() {
+        let mut image = ImageBuffer::from_pixel(1, 1, Rgba([65535u16, 32768, 0, 32768]));
+        premultiply_alpha(&mut image);
+        assert_eq!(image.get_pixel(0, 0), &Rgba([32768, 16384, 0, 32768]));
+    }
+
     #[allow(clippy::type_complexity)]
     fn pixel_diffs<I, J, P>(left: &I, right: &J) -> Vec<((u32, u32, P), (u32, u32, P))>
     where
@@ -645,4 +1291,40 @@ mod test {
             .filter(|&(p, q)| p != q)
             .collect::<Vec<_>>()
     }
+
+    #[test]
+    fn test_threshold_gradient() {
+        let image = GrayImage::from_fn(256, 2, |x, _| Luma([x as u8]));
+        let binary = threshold(&image, 128);
+
+        for (x, _, p) in binary.enumerate_pixels() {
+            assert_eq!(p.0[0], if x < 128 { 0 } else { 255 });
+        }
+    }
+
+    #[test]
+    fn test_adaptive_threshold_uneven_lighting() {
+        // A page lit from the right, with dark strokes in every eighth column.
+        let is_stroke = |x: u32| x % 8 == 3;
+        let page = GrayImage::from_fn(128, 16, |x, _| {
+            let background = (40 + x * 3 / 2) as u8;
+            Luma([if is_stroke(x) {
+                background - 30
+            } else {
+                background
+            }])
+        });
+
+        let errors = |binary: &GrayImage| {
+            binary
+                .enumerate_pixels()
+                .filter(|&(x, _, p)| (p.0[0] == 0) != is_stroke(x))
+                .count()
+        };
+
+        let global = errors(&threshold(&page, 128));
+        let adaptive = errors(&adaptive_threshold(&page, 4, 5));
+        assert_eq!(adaptive, 0);
+        assert!(global > 0);
+    }
 }
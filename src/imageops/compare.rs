@@ -0,0 +1,228 @@
+//! Functions for measuring the similarity of two images
+
+use crate::image::GenericImageView;
+use crate::traits::{Pixel, Primitive};
+
+/// Side length of the square windows over which local statistics are gathered by [`ssim`].
+const SSIM_WINDOW: u32 = 8;
+/// Offset between neighbouring windows of [`ssim`].
+const SSIM_STEP: u32 = 4;
+/// Stabilizing constants of the SSIM formula, for a dynamic range of `1.0`.
+const SSIM_C1: f64 = 0.01 * 0.01;
+const SSIM_C2: f64 = 0.03 * 0.03;
+
+/// Compute the mean squared error between two images of equal size.
+///
+/// All channels, including alpha, contribute equally. Channel values are normalized by the
+/// nominal maximum of their subpixel type, so the result lies within `0.0..=1.0` for integer
+/// images regardless of their bit depth.
+///
+/// # Panics
+///
+/// Panics if the dimensions of the images differ.
+pub fn mse<I, J, P>(a: &I, b: &J) -> f64
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+{
+    assert_same_dimensions(a, b);
+
+    let (width, height) = a.dimensions();
+    let samples = u64::from(width) * u64::from(height) * u64::from(P::CHANNEL_COUNT);
+    if samples == 0 {
+        return 0.0;
+    }
+
+    let sum: f64 = a
+        .pixels()
+        .zip(b.pixels())
+        .map(|((_, _, p), (_, _, q))| {
+            p.channels()
+                .iter()
+                .zip(q.channels())
+                .map(|(&x, &y)| {
+                    let diff = normalize(x) - normalize(y);
+                    diff * diff
+                })
+                .sum::<f64>()
+        })
+        .sum();
+
+    sum / samples as f64
+}
+
+/// Compute the peak signal-to-noise ratio, in decibels, between two images of equal size.
+///
+/// This is derived from [`mse`] with a peak value of `1.0`. Identical images have an infinite
+/// PSNR.
+///
+/// # Panics
+///
+/// Panics if the dimensions of the images differ.
+pub fn psnr<I, J, P>(a: &I, b: &J) -> f64
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+{
+    let mse = mse(a, b);
+    if mse == 0.0 {
+        f64::INFINITY
+    } else {
+        -10.0 * mse.log10()
+    }
+}
+
+/// Compute the structural similarity index (SSIM) between two images of equal size.
+///
+/// Local means, variances and the covariance are gathered over overlapping 8x8 windows, for each
+/// channel separately, and the resulting indices are averaged. Images smaller than a window are
+/// treated as a single window. The result is `1.0` for identical images and decreases as they
+/// diverge.
+///
+/// See <https://en.wikipedia.org/wiki/Structural_similarity>
+///
+/// # Panics
+///
+/// Panics if the dimensions of the images differ.
+pub fn ssim<I, J, P>(a: &I, b: &J) -> f64
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+{
+    assert_same_dimensions(a, b);
+
+    let (width, height) = a.dimensions();
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+
+    let window_width = width.min(SSIM_WINDOW);
+    let window_height = height.min(SSIM_WINDOW);
+    let channels = usize::from(P::CHANNEL_COUNT);
+
+    let mut total = 0.0;
+    let mut windows = 0u64;
+
+    for y0 in window_starts(height, window_height) {
+        for x0 in window_starts(width, window_width) {
+            for c in 0..channels {
+                let mut sum_a = 0.0;
+                let mut sum_b = 0.0;
+                let mut sum_aa = 0.0;
+                let mut sum_bb = 0.0;
+                let mut sum_ab = 0.0;
+
+                for y in y0..y0 + window_height {
+                    for x in x0..x0 + window_width {
+                        let p = normalize(a.get_pixel(x, y).channels()[c]);
+                        let q = normalize(b.get_pixel(x, y).channels()[c]);
+                        sum_a += p;
+                        sum_b += q;
+                        sum_aa += p * p;
+                        sum_bb += q * q;
+                        sum_ab += p * q;
+                    }
+                }
+
+                let n = f64::from(window_width * window_height);
+                let mean_a = sum_a / n;
+                let mean_b = sum_b / n;
+                let var_a = (sum_aa / n - mean_a * mean_a).max(0.0);
+                let var_b = (sum_bb / n - mean_b * mean_b).max(0.0);
+                let covar = sum_ab / n - mean_a * mean_b;
+
+                total += ((2.0 * mean_a * mean_b + SSIM_C1) * (2.0 * covar + SSIM_C2))
+                    / ((mean_a * mean_a + mean_b * mean_b + SSIM_C1) * (var_a + var_b + SSIM_C2));
+                windows += 1;
+            }
+        }
+    }
+
+    total / windows as f64
+}
+
+/// The starting offsets of all windows along one axis, the last one flush with the border.
+fn window_starts(length: u32, window: u32) -> impl Iterator<Item = u32> {
+    let last = length - window;
+    (0..last)
+        .step_by(SSIM_STEP as usize)
+        .chain(std::iter::once(last))
+}
+
+fn normalize<S: Primitive>(value: S) -> f64 {
+    let value: f64 = num_traits::NumCast::from(value).unwrap();
+    let max: f64 = num_traits::NumCast::from(S::DEFAULT_MAX_VALUE).unwrap();
+    value / max
+}
+
+fn assert_same_dimensions<I: GenericImageView, J: GenericImageView>(a: &I, b: &J) {
+    assert_eq!(
+        a.dimensions(),
+        b.dimensions(),
+        "images must have the same dimensions"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GrayImage, RgbImage};
+
+    fn noisy(image: &RgbImage) -> RgbImage {
+        RgbImage::from_fn(image.width(), image.height(), |x, y| {
+            let offset = ((x * 7 + y * 13) % 5) as u8;
+            image.get_pixel(x, y).map(|c| c.saturating_add(offset))
+        })
+    }
+
+    fn test_image() -> RgbImage {
+        RgbImage::from_fn(32, 24, |x, y| {
+            crate::Rgb([(x * 8) as u8, (y * 10) as u8, ((x + y) * 4) as u8])
+        })
+    }
+
+    #[test]
+    fn identical_images() {
+        let image = test_image();
+        assert_eq!(mse(&image, &image), 0.0);
+        assert_eq!(psnr(&image, &image), f64::INFINITY);
+        assert!((ssim(&image, &image) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn noisy_images() {
+        let image = test_image();
+        let noisy = noisy(&image);
+
+        assert!(mse(&image, &noisy) > 0.0);
+        assert!(psnr(&image, &noisy).is_finite());
+        let index = ssim(&image, &noisy);
+        assert!(index < 1.0);
+        assert!(index > 0.5);
+    }
+
+    #[test]
+    fn psnr_of_known_error() {
+        let a = GrayImage::from_pixel(4, 4, crate::Luma([0]));
+        let b = GrayImage::from_pixel(4, 4, crate::Luma([255]));
+        assert_eq!(mse(&a, &b), 1.0);
+        assert_eq!(psnr(&a, &b), 0.0);
+    }
+
+    #[test]
+    fn small_images() {
+        let a = GrayImage::from_pixel(3, 2, crate::Luma([10]));
+        assert!((ssim(&a, &a) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn mismatched_dimensions() {
+        let a = GrayImage::new(3, 2);
+        let b = GrayImage::new(2, 3);
+        let _ = ssim(&a, &b);
+    }
+}
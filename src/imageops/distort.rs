@@ -0,0 +1,286 @@
+//! Geometric distortion of images by displacement maps
+
+use super::sample::{interpolate_bilinear, FloatNearest};
+use crate::image::GenericImageView;
+use crate::traits::{Pixel, Primitive};
+use crate::{ImageBuffer, Luma};
+
+/// How to sample positions that lie outside of the source image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeMode {
+    /// Repeat the nearest pixel on the edge of the image.
+    Clamp,
+    /// Continue from the opposite edge, as if the image was tiled.
+    Wrap,
+    /// Treat pixels outside of the image as having all channels zero, which is transparent black
+    /// for images with an alpha channel.
+    Zero,
+}
+
+/// Warp an image by moving every pixel according to a pair of displacement maps.
+///
+/// The output pixel at `(x, y)` is sampled from the position `(x + dx, y + dy)` of the source
+/// image, where `dx` and `dy` are the values of the maps at `(x, y)` in pixels. Fractional
+/// positions are interpolated bilinearly, and positions outside of the image are resolved by
+/// `edge`. Smoothly varying maps give effects such as ripples or heat haze.
+///
+/// # Panics
+///
+/// Panics if the dimensions of either map differ from those of the image.
+pub fn displace<I, P, S>(
+    image: &I,
+    dx: &ImageBuffer<Luma<f32>, Vec<f32>>,
+    dy: &ImageBuffer<Luma<f32>, Vec<f32>>,
+    edge: EdgeMode,
+) -> ImageBuffer<P, Vec<S>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let (width, height) = image.dimensions();
+    assert_eq!(dx.dimensions(), (width, height), "dx map size mismatch");
+    assert_eq!(dy.dimensions(), (width, height), "dy map size mismatch");
+
+    let max: f32 = num_traits::NumCast::from(S::max_value()).unwrap_or(f32::MAX);
+    let min: f32 = num_traits::NumCast::from(S::min_value()).unwrap_or(f32::MIN);
+    let channels = usize::from(P::CHANNEL_COUNT);
+
+    let resolve = |coordinate: i64, size: u32| -> Option<u32> {
+        let size = i64::from(size);
+        match edge {
+            EdgeMode::Clamp => Some(coordinate.clamp(0, size - 1) as u32),
+            EdgeMode::Wrap => Some(coordinate.rem_euclid(size) as u32),
+            EdgeMode::Zero => (0..size).contains(&coordinate).then_some(coordinate as u32),
+        }
+    };
+
+    let mut out: ImageBuffer<P, Vec<S>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in out.enumerate_pixels_mut() {
+        let sx = x as f32 + dx.get_pixel(x, y).0[0];
+        let sy = y as f32 + dy.get_pixel(x, y).0[0];
+        if !sx.is_finite() || !sy.is_finite() {
+            continue;
+        }
+
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let mut sums = [0.0f32; 4];
+        for (ox, oy, weight) in [
+            (0, 0, (1.0 - fx) * (1.0 - fy)),
+            (1, 0, fx * (1.0 - fy)),
+            (0, 1, (1.0 - fx) * fy),
+            (1, 1, fx * fy),
+        ] {
+            if weight == 0.0 {
+                continue;
+            }
+            let (Some(u), Some(v)) = (resolve(x0 + ox, width), resolve(y0 + oy, height)) else {
+                continue;
+            };
+            let source = image.get_pixel(u, v);
+            for (sum, c) in sums.iter_mut().zip(source.channels()) {
+                *sum += weight * c.to_f32().unwrap_or(0.0);
+            }
+        }
+
+        for (c, &sum) in pixel.channels_mut().iter_mut().zip(&sums[..channels]) {
+            *c = num_traits::NumCast::from(FloatNearest(sum.clamp(min, max))).unwrap();
+        }
+    }
+
+    out
+}
+
+/// Rectify a quadrilateral of an image into a rectangle, such as a photographed document.
+///
+/// The corners of `src_quad` are given in source pixel coordinates, in the order top-left,
+/// top-right, bottom-right and bottom-left. They are mapped onto the centers of the corner pixels
+/// of an output image of `dst_size`, and every pixel in between is sampled bilinearly from the
+/// position given by the perspective transform that relates the two. Pixels whose position lies
+/// outside of the source image are set to `fill`, so all of them are if the source is empty.
+///
+/// # Panics
+///
+/// Panics if three of the corners lie on a line, as no perspective transform maps a rectangle
+/// onto such a quadrilateral.
+pub fn warp_perspective<I, P, S>(
+    image: &I,
+    src_quad: [(f32, f32); 4],
+    dst_size: (u32, u32),
+    fill: P,
+) -> ImageBuffer<P, Vec<S>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let homography = Homography::from_unit_square(src_quad.map(|(x, y)| (x.into(), y.into())))
+        .expect("degenerate source quadrilateral");
+    let (width, height) = image.dimensions();
+    let (dst_width, dst_height) = dst_size;
+    if width == 0 || height == 0 {
+        return ImageBuffer::from_pixel(dst_width, dst_height, fill);
+    }
+    let scale = |size: u32| if size > 1 { f64::from(size - 1) } else { 1.0 };
+    let (scale_x, scale_y) = (scale(dst_width), scale(dst_height));
+
+    ImageBuffer::from_fn(dst_width, dst_height, |x, y| {
+        let (sx, sy) = homography.apply(f64::from(x) / scale_x, f64::from(y) / scale_y);
+        // Positions within half a pixel of the border still lie on a source pixel, and may be
+        // just outside of it due to rounding when they were meant to be exactly on a corner.
+        let inside = |v: f64, size: u32| v >= -0.5 && v <= f64::from(size) - 0.5;
+        if !inside(sx, width) || !inside(sy, height) {
+            return fill;
+        }
+        let sx = sx.clamp(0.0, f64::from(width - 1)) as f32;
+        let sy = sy.clamp(0.0, f64::from(height - 1)) as f32;
+        interpolate_bilinear(image, sx, sy).unwrap_or(fill)
+    })
+}
+
+/// A projective map `(u, v) -> ((au + bv + c) / w, (du + ev + f) / w)` with `w = gu + hv + 1`.
+struct Homography([f64; 8]);
+
+impl Homography {
+    /// The map taking the corners of the unit square, clockwise from the origin, onto `quad`.
+    ///
+    /// See Heckbert, "Fundamentals of Texture Mapping and Image Warping", section 2.2.3.
+    fn from_unit_square(quad: [(f64, f64); 4]) -> Option<Self> {
+        let [(x0, y0), (x1, y1), (x2, y2), (x3, y3)] = quad;
+        let (sx, sy) = (x0 - x1 + x2 - x3, y0 - y1 + y2 - y3);
+        let (dx1, dx2, dy1, dy2) = (x1 - x2, x3 - x2, y1 - y2, y3 - y2);
+
+        let den = dx1 * dy2 - dx2 * dy1;
+        if den.abs() < f64::EPSILON || !den.is_finite() {
+            return None;
+        }
+        let g = (sx * dy2 - dx2 * sy) / den;
+        let h = (dx1 * sy - sx * dy1) / den;
+        let (a, b, d, e) = (
+            x1 - x0 + g * x1,
+            x3 - x0 + h * x3,
+            y1 - y0 + g * y1,
+            y3 - y0 + h * y3,
+        );
+        // The unit square itself has to be mapped onto an area, not a line.
+        if (a * e - b * d).abs() < f64::EPSILON {
+            return None;
+        }
+        Some(Homography([a, b, x0, d, e, y0, g, h]))
+    }
+
+    fn apply(&self, u: f64, v: f64) -> (f64, f64) {
+        let [a, b, c, d, e, f, g, h] = self.0;
+        let w = g * u + h * v + 1.0;
+        ((a * u + b * v + c) / w, (d * u + e * v + f) / w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GrayImage, Rgba, RgbaImage};
+
+    fn pattern() -> RgbaImage {
+        RgbaImage::from_fn(7, 5, |x, y| Rgba([x as u8 * 30, y as u8 * 40, 100, 255]))
+    }
+
+    fn constant_map(value: f32) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+        ImageBuffer::from_pixel(7, 5, Luma([value]))
+    }
+
+    #[test]
+    fn zero_displacement_is_identity() {
+        let image = pattern();
+        for edge in [EdgeMode::Clamp, EdgeMode::Wrap, EdgeMode::Zero] {
+            let out = displace(&image, &constant_map(0.0), &constant_map(0.0), edge);
+            assert_eq!(out, image);
+        }
+    }
+
+    #[test]
+    fn constant_shift_translates() {
+        let image = pattern();
+        let shifted = |edge| displace(&image, &constant_map(2.0), &constant_map(-1.0), edge);
+
+        let clamped = shifted(EdgeMode::Clamp);
+        let wrapped = shifted(EdgeMode::Wrap);
+        let zeroed = shifted(EdgeMode::Zero);
+        for (x, y, pixel) in clamped.enumerate_pixels() {
+            let (sx, sy) = (x as i64 + 2, y as i64 - 1);
+            let clamp = image.get_pixel(sx.clamp(0, 6) as u32, sy.clamp(0, 4) as u32);
+            assert_eq!(pixel, clamp);
+
+            let wrap = image.get_pixel(sx.rem_euclid(7) as u32, sy.rem_euclid(5) as u32);
+            assert_eq!(wrapped.get_pixel(x, y), wrap);
+
+            let inside = (0..7).contains(&sx) && (0..5).contains(&sy);
+            let zero = if inside { *clamp } else { Rgba([0; 4]) };
+            assert_eq!(*zeroed.get_pixel(x, y), zero);
+        }
+    }
+
+    #[test]
+    fn fractional_shift_interpolates() {
+        let image = GrayImage::from_fn(4, 1, |x, _| Luma([x as u8 * 60]));
+        let dx = ImageBuffer::from_pixel(4, 1, Luma([0.5f32]));
+        let dy = ImageBuffer::from_pixel(4, 1, Luma([0.0f32]));
+        let out = displace(&image, &dx, &dy, EdgeMode::Clamp);
+        assert_eq!(out.into_raw(), vec![30, 90, 150, 180]);
+    }
+
+    #[test]
+    fn warp_trapezoid_to_rectangle() {
+        // A trapezoid with thick white top and bottom edges and colored marks at its corners.
+        let corners = [(40, 30), (160, 30), (190, 170), (10, 170)];
+        let marks = [
+            Rgba([255, 0, 0, 255]),
+            Rgba([0, 255, 0, 255]),
+            Rgba([0, 0, 255, 255]),
+            Rgba([255, 255, 0, 255]),
+        ];
+        let white = Rgba([255; 4]);
+        let mut image = RgbaImage::from_pixel(200, 200, Rgba([0, 0, 0, 255]));
+        for (x, y) in (0..200).flat_map(|x| (0..200).map(move |y| (x, y))) {
+            let on_edge = |(x0, ey): (u32, u32), (x1, _): (u32, u32)| {
+                (x0 - 2..=x1 + 2).contains(&x) && ey.abs_diff(y) < 2
+            };
+            if on_edge(corners[0], corners[1]) || on_edge(corners[3], corners[2]) {
+                image.put_pixel(x, y, white);
+            }
+        }
+        for (&(cx, cy), &mark) in corners.iter().zip(&marks) {
+            for (x, y) in (cx - 2..=cx + 2).flat_map(|x| (cy - 2..=cy + 2).map(move |y| (x, y))) {
+                image.put_pixel(x, y, mark);
+            }
+        }
+
+        let quad = corners.map(|(x, y)| (x as f32, y as f32));
+        let fill = Rgba([1, 2, 3, 4]);
+        let out = warp_perspective(&image, quad, (64, 48), fill);
+        assert_eq!(out.dimensions(), (64, 48));
+        for ((x, y), mark) in [(0, 0), (63, 0), (63, 47), (0, 47)].into_iter().zip(marks) {
+            assert_eq!(*out.get_pixel(x, y), mark, "corner ({x}, {y})");
+        }
+        // The straight edges of the trapezoid become the top and bottom rows of the rectangle.
+        for x in 3..61 {
+            assert_eq!(*out.get_pixel(x, 0), white, "top at {x}");
+            assert_eq!(*out.get_pixel(x, 47), white, "bottom at {x}");
+        }
+        assert_eq!(*out.get_pixel(32, 24), Rgba([0, 0, 0, 255]));
+
+        // Corners outside of the source image produce the fill color.
+        let outside = [(-50.0, -50.0), (160.0, 30.0), (190.0, 170.0), (10.0, 170.0)];
+        let out = warp_perspective(&image, outside, (64, 48), fill);
+        assert_eq!(*out.get_pixel(0, 0), fill);
+        assert_eq!(*out.get_pixel(63, 47), marks[2]);
+
+        // The first corner lies on the border of an empty image.
+        let quad = [(-0.5, -0.5), (1.0, -0.5), (1.0, 1.0), (-0.5, 1.0)];
+        let out = warp_perspective(&RgbaImage::new(0, 0), quad, (3, 2), fill);
+        assert_eq!(out, RgbaImage::from_pixel(3, 2, fill));
+    }
+}
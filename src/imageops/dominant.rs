@@ -0,0 +1,215 @@
+//! Extraction of the dominant colors of an image
+
+use std::cmp::Ordering;
+
+use crate::color::Rgb;
+use crate::image::GenericImageView;
+use crate::traits::{Pixel, Primitive};
+
+/// Upper bound on the number of pixels that are clustered by [`dominant_colors`].
+const MAX_SAMPLES: u64 = 1 << 14;
+/// Upper bound on the number of refinement passes of [`dominant_colors`].
+const MAX_ITERATIONS: usize = 32;
+
+/// Find the `k` most prominent colors of an image by k-means clustering.
+///
+/// Returns each color along with the fraction of the image it covers, ordered from the most to
+/// the least common. Fewer than `k` colors are returned if the image does not contain `k`
+/// distinct colors. Alpha is ignored.
+///
+/// Large images are subsampled on a regular grid before clustering. The initial centers are
+/// chosen deterministically, starting from the sample farthest from the mean color and then
+/// adding the sample farthest from all centers so far, so the result is reproducible for a given
+/// image.
+pub fn dominant_colors<I, P, S>(image: &I, k: usize) -> Vec<(Rgb<u8>, f32)>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S>,
+    S: Primitive,
+{
+    let samples = collect_samples(image);
+    if k == 0 || samples.is_empty() {
+        return Vec::new();
+    }
+
+    let mut centers = initial_centers(&samples, k);
+    let mut assignment = vec![usize::MAX; samples.len()];
+    let mut counts = vec![0usize; centers.len()];
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (sample, assigned) in samples.iter().zip(assignment.iter_mut()) {
+            let nearest = nearest_center(&centers, sample).0;
+            if *assigned != nearest {
+                *assigned = nearest;
+                changed = true;
+            }
+        }
+
+        let mut sums = vec![[0.0f64; 3]; centers.len()];
+        counts.iter_mut().for_each(|c| *c = 0);
+        for (sample, &assigned) in samples.iter().zip(&assignment) {
+            for (sum, value) in sums[assigned].iter_mut().zip(sample) {
+                *sum += f64::from(*value);
+            }
+            counts[assigned] += 1;
+        }
+        for ((center, sum), &count) in centers.iter_mut().zip(&sums).zip(&counts) {
+            if count > 0 {
+                for (c, s) in center.iter_mut().zip(sum) {
+                    *c = (s / count as f64) as f32;
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let total = samples.len() as f32;
+    let mut colors: Vec<(Rgb<u8>, f32)> = centers
+        .iter()
+        .zip(&counts)
+        .filter(|(_, &count)| count > 0)
+        .map(|(center, &count)| {
+            let color = Rgb(center.map(|c| c.round().clamp(0.0, 255.0) as u8));
+            (color, count as f32 / total)
+        })
+        .collect();
+    colors.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    colors
+}
+
+/// Gather the colors of a regular grid of pixels, scaled to `0.0..=255.0`.
+fn collect_samples<I, P, S>(image: &I) -> Vec<[f32; 3]>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S>,
+    S: Primitive,
+{
+    let (width, height) = image.dimensions();
+    let pixels = u64::from(width) * u64::from(height);
+    if pixels == 0 {
+        return Vec::new();
+    }
+
+    let step = ((pixels as f64 / MAX_SAMPLES as f64).sqrt().ceil() as u32).max(1);
+    let max: f32 = num_traits::NumCast::from(S::DEFAULT_MAX_VALUE).unwrap();
+    let scale = |value: S| {
+        let value: f32 = num_traits::NumCast::from(value).unwrap_or(0.0);
+        (value / max * 255.0).clamp(0.0, 255.0)
+    };
+
+    (0..height)
+        .step_by(step as usize)
+        .flat_map(|y| (0..width).step_by(step as usize).map(move |x| (x, y)))
+        .map(|(x, y)| image.get_pixel(x, y).to_rgb().0.map(scale))
+        .collect()
+}
+
+/// Pick up to `k` distinct starting centers by farthest-point traversal.
+fn initial_centers(samples: &[[f32; 3]], k: usize) -> Vec<[f32; 3]> {
+    let mut mean = [0.0f64; 3];
+    for sample in samples {
+        for (m, value) in mean.iter_mut().zip(sample) {
+            *m += f64::from(*value);
+        }
+    }
+    let mean = mean.map(|m| (m / samples.len() as f64) as f32);
+
+    // The mean itself may lie far from every pixel, so it only serves to pick the first center.
+    let first = farthest_sample(&[mean], samples).0;
+    let mut centers = vec![samples[first]];
+    while centers.len() < k {
+        let (index, distance) = farthest_sample(&centers, samples);
+        if distance == 0.0 {
+            break;
+        }
+        centers.push(samples[index]);
+    }
+    centers
+}
+
+/// Find the sample with the greatest distance to its nearest center.
+fn farthest_sample(centers: &[[f32; 3]], samples: &[[f32; 3]]) -> (usize, f32) {
+    let mut best = (0, 0.0);
+    for (index, sample) in samples.iter().enumerate() {
+        let distance = nearest_center(centers, sample).1;
+        if distance > best.1 {
+            best = (index, distance);
+        }
+    }
+    best
+}
+
+/// Find the index of the center closest to `sample`, along with its squared distance.
+fn nearest_center(centers: &[[f32; 3]], sample: &[f32; 3]) -> (usize, f32) {
+    let mut best = (0, f32::INFINITY);
+    for (index, center) in centers.iter().enumerate() {
+        let distance: f32 = center
+            .iter()
+            .zip(sample)
+            .map(|(c, s)| (c - s) * (c - s))
+            .sum();
+        if distance < best.1 {
+            best = (index, distance);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rgb32FImage, RgbImage, RgbaImage};
+
+    #[test]
+    fn recovers_known_colors() {
+        let red = Rgb([200, 30, 40]);
+        let green = Rgb([20, 180, 60]);
+        let blue = Rgb([10, 40, 220]);
+        // Half red, 30% green and 20% blue, large enough to be subsampled.
+        let image = RgbImage::from_fn(300, 200, |_, y| match y {
+            0..=99 => red,
+            100..=159 => green,
+            _ => blue,
+        });
+
+        let colors = dominant_colors(&image, 3);
+        assert_eq!(colors.len(), 3);
+        for ((color, weight), (expected, expected_weight)) in
+            colors.iter().zip([(red, 0.5), (green, 0.3), (blue, 0.2)])
+        {
+            assert_eq!(*color, expected);
+            assert!((weight - expected_weight).abs() < 0.05, "{weight}");
+        }
+
+        assert_eq!(colors, dominant_colors(&image, 3));
+    }
+
+    #[test]
+    fn fewer_colors_than_requested() {
+        let image = RgbaImage::from_pixel(8, 8, crate::Rgba([1, 2, 3, 4]));
+        assert_eq!(dominant_colors(&image, 4), vec![(Rgb([1, 2, 3]), 1.0)]);
+        assert!(dominant_colors(&image, 0).is_empty());
+        assert!(dominant_colors(&RgbImage::new(0, 5), 2).is_empty());
+    }
+
+    #[test]
+    fn float_image() {
+        let image = Rgb32FImage::from_fn(4, 4, |x, _| {
+            if x < 2 {
+                Rgb([1.0, 0.0, 0.0])
+            } else {
+                Rgb([0.0, 0.0, 1.0])
+            }
+        });
+        let mut colors = dominant_colors(&image, 2);
+        colors.sort_by_key(|(color, _)| color.0);
+        assert_eq!(
+            colors,
+            vec![(Rgb([0, 0, 255]), 0.5), (Rgb([255, 0, 0]), 0.5)]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Functions for detecting edges in images
+
+use crate::color::Luma;
+use crate::image::GenericImageView;
+use crate::traits::{Pixel, Primitive};
+use crate::{GrayImage, ImageBuffer};
+
+/// Weights of the Sobel kernel for the derivative along the x axis, in row-major order.
+const SOBEL_X: [f32; 9] = [-1.0, 0.0, 1.0, -2.0, 0.0, 2.0, -1.0, 0.0, 1.0];
+/// Weights of the Sobel kernel for the derivative along the y axis, in row-major order.
+const SOBEL_Y: [f32; 9] = [-1.0, -2.0, -1.0, 0.0, 0.0, 0.0, 1.0, 2.0, 1.0];
+
+/// Compute the horizontal gradient of an image with the Sobel operator.
+///
+/// The image is converted to grayscale and normalized to `0.0..=1.0` first, so the result lies
+/// within `-4.0..=4.0` and is positive where the image gets brighter towards the right. Pixels
+/// outside the image are taken from the nearest edge.
+pub fn sobel_horizontal<I, P>(image: &I) -> ImageBuffer<Luma<f32>, Vec<f32>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel,
+{
+    convolve(&intensity(image), &SOBEL_X)
+}
+
+/// Compute the vertical gradient of an image with the Sobel operator.
+///
+/// Like [`sobel_horizontal`], but positive where the image gets brighter towards the bottom.
+pub fn sobel_vertical<I, P>(image: &I) -> ImageBuffer<Luma<f32>, Vec<f32>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel,
+{
+    convolve(&intensity(image), &SOBEL_Y)
+}
+
+/// Compute the gradient magnitude of an image with the Sobel operator.
+///
+/// The magnitude is scaled such that a step from black to white along one axis maps to `255`,
+/// larger values are clamped. Flat regions map to `0`.
+pub fn sobel<I, P>(image: &I) -> GrayImage
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel,
+{
+    let intensity = intensity(image);
+    let gx = convolve(&intensity, &SOBEL_X);
+    let gy = convolve(&intensity, &SOBEL_Y);
+
+    GrayImage::from_fn(intensity.width(), intensity.height(), |x, y| {
+        let (dx, dy) = (gx.get_pixel(x, y).0[0], gy.get_pixel(x, y).0[0]);
+        let magnitude = (dx * dx + dy * dy).sqrt() / 4.0;
+        Luma([(magnitude * 255.0).round().min(255.0) as u8])
+    })
+}
+
+/// Convert an image to grayscale with intensities in `0.0..=1.0`.
+fn intensity<I, P>(image: &I) -> ImageBuffer<Luma<f32>, Vec<f32>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel,
+{
+    let max: f32 = num_traits::NumCast::from(P::Subpixel::DEFAULT_MAX_VALUE).unwrap();
+    let (width, height) = image.dimensions();
+
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let luma: f32 = num_traits::NumCast::from(image.get_pixel(x, y).to_luma().0[0]).unwrap();
+        Luma([luma / max])
+    })
+}
+
+/// Apply a 3x3 kernel, clamping the sample positions to the image.
+fn convolve(
+    image: &ImageBuffer<Luma<f32>, Vec<f32>>,
+    kernel: &[f32; 9],
+) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+    let (width, height) = image.dimensions();
+
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let mut sum = 0.0;
+        for (i, &k) in kernel.iter().enumerate() {
+            let sx = (x + i as u32 % 3).saturating_sub(1).min(width - 1);
+            let sy = (y + i as u32 / 3).saturating_sub(1).min(height - 1);
+            sum += k * image.get_pixel(sx, sy).0[0];
+        }
+        Luma([sum])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertical_edge() -> GrayImage {
+        GrayImage::from_fn(8, 6, |x, _| Luma([if x < 4 { 0 } else { 255 }]))
+    }
+
+    #[test]
+    fn gradient_peaks_along_edge() {
+        let edges = sobel(&vertical_edge());
+
+        for y in 0..6 {
+            assert_eq!(edges.get_pixel(3, y).0[0], 255);
+            assert_eq!(edges.get_pixel(4, y).0[0], 255);
+            for x in [0, 1, 2, 5, 6, 7] {
+                assert_eq!(edges.get_pixel(x, y).0[0], 0);
+            }
+        }
+    }
+
+    #[test]
+    fn gradient_directions() {
+        let image = vertical_edge();
+        let gx = sobel_horizontal(&image);
+        let gy = sobel_vertical(&image);
+
+        assert_eq!(gx.get_pixel(3, 2).0[0], 4.0);
+        assert_eq!(gx.get_pixel(0, 2).0[0], 0.0);
+        assert!(gy.pixels().all(|p| p.0[0] == 0.0));
+    }
+
+    #[test]
+    fn color_input() {
+        let image = crate::RgbImage::from_fn(5, 5, |_, y| {
+            crate::Rgb(if y < 2 { [0, 0, 0] } else { [255, 255, 255] })
+        });
+        let edges = sobel(&image);
+        assert_eq!(edges.get_pixel(2, 0).0[0], 0);
+        assert_eq!(edges.get_pixel(2, 1).0[0], 255);
+    }
+}
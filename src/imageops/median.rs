@@ -0,0 +1,206 @@
+//! Median filtering for the removal of impulse noise
+
+use std::cmp::Ordering;
+
+use crate::image::GenericImageView;
+use crate::traits::{Pixel, Primitive};
+use crate::ImageBuffer;
+
+/// Apply a median filter to an image.
+///
+/// Every channel of every pixel is replaced with the median of that channel over the
+/// `(2 * radius + 1)²` pixels centered on it. Pixels outside the image are taken from the nearest
+/// edge. This removes isolated outliers such as salt-and-pepper noise while preserving edges.
+///
+/// The medians are tracked with a histogram that slides along each row, so the cost per pixel
+/// grows linearly with the radius rather than with the size of the window.
+///
+/// # Panics
+///
+/// If the window holds more than `u64::MAX` samples, that is for a radius of `2^31` or more.
+pub fn median_filter<I, P, S>(image: &I, radius: u32) -> ImageBuffer<P, Vec<S>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(width, height);
+    if width == 0 || height == 0 {
+        return out;
+    }
+
+    let pixels: Vec<P> = image.pixels().map(|(_, _, p)| p).collect();
+    // The window always holds an odd number of samples, so the median is exact.
+    let side = 2 * u64::from(radius) + 1;
+    let median_index = side
+        .checked_mul(side)
+        .expect("median filter radius is too large")
+        / 2;
+    let radius = i64::from(radius);
+
+    for channel in 0..usize::from(P::CHANNEL_COUNT) {
+        // Work on the rank of each value within the sorted distinct values of the channel, which
+        // keeps the histogram as small as possible for any subpixel type.
+        let mut values: Vec<S> = pixels.iter().map(|p| p.channels()[channel]).collect();
+        values.sort_by(compare);
+        values.dedup();
+        let ranks: Vec<usize> = pixels
+            .iter()
+            .map(|p| {
+                let value = p.channels()[channel];
+                values.binary_search_by(|v| compare(v, &value)).unwrap_or(0)
+            })
+            .collect();
+
+        let rank_at = |x: i64, y: i64| {
+            let x = x.clamp(0, i64::from(width) - 1) as usize;
+            let y = y.clamp(0, i64::from(height) - 1) as usize;
+            ranks[y * width as usize + x]
+        };
+
+        let mut window = Window::new(values.len());
+        for y in 0..i64::from(height) {
+            // Move the window from the end of the previous row to the start of this one.
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    if y > 0 {
+                        window.remove(rank_at(i64::from(width) - 1 + dx, y - 1 + dy));
+                    }
+                    window.add(rank_at(dx, y + dy));
+                }
+            }
+
+            for x in 0..i64::from(width) {
+                if x > 0 {
+                    for dy in -radius..=radius {
+                        window.remove(rank_at(x - 1 - radius, y + dy));
+                        window.add(rank_at(x + radius, y + dy));
+                    }
+                }
+
+                let median = values[window.median(median_index)];
+                out.get_pixel_mut(x as u32, y as u32).channels_mut()[channel] = median;
+            }
+        }
+    }
+
+    out
+}
+
+/// Orders samples as `partial_cmp` does, and NaN values as `f64::total_cmp` does, such that
+/// sorting and searching agree on every pair.
+fn compare<S: Primitive>(a: &S, b: &S) -> Ordering {
+    a.partial_cmp(b)
+        .unwrap_or_else(|| a.to_f64().unwrap().total_cmp(&b.to_f64().unwrap()))
+}
+
+/// A histogram of the ranks within the filter window, along with a running median.
+struct Window {
+    histogram: Vec<u64>,
+    /// The rank of the median found last.
+    median: usize,
+    /// The number of samples with a rank below `median`.
+    below: u64,
+}
+
+impl Window {
+    fn new(ranks: usize) -> Self {
+        Window {
+            histogram: vec![0; ranks],
+            median: 0,
+            below: 0,
+        }
+    }
+
+    fn add(&mut self, rank: usize) {
+        self.histogram[rank] += 1;
+        if rank < self.median {
+            self.below += 1;
+        }
+    }
+
+    fn remove(&mut self, rank: usize) {
+        self.histogram[rank] -= 1;
+        if rank < self.median {
+            self.below -= 1;
+        }
+    }
+
+    /// Find the rank of the sample at `index` in sorted order, starting from the last median.
+    fn median(&mut self, index: u64) -> usize {
+        while self.below > index {
+            self.median -= 1;
+            self.below -= self.histogram[self.median];
+        }
+        while self.below + self.histogram[self.median] <= index {
+            self.below += self.histogram[self.median];
+            self.median += 1;
+        }
+        self.median
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GrayImage, Luma, Rgb, Rgb32FImage, RgbImage};
+
+    #[test]
+    fn removes_impulse_noise() {
+        let mut image = RgbImage::from_pixel(9, 7, Rgb([100, 150, 200]));
+        for (x, y) in [(0, 0), (4, 3), (8, 6), (2, 5), (6, 1)] {
+            image.put_pixel(x, y, Rgb([255, 0, 255]));
+        }
+
+        let filtered = median_filter(&image, 1);
+        assert!(filtered.pixels().all(|p| *p == Rgb([100, 150, 200])));
+    }
+
+    #[test]
+    fn matches_sorted_window() {
+        let image = GrayImage::from_fn(13, 11, |x, y| Luma([((x * 37 + y * 91) % 23) as u8]));
+
+        for radius in [0, 1, 2, 5] {
+            let filtered = median_filter(&image, radius);
+            let r = i64::from(radius);
+            for (x, y, pixel) in filtered.enumerate_pixels() {
+                let mut window = Vec::new();
+                for dy in -r..=r {
+                    for dx in -r..=r {
+                        let sx = (i64::from(x) + dx).clamp(0, 12) as u32;
+                        let sy = (i64::from(y) + dy).clamp(0, 10) as u32;
+                        window.push(image.get_pixel(sx, sy).0[0]);
+                    }
+                }
+                window.sort_unstable();
+                assert_eq!(pixel.0[0], window[window.len() / 2], "radius {radius}");
+            }
+        }
+    }
+
+    #[test]
+    fn float_image() {
+        let mut image = Rgb32FImage::from_pixel(5, 5, Rgb([0.25, 4.0, 1.0]));
+        image.put_pixel(2, 2, Rgb([100.0, -3.0, 0.0]));
+
+        let filtered = median_filter(&image, 1);
+        assert_eq!(
+            filtered,
+            Rgb32FImage::from_pixel(5, 5, Rgb([0.25, 4.0, 1.0]))
+        );
+    }
+
+    #[test]
+    fn nan_samples() {
+        let mut image = Rgb32FImage::from_pixel(5, 5, Rgb([0.25, 4.0, 1.0]));
+        image.put_pixel(2, 2, Rgb([f32::NAN, -f32::NAN, 0.0]));
+        image.put_pixel(0, 4, Rgb([f32::NAN, 3.0, f32::NAN]));
+
+        let filtered = median_filter(&image, 1);
+        assert_eq!(
+            filtered,
+            Rgb32FImage::from_pixel(5, 5, Rgb([0.25, 4.0, 1.0]))
+        );
+    }
+}
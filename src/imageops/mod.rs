@@ -1,10 +1,13 @@
 //! Image Processing Functions
 use std::cmp;
 
+use crate::color::FromPrimitive;
+use crate::error::{ImageError, ImageResult, ParameterError, ParameterErrorKind};
 use crate::image::{GenericImage, GenericImageView, SubImage};
 use crate::traits::{Lerp, Pixel, Primitive};
+use crate::{GrayImage, ImageBuffer};
 
-pub use self::sample::FilterType;
+pub use self::sample::{EdgeHandling, FilterType};
 
 pub use self::sample::FilterType::{CatmullRom, Gaussian, Lanczos3, Nearest, Triangle};
 
@@ -12,26 +15,58 @@ pub use self::sample::FilterType::{CatmullRom, Gaussian, Lanczos3, Nearest, Tria
 pub use self::affine::{
     flip_horizontal, flip_horizontal_in, flip_horizontal_in_place, flip_vertical, flip_vertical_in,
     flip_vertical_in_place, rotate180, rotate180_in, rotate180_in_place, rotate270, rotate270_in,
-    rotate90, rotate90_in,
+    rotate270_in_place, rotate90, rotate90_in, rotate90_in_place,
 };
 
 /// Image sampling
 pub use self::sample::{
-    blur, filter3x3, interpolate_bilinear, interpolate_nearest, resize, sample_bilinear,
-    sample_nearest, thumbnail, unsharpen,
+    blur, filter3x3, interpolate_bilinear, interpolate_nearest, resize, resize_antialiased,
+    resize_integer, resize_into, resize_with_color_key, resize_with_edges, resize_xy,
+    sample_bilinear, sample_nearest, thumbnail, unsharp_mask, unsharpen, ResizePlan,
 };
 
 /// Color operations
 pub use self::colorops::{
-    brighten, contrast, dither, grayscale, grayscale_alpha, grayscale_with_type,
-    grayscale_with_type_alpha, huerotate, index_colors, invert, BiLevel, ColorMap,
+    adaptive_threshold, apply_lut, apply_lut_rgb, autocontrast, brighten, contrast, dither,
+    equalize, grayscale, grayscale_alpha, grayscale_with_type, grayscale_with_type_alpha,
+    huerotate, index_colors, invert, invert_with_max, premultiply_alpha, replace_color, threshold,
+    unpremultiply_alpha, BiLevel, ColorMap, Equalization,
 };
 
+/// Image comparison
+pub use self::compare::{mse, psnr, ssim};
+
+/// Color analysis
+pub use self::dominant::dominant_colors;
+
+/// Geometric distortion
+pub use self::distort::{displace, warp_perspective, EdgeMode};
+
+/// Edge detection
+pub use self::edge::{sobel, sobel_horizontal, sobel_vertical};
+
+/// Noise reduction
+pub use self::median::median_filter;
+
+/// Composition
+pub use self::montage::montage;
+
+/// Content-aware resizing
+pub use self::seam_carving::seam_carve;
+
 mod affine;
+mod compare;
+mod distort;
+mod dominant;
+mod edge;
+mod median;
+mod montage;
 // Public only because of Rust bug:
 // https://github.com/rust-lang/rust/issues/18241
 pub mod colorops;
+pub mod quantize;
 mod sample;
+mod seam_carving;
 
 /// Return a mutable view into an image
 /// The coordinates set the position of the top left corner of the crop.
@@ -59,7 +94,59 @@ pub fn crop_imm<I: GenericImageView>(
     SubImage::new(image, x, y, width, height)
 }
 
-fn crop_dimms<I: GenericImageView>(
+/// Return a mutable view into an image, or an error if the rectangle does not fit.
+///
+/// Unlike [`crop`], which shrinks the rectangle to the part overlapping the image, this fails
+/// with a [`ParameterError`] naming the rectangle and the image dimensions if any part of the
+/// rectangle lies outside of the image.
+///
+/// [`ParameterError`]: crate::error::ParameterError
+pub fn try_crop<I: GenericImageView>(
+    image: &mut I,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> ImageResult<SubImage<&mut I>> {
+    check_crop(image, x, y, width, height)?;
+    Ok(SubImage::new(image, x, y, width, height))
+}
+
+/// Return an immutable view into an image, or an error if the rectangle does not fit.
+///
+/// This is the immutable counterpart of [`try_crop`].
+pub fn try_crop_imm<I: GenericImageView>(
+    image: &I,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> ImageResult<SubImage<&I>> {
+    check_crop(image, x, y, width, height)?;
+    Ok(SubImage::new(image, x, y, width, height))
+}
+
+fn check_crop<I: GenericImageView>(
+    image: &I,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> ImageResult<()> {
+    let (iwidth, iheight) = image.dimensions();
+    let fits = |start: u32, len: u32, size: u32| u64::from(start) + u64::from(len) <= size.into();
+    if fits(x, width, iwidth) && fits(y, height, iheight) {
+        Ok(())
+    } else {
+        Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::Generic(format!(
+                "crop of {width}x{height} at ({x}, {y}) exceeds the {iwidth}x{iheight} image"
+            )),
+        )))
+    }
+}
+
+pub(crate) fn crop_dimms<I: GenericImageView>(
     image: &I,
     x: u32,
     y: u32,
@@ -262,6 +349,27 @@ where
     }
 }
 
+/// Repeat an image to fill a new image of the given size.
+///
+/// Copies of `image` are laid out in rows and columns from the top left corner, and the copies
+/// along the right and bottom edges are cut off where they extend past the new image. Unlike
+/// [`tile`], which draws over an existing image and blends transparent pixels into it, the pixels
+/// are copied as they are. If `image` is empty, the new image is left black.
+pub fn tiled<I, P, S>(image: &I, width: u32, height: u32) -> ImageBuffer<P, Vec<S>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let (tile_width, tile_height) = image.dimensions();
+    if tile_width == 0 || tile_height == 0 {
+        return ImageBuffer::new(width, height);
+    }
+    ImageBuffer::from_fn(width, height, |x, y| {
+        image.get_pixel(x % tile_width, y % tile_height)
+    })
+}
+
 /// Fill the image with a linear vertical gradient
 ///
 /// This function assumes a linear color space.
@@ -328,6 +436,164 @@ where
     }
 }
 
+/// Create an image filled with a linear gradient at an arbitrary angle.
+///
+/// The `angle` is given in degrees and measured clockwise from the positive x-axis, so `0.0`
+/// runs from `start` on the left to `stop` on the right and `90.0` runs from the top to the
+/// bottom. The gradient is stretched such that the corners furthest along its direction have
+/// exactly the colors `start` and `stop`.
+///
+/// Unlike [`horizontal_gradient`] and [`vertical_gradient`], the color channels are assumed to
+/// be sRGB encoded and are interpolated in linear light, which avoids the dark band in the middle
+/// of a gradient between saturated colors. The alpha channel is interpolated as is.
+///
+/// # Examples
+/// ```no_run
+/// use image::Rgb;
+///
+/// let img = image::imageops::linear_gradient(100, 100, Rgb([255u8, 0, 0]), Rgb([0, 0, 255]), 45.0);
+/// img.save("diagonal_gradient.png").unwrap();
+/// ```
+pub fn linear_gradient<P, S>(
+    width: u32,
+    height: u32,
+    start: P,
+    stop: P,
+    angle: f32,
+) -> ImageBuffer<P, Vec<S>>
+where
+    P: Pixel<Subpixel = S>,
+    S: Primitive + FromPrimitive<f32>,
+{
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let project = |x: u32, y: u32| x as f32 * cos + y as f32 * sin;
+
+    let (max_x, max_y) = (width.saturating_sub(1), height.saturating_sub(1));
+    let corners = [
+        project(0, 0),
+        project(max_x, 0),
+        project(0, max_y),
+        project(max_x, max_y),
+    ];
+    let min = corners.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = corners.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    // Snap tiny extents, such as the vertical one of a horizontal gradient, to a constant color.
+    let range = if max - min > 1e-3 {
+        max - min
+    } else {
+        f32::INFINITY
+    };
+
+    let gradient = LinearLightGradient::new(&start, &stop);
+    ImageBuffer::from_fn(width, height, |x, y| {
+        gradient.at(((project(x, y) - min) / range).clamp(0.0, 1.0))
+    })
+}
+
+/// Create an image filled with a radial gradient.
+///
+/// The gradient has the color `start` in the center of the image and reaches `stop` at the
+/// corners. As with [`linear_gradient`], the color channels are assumed to be sRGB encoded and
+/// are interpolated in linear light.
+pub fn radial_gradient<P, S>(width: u32, height: u32, start: P, stop: P) -> ImageBuffer<P, Vec<S>>
+where
+    P: Pixel<Subpixel = S>,
+    S: Primitive + FromPrimitive<f32>,
+{
+    let center_x = width.saturating_sub(1) as f32 / 2.0;
+    let center_y = height.saturating_sub(1) as f32 / 2.0;
+    let radius = center_x.hypot(center_y);
+    let radius = if radius > 0.0 { radius } else { f32::INFINITY };
+
+    let gradient = LinearLightGradient::new(&start, &stop);
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let distance = (x as f32 - center_x).hypot(y as f32 - center_y);
+        gradient.at((distance / radius).min(1.0))
+    })
+}
+
+/// Interpolation between two colors in linear light.
+struct LinearLightGradient<P: Pixel> {
+    template: P,
+    /// Channels of the start color, linearized where they hold color instead of alpha.
+    start: Vec<f32>,
+    /// Channels of the stop color, linearized where they hold color instead of alpha.
+    stop: Vec<f32>,
+    is_alpha: Vec<bool>,
+}
+
+impl<P, S> LinearLightGradient<P>
+where
+    P: Pixel<Subpixel = S>,
+    S: Primitive + FromPrimitive<f32>,
+{
+    fn new(start: &P, stop: &P) -> Self {
+        // Determine the position of the alpha channel, if any, by marking it.
+        let marked = start.map_with_alpha(|_| S::DEFAULT_MIN_VALUE, |_| S::DEFAULT_MAX_VALUE);
+        let is_alpha: Vec<bool> = marked
+            .channels()
+            .iter()
+            .map(|&c| c == S::DEFAULT_MAX_VALUE)
+            .collect();
+
+        let max = <f32 as num_traits::NumCast>::from(S::DEFAULT_MAX_VALUE).unwrap();
+        let linearize = |pixel: &P| -> Vec<f32> {
+            pixel
+                .channels()
+                .iter()
+                .zip(&is_alpha)
+                .map(|(&c, &is_alpha)| {
+                    let c = <f32 as num_traits::NumCast>::from(c).unwrap() / max;
+                    if is_alpha {
+                        c
+                    } else {
+                        srgb_to_linear(c)
+                    }
+                })
+                .collect()
+        };
+
+        LinearLightGradient {
+            template: *start,
+            start: linearize(start),
+            stop: linearize(stop),
+            is_alpha,
+        }
+    }
+
+    fn at(&self, t: f32) -> P {
+        let mut pixel = self.template;
+        for (i, channel) in pixel.channels_mut().iter_mut().enumerate() {
+            let value = self.start[i] + (self.stop[i] - self.start[i]) * t;
+            let value = if self.is_alpha[i] {
+                value
+            } else {
+                linear_to_srgb(value)
+            };
+            *channel = S::from_primitive(value);
+        }
+        pixel
+    }
+}
+
+/// Decode a normalized sRGB value into linear light.
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a normalized linear light value with the sRGB transfer function.
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
 /// Replace the contents of an image at a given coordinate (x, y)
 pub fn replace<I, J>(bottom: &mut I, top: &J, x: i64, y: i64)
 where
@@ -349,13 +615,109 @@ where
     }
 }
 
+/// Paste an image onto another at a given coordinate (x, y), weighted by a mask.
+///
+/// Each pixel of `top` is interpolated with the pixel below it by the value of `mask` at the same
+/// position in `top`: `0` keeps the bottom pixel and `255` replaces it. All channels, including
+/// alpha, are interpolated. Only the area covered by both `top` and `mask` is pasted, clipped to
+/// the bounds of `bottom`.
+pub fn paste_with_mask<I, J, P, S>(bottom: &mut I, top: &J, mask: &GrayImage, x: i64, y: i64)
+where
+    I: GenericImage<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + Lerp + 'static,
+{
+    let bottom_dims = bottom.dimensions();
+    let top_dims = (
+        top.width().min(mask.width()),
+        top.height().min(mask.height()),
+    );
+
+    // Crop our top image if we're going out of bounds
+    let (origin_bottom_x, origin_bottom_y, origin_top_x, origin_top_y, range_width, range_height) =
+        overlay_bounds_ext(bottom_dims, top_dims, x, y);
+
+    let max = <S::Ratio as num_traits::NumCast>::from(255).unwrap();
+    for y in 0..range_height {
+        for x in 0..range_width {
+            let (top_x, top_y) = (origin_top_x + x, origin_top_y + y);
+            let weight = mask.get_pixel(top_x, top_y).0[0];
+            let weight = <S::Ratio as num_traits::NumCast>::from(weight).unwrap() / max;
+
+            let p = top.get_pixel(top_x, top_y);
+            let bottom_pixel = bottom.get_pixel(origin_bottom_x + x, origin_bottom_y + y);
+            let blended = bottom_pixel.map2(&p, |a, b| S::lerp(a, b, weight));
+
+            bottom.put_pixel(origin_bottom_x + x, origin_bottom_y + y, blended);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use super::{overlay, overlay_bounds_ext};
+    use super::{overlay, overlay_bounds_ext, tiled, try_crop, try_crop_imm};
     use crate::color::Rgb;
+    use crate::error::ImageError;
     use crate::ImageBuffer;
     use crate::RgbaImage;
+    use crate::{GenericImage, GenericImageView, GrayImage, Luma};
+
+    #[test]
+    fn tiled_clips_edge_tiles() {
+        let pattern = GrayImage::from_fn(3, 3, |x, y| Luma([(x + 3 * y) as u8]));
+        let canvas = tiled(&pattern, 7, 7);
+        assert_eq!(canvas.dimensions(), (7, 7));
+        #[rustfmt::skip]
+        let expected = [
+            0, 1, 2, 0, 1, 2, 0,
+            3, 4, 5, 3, 4, 5, 3,
+            6, 7, 8, 6, 7, 8, 6,
+            0, 1, 2, 0, 1, 2, 0,
+            3, 4, 5, 3, 4, 5, 3,
+            6, 7, 8, 6, 7, 8, 6,
+            0, 1, 2, 0, 1, 2, 0,
+        ];
+        assert_eq!(canvas.as_raw(), &expected);
+
+        // Smaller than a single tile, and from an empty image.
+        assert_eq!(tiled(&pattern, 2, 1).as_raw(), &[0, 1]);
+        assert_eq!(tiled(&GrayImage::new(0, 3), 2, 2).as_raw(), &[0; 4]);
+
+        let dynamic = crate::DynamicImage::ImageLuma8(pattern).tiled(7, 7);
+        assert_eq!(dynamic.as_luma8(), Some(&canvas));
+    }
+
+    #[test]
+    fn try_crop_valid() {
+        let mut image = GrayImage::from_fn(6, 4, |x, y| Luma([(x + 10 * y) as u8]));
+        let view = try_crop_imm(&image, 2, 1, 4, 3).unwrap();
+        assert_eq!(view.dimensions(), (4, 3));
+        assert_eq!(view.get_pixel(0, 0), Luma([12]));
+        assert_eq!(view.get_pixel(3, 2), Luma([35]));
+
+        try_crop(&mut image, 0, 0, 6, 4)
+            .unwrap()
+            .put_pixel(5, 3, Luma([0]));
+        assert_eq!(image.get_pixel(5, 3), &Luma([0]));
+        assert!(try_crop_imm(&image, 6, 4, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn try_crop_out_of_bounds() {
+        let mut image = GrayImage::new(6, 4);
+        match try_crop(&mut image, 3, 0, 4, 2) {
+            Err(ImageError::Parameter(err)) => {
+                let message = err.to_string();
+                assert!(message.contains("4x2 at (3, 0)"), "{message}");
+                assert!(message.contains("6x4"), "{message}");
+            }
+            _ => panic!("expected a parameter error"),
+        }
+        assert!(try_crop_imm(&image, 7, 5, 1, 1).is_err());
+        assert!(try_crop_imm(&image, 0, 1, 1, u32::MAX).is_err());
+    }
 
     #[test]
     fn test_overlay_bounds_ext() {
@@ -448,6 +810,33 @@ mod tests {
 
     use super::{horizontal_gradient, vertical_gradient};
 
+    #[test]
+    /// Test that pasting with a mask blends by the mask values and clips to the images
+    fn test_paste_with_mask() {
+        use super::paste_with_mask;
+        use crate::{GrayImage, Luma};
+
+        let mut bottom = ImageBuffer::from_pixel(6, 3, Rgb([0u8, 0, 100]));
+        let top = ImageBuffer::from_pixel(4, 4, Rgb([200u8, 100, 100]));
+        // A horizontal gradient, one row shorter than `top`.
+        let mask = GrayImage::from_fn(5, 3, |x, _| Luma([[0, 51, 102, 255, 255][x as usize]]));
+
+        paste_with_mask(&mut bottom, &top, &mask, 1, -1);
+
+        for y in 0..3 {
+            assert_eq!(bottom.get_pixel(0, y), &Rgb([0, 0, 100]));
+            assert_eq!(bottom.get_pixel(5, y), &Rgb([0, 0, 100]));
+        }
+        for y in 0..2 {
+            assert_eq!(bottom.get_pixel(1, y), &Rgb([0, 0, 100]));
+            assert_eq!(bottom.get_pixel(2, y), &Rgb([40, 20, 100]));
+            assert_eq!(bottom.get_pixel(3, y), &Rgb([80, 40, 100]));
+            assert_eq!(bottom.get_pixel(4, y), &Rgb([200, 100, 100]));
+        }
+        // The mask does not cover the last row of `top`.
+        assert!((0..6).all(|x| bottom.get_pixel(x, 2) == &Rgb([0, 0, 100])));
+    }
+
     #[test]
     /// Test that horizontal gradients are correctly generated
     fn test_image_horizontal_gradient_limits() {
@@ -476,6 +865,75 @@ mod tests {
         assert_eq!(img.get_pixel(0, img.height() - 1), &end);
     }
 
+    #[test]
+    /// Test that gradients at an angle start and end at the given colors
+    fn test_linear_gradient_limits() {
+        use super::linear_gradient;
+
+        let start = Rgb([0u8, 128, 0]);
+        let end = Rgb([255u8, 255, 255]);
+
+        let img = linear_gradient(100, 10, start, end, 0.0);
+        for y in 0..img.height() {
+            assert_eq!(img.get_pixel(0, y), &start);
+            assert_eq!(img.get_pixel(img.width() - 1, y), &end);
+        }
+
+        let img = linear_gradient(10, 100, start, end, 90.0);
+        for x in 0..img.width() {
+            assert_eq!(img.get_pixel(x, 0), &start);
+            assert_eq!(img.get_pixel(x, img.height() - 1), &end);
+        }
+
+        let img = linear_gradient(10, 10, start, end, 180.0);
+        assert_eq!(img.get_pixel(0, 5), &end);
+        assert_eq!(img.get_pixel(9, 5), &start);
+
+        let img = linear_gradient(10, 10, start, end, 45.0);
+        assert_eq!(img.get_pixel(0, 0), &start);
+        assert_eq!(img.get_pixel(9, 9), &end);
+        assert_eq!(img.get_pixel(9, 0), img.get_pixel(0, 9));
+    }
+
+    #[test]
+    /// Test that gradients are interpolated in linear light
+    fn test_linear_gradient_linear_light() {
+        use super::linear_gradient;
+        use crate::color::Rgba;
+
+        let img = linear_gradient(
+            3,
+            1,
+            Rgba([0u8, 0, 0, 0]),
+            Rgba([255u8, 255, 255, 255]),
+            0.0,
+        );
+        // Half the intensity of white is encoded as 188 in sRGB, alpha is not transformed.
+        assert_eq!(img.get_pixel(1, 0), &Rgba([188, 188, 188, 128]));
+
+        let img = linear_gradient(3, 1, Rgb([0.0f32, 0.0, 0.0]), Rgb([1.0f32, 1.0, 1.0]), 0.0);
+        assert!((img.get_pixel(1, 0)[0] - 0.7354).abs() < 1e-3);
+    }
+
+    #[test]
+    /// Test that radial gradients start in the center and end at the corners
+    fn test_radial_gradient_limits() {
+        use super::radial_gradient;
+
+        let start = Rgb([0u8, 128, 0]);
+        let end = Rgb([255u8, 255, 255]);
+
+        let img = radial_gradient(11, 7, start, end);
+        assert_eq!(img.get_pixel(5, 3), &start);
+        assert_eq!(img.get_pixel(0, 0), &end);
+        assert_eq!(img.get_pixel(10, 0), &end);
+        assert_eq!(img.get_pixel(0, 6), &end);
+        assert_eq!(img.get_pixel(10, 6), &end);
+
+        let img = radial_gradient(1, 1, start, end);
+        assert_eq!(img.get_pixel(0, 0), &start);
+    }
+
     #[test]
     /// Test blur doesn't panick when passed 0.0
     fn test_blur_zero() {
@@ -0,0 +1,125 @@
+//! Composition of several images into a single one
+
+use crate::color::Rgba;
+use crate::dynimage::DynamicImage;
+use crate::RgbaImage;
+
+/// Arrange images in a grid, such as for a contact sheet.
+///
+/// The images are placed row by row, `cols` per row, in cells as large as the largest image and
+/// separated by `spacing` pixels. Smaller images are centered in their cell. The canvas is
+/// filled with `bg` and the images are drawn over it, blending any transparent pixels. The
+/// result has no margin around the outermost cells, has no more columns than there are images
+/// and is empty if there are no images.
+///
+/// # Panics
+///
+/// Panics if `cols` is zero, or if the montage would be wider or taller than `u32::MAX` pixels.
+pub fn montage(images: &[DynamicImage], cols: u32, spacing: u32, bg: Rgba<u8>) -> DynamicImage {
+    assert!(cols > 0, "a montage needs at least one column");
+    if images.is_empty() {
+        return DynamicImage::ImageRgba8(RgbaImage::new(0, 0));
+    }
+
+    let cell_width = images.iter().map(|i| i.width()).max().unwrap_or(0);
+    let cell_height = images.iter().map(|i| i.height()).max().unwrap_or(0);
+    let count = u32::try_from(images.len()).unwrap_or(u32::MAX);
+    let cols = cols.min(count);
+    let rows = count / cols + u32::from(count % cols != 0);
+
+    let extent = |cells: u32, size: u32| {
+        cells
+            .checked_mul(size)
+            .and_then(|total| total.checked_add((cells - 1).checked_mul(spacing)?))
+            .expect("the montage is too large")
+    };
+    let mut canvas = RgbaImage::from_pixel(extent(cols, cell_width), extent(rows, cell_height), bg);
+
+    // The offset of an image within its cell, from the cell index along an axis. The spacing
+    // after the last cell is not part of the canvas, so the sum of both may not fit in a u32.
+    let offset = |cell: u32, cell_size: u32, size: u32| {
+        i64::from(cell) * (i64::from(cell_size) + i64::from(spacing))
+            + i64::from((cell_size - size) / 2)
+    };
+    for (index, image) in images.iter().enumerate() {
+        let (col, row) = (index as u32 % cols, index as u32 / cols);
+        let x = offset(col, cell_width, image.width());
+        let y = offset(row, cell_height, image.height());
+        super::overlay(&mut canvas, &image.to_rgba8(), x, y);
+    }
+
+    DynamicImage::ImageRgba8(canvas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GenericImageView;
+
+    #[test]
+    fn two_by_two_grid() {
+        let colors = [
+            Rgba([255, 0, 0, 255]),
+            Rgba([0, 255, 0, 255]),
+            Rgba([0, 0, 255, 255]),
+            Rgba([255, 255, 0, 255]),
+        ];
+        let images: Vec<_> = colors
+            .iter()
+            .map(|&c| DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 10, c)))
+            .collect();
+        let bg = Rgba([0, 0, 0, 255]);
+
+        let sheet = montage(&images, 2, 2, bg);
+        assert_eq!(sheet.dimensions(), (22, 22));
+        for (index, &color) in colors.iter().enumerate() {
+            let (x0, y0) = ((index as u32 % 2) * 12, (index as u32 / 2) * 12);
+            for (x, y) in [(0, 0), (9, 0), (0, 9), (9, 9)] {
+                assert_eq!(sheet.get_pixel(x0 + x, y0 + y), color, "cell {index}");
+            }
+        }
+        // The gaps between the cells show the background.
+        assert_eq!(sheet.get_pixel(10, 0), bg);
+        assert_eq!(sheet.get_pixel(11, 21), bg);
+        assert_eq!(sheet.get_pixel(0, 11), bg);
+    }
+
+    #[test]
+    fn smaller_images_are_centered() {
+        let white = Rgba([255; 4]);
+        let images = [
+            DynamicImage::new_rgb8(8, 6),
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 2, white)),
+            DynamicImage::new_luma8(2, 2),
+        ];
+        let bg = Rgba([10, 20, 30, 255]);
+
+        let sheet = montage(&images, 2, 1, bg).into_rgba8();
+        assert_eq!(sheet.dimensions(), (17, 13));
+        // The second image sits in the middle of the cell to the right of the first.
+        assert_eq!(*sheet.get_pixel(11, 2), white);
+        assert_eq!(*sheet.get_pixel(14, 3), white);
+        assert_eq!(*sheet.get_pixel(10, 2), bg);
+        assert_eq!(*sheet.get_pixel(11, 1), bg);
+        // The last row only has a single image, the rest is background.
+        assert_eq!(*sheet.get_pixel(3, 9), Rgba([0, 0, 0, 255]));
+        assert_eq!(*sheet.get_pixel(2, 9), bg);
+        assert_eq!(*sheet.get_pixel(12, 10), bg);
+
+        assert_eq!(montage(&[], 3, 4, bg).dimensions(), (0, 0));
+    }
+
+    #[test]
+    fn spacing_beyond_the_last_cell() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 3, Rgba([9; 4])));
+        let sheet = montage(std::slice::from_ref(&image), 1, u32::MAX, Rgba([0; 4]));
+        assert_eq!(sheet, image);
+    }
+
+    #[test]
+    #[should_panic(expected = "the montage is too large")]
+    fn too_large() {
+        let images = vec![DynamicImage::new_luma8(1, 1); 3];
+        montage(&images, 3, u32::MAX / 2, Rgba([0; 4]));
+    }
+}
@@ -0,0 +1,293 @@
+//! Color quantization, reducing the colors of an image to a small palette.
+//!
+//! Both quantizers produce a [`Palette`], which implements [`ColorMap`] and can therefore be used
+//! with [`index_colors`] and [`dither`] to remap an image onto it.
+//!
+//! [`index_colors`]: super::colorops::index_colors
+//! [`dither`]: super::colorops::dither
+
+use std::collections::HashMap;
+
+use super::colorops::ColorMap;
+use crate::color::Rgba;
+
+/// A list of colors, mapping every color to the nearest entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<Rgba<u8>>,
+}
+
+impl Palette {
+    /// Create a palette from a list of colors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `colors` is empty.
+    pub fn new(colors: Vec<Rgba<u8>>) -> Self {
+        assert!(!colors.is_empty(), "a palette needs at least one color");
+        Palette { colors }
+    }
+
+    /// The colors of the palette.
+    pub fn colors(&self) -> &[Rgba<u8>] {
+        &self.colors
+    }
+}
+
+impl ColorMap for Palette {
+    type Color = Rgba<u8>;
+
+    /// Find the entry with the smallest squared distance to `color` over all four channels.
+    fn index_of(&self, color: &Rgba<u8>) -> usize {
+        let distance = |entry: &Rgba<u8>| -> u32 {
+            entry
+                .0
+                .iter()
+                .zip(color.0)
+                .map(|(&a, b)| u32::from(a.abs_diff(b)).pow(2))
+                .sum()
+        };
+        let mut best = (0, u32::MAX);
+        for (index, entry) in self.colors.iter().enumerate() {
+            let d = distance(entry);
+            if d < best.1 {
+                best = (index, d);
+            }
+        }
+        best.0
+    }
+
+    fn lookup(&self, index: usize) -> Option<Rgba<u8>> {
+        self.colors.get(index).copied()
+    }
+
+    fn has_lookup(&self) -> bool {
+        true
+    }
+
+    fn map_color(&self, color: &mut Rgba<u8>) {
+        *color = self.colors[self.index_of(color)];
+    }
+}
+
+/// Compute a palette of at most `max_colors` colors by median cut.
+///
+/// The distinct colors of `pixels` are repeatedly split at the median of the channel with the
+/// widest range, weighted by how often every color occurs, and each resulting group is
+/// represented by its weighted average. The result is deterministic. Images with no more than
+/// `max_colors` distinct colors are represented exactly.
+///
+/// # Panics
+///
+/// Panics if `pixels` is empty or `max_colors` is zero.
+pub fn median_cut(pixels: &[Rgba<u8>], max_colors: usize) -> Palette {
+    assert!(!pixels.is_empty(), "cannot quantize an empty set of pixels");
+    assert!(max_colors > 0, "a palette needs at least one color");
+
+    let mut counts = HashMap::new();
+    for pixel in pixels {
+        *counts.entry(pixel.0).or_insert(0u64) += 1;
+    }
+    let mut colors: Vec<([u8; 4], u64)> = counts.into_iter().collect();
+    colors.sort_unstable();
+
+    let mut boxes = vec![ColorBox::new(colors)];
+    while boxes.len() < max_colors {
+        // Split the box with the widest channel, preferring earlier boxes on ties.
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.colors.len() > 1)
+            .max_by_key(|(index, b)| (b.widest_channel().1, std::cmp::Reverse(*index)));
+        let Some((index, _)) = widest else {
+            break;
+        };
+        let (low, high) = boxes.swap_remove(index).split();
+        boxes.push(low);
+        boxes.push(high);
+    }
+
+    Palette::new(boxes.iter().map(ColorBox::average).collect())
+}
+
+/// A group of distinct colors with the number of pixels of each.
+struct ColorBox {
+    colors: Vec<([u8; 4], u64)>,
+}
+
+impl ColorBox {
+    fn new(colors: Vec<([u8; 4], u64)>) -> Self {
+        ColorBox { colors }
+    }
+
+    /// The channel with the largest difference between its extreme values, and that difference.
+    fn widest_channel(&self) -> (usize, u8) {
+        (0..4)
+            .map(|channel| {
+                let values = self.colors.iter().map(|(color, _)| color[channel]);
+                let min = values.clone().min().unwrap_or(0);
+                let max = values.max().unwrap_or(0);
+                (channel, max - min)
+            })
+            .max_by_key(|&(channel, range)| (range, std::cmp::Reverse(channel)))
+            .unwrap()
+    }
+
+    /// Split at the weighted median of the widest channel, leaving both halves non-empty.
+    fn split(mut self) -> (ColorBox, ColorBox) {
+        let (channel, _) = self.widest_channel();
+        self.colors
+            .sort_unstable_by_key(|&(color, _)| (color[channel], color));
+
+        let total: u64 = self.colors.iter().map(|(_, count)| count).sum();
+        let mut below = 0;
+        let mut at = self.colors.len() - 1;
+        for (index, (_, count)) in self.colors.iter().enumerate() {
+            below += count;
+            if 2 * below >= total {
+                at = index + 1;
+                break;
+            }
+        }
+        let at = at.clamp(1, self.colors.len() - 1);
+
+        let high = self.colors.split_off(at);
+        (self, ColorBox::new(high))
+    }
+
+    fn average(&self) -> Rgba<u8> {
+        let total: u64 = self.colors.iter().map(|(_, count)| count).sum();
+        let mut sums = [0u64; 4];
+        for (color, count) in &self.colors {
+            for (sum, &value) in sums.iter_mut().zip(color) {
+                *sum += u64::from(value) * count;
+            }
+        }
+        Rgba(sums.map(|sum| ((sum + total / 2) / total) as u8))
+    }
+}
+
+/// Compute a palette of `max_colors` colors with the NeuQuant neural network quantizer.
+///
+/// This is the quantizer of the `color_quant` crate, which is also used for encoding GIF images.
+/// `sample_factor` trades speed for quality: `1` learns from every pixel, while larger values up
+/// to `30` only look at every n-th pixel. Unlike [`median_cut`], the palette always has
+/// `max_colors` entries, some of which may be duplicates.
+///
+/// # Panics
+///
+/// Panics if `pixels` is empty, `max_colors` is not within `1..=256` or `sample_factor` is not
+/// within `1..=30`.
+pub fn neuquant(pixels: &[Rgba<u8>], max_colors: usize, sample_factor: i32) -> Palette {
+    assert!(!pixels.is_empty(), "cannot quantize an empty set of pixels");
+    assert!(
+        (1..=256).contains(&max_colors),
+        "NeuQuant supports between 1 and 256 colors"
+    );
+    assert!(
+        (1..=30).contains(&sample_factor),
+        "the sample factor must be within 1..=30"
+    );
+
+    let bytes: Vec<u8> = pixels.iter().flat_map(|p| p.0).collect();
+    let quantizer = color_quant::NeuQuant::new(sample_factor, max_colors, &bytes);
+    let colors = quantizer
+        .color_map_rgba()
+        .chunks_exact(4)
+        .map(|c| Rgba([c[0], c[1], c[2], c[3]]))
+        .collect();
+    Palette::new(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imageops::colorops::index_colors;
+    use crate::RgbaImage;
+
+    fn gradient() -> RgbaImage {
+        RgbaImage::from_fn(256, 32, |x, y| {
+            Rgba([x as u8, (x / 2 + y * 4) as u8, 255 - x as u8, 255])
+        })
+    }
+
+    /// The largest distance in any channel between a pixel and the palette entry it maps to.
+    fn max_error(image: &RgbaImage, palette: &Palette) -> u8 {
+        image
+            .pixels()
+            .map(|p| {
+                let entry = palette.colors()[palette.index_of(p)];
+                p.0.iter()
+                    .zip(entry.0)
+                    .map(|(&a, b)| a.abs_diff(b))
+                    .max()
+                    .unwrap()
+            })
+            .max()
+            .unwrap()
+    }
+
+    fn distinct(palette: &Palette) -> usize {
+        let mut colors = palette.colors().to_vec();
+        colors.sort_by_key(|c| c.0);
+        colors.dedup();
+        colors.len()
+    }
+
+    #[test]
+    fn median_cut_gradient() {
+        let image = gradient();
+        let pixels: Vec<_> = image.pixels().copied().collect();
+        let palette = median_cut(&pixels, 16);
+
+        assert!(distinct(&palette) <= 16);
+        assert_eq!(palette.colors().len(), 16);
+        assert!(
+            max_error(&image, &palette) <= 40,
+            "{}",
+            max_error(&image, &palette)
+        );
+
+        // Every entry is used by some pixel.
+        let indices = index_colors(&image, &palette);
+        let mut used = [false; 16];
+        for index in indices.pixels() {
+            used[usize::from(index.0[0])] = true;
+        }
+        assert!(used.iter().all(|&u| u));
+
+        assert_eq!(palette, median_cut(&pixels, 16));
+    }
+
+    #[test]
+    fn median_cut_few_colors() {
+        let colors = [
+            Rgba([1, 2, 3, 4]),
+            Rgba([200, 0, 0, 255]),
+            Rgba([0, 0, 0, 0]),
+        ];
+        let pixels: Vec<_> = (0..100).map(|i| colors[i % 3]).collect();
+        let palette = median_cut(&pixels, 16);
+
+        let mut expected = colors.to_vec();
+        expected.sort_by_key(|c| c.0);
+        let mut actual = palette.colors().to_vec();
+        actual.sort_by_key(|c| c.0);
+        assert_eq!(actual, expected);
+        assert_eq!(median_cut(&pixels, 1).colors().len(), 1);
+    }
+
+    #[test]
+    fn neuquant_gradient() {
+        let image = gradient();
+        let pixels: Vec<_> = image.pixels().copied().collect();
+        let palette = neuquant(&pixels, 16, 1);
+
+        assert!(distinct(&palette) <= 16);
+        assert!(
+            max_error(&image, &palette) <= 64,
+            "{}",
+            max_error(&image, &palette)
+        );
+    }
+}
@@ -4,13 +4,15 @@
 // for some of the theory behind image scaling and convolution
 
 use std::f32;
+use std::ops::DerefMut;
 
 use num_traits::{NumCast, ToPrimitive, Zero};
 
+use crate::error::{ImageError, ImageResult, ParameterError, ParameterErrorKind};
 use crate::image::{GenericImage, GenericImageView};
 use crate::traits::{Enlargeable, Pixel, Primitive};
 use crate::utils::clamp;
-use crate::{ImageBuffer, Rgba32FImage};
+use crate::{ImageBuffer, Luma, Rgba, Rgba32FImage};
 
 /// Available Sampling Filters.
 ///
@@ -96,6 +98,73 @@ pub enum FilterType {
     Lanczos3,
 }
 
+/// How resampling filters treat the pixels beyond the edges of an image.
+///
+/// Filter kernels near an edge reach past it. By default only the pixels inside the image are
+/// used, which suits most photographs. Textures that are tiled when rendered should be resized
+/// with [`EdgeHandling::Wrap`] instead, so that the result still tiles without a seam. See
+/// [`EdgeMode`](super::EdgeMode) for the equivalent choice when distorting images.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeHandling {
+    /// Only sample pixels inside the image, the kernel is cut off at the edge and its remaining
+    /// weights are rescaled. This is what [`resize`] does. Unlike
+    /// [`EdgeMode::Clamp`](super::EdgeMode::Clamp), the edge pixels are not repeated.
+    Truncate,
+    /// Mirror the image at its edges, so the pixel next to the edge is its own neighbour.
+    Reflect,
+    /// Continue from the opposite edge, as if the image was tiled.
+    Wrap,
+}
+
+impl EdgeHandling {
+    /// Map a possibly out of bounds index to a pixel in a row or column of length `size`.
+    fn resolve(self, index: i64, size: u32) -> u32 {
+        let size = <i64 as From<_>>::from(size);
+        let index = match self {
+            // The taps are already limited to the image.
+            EdgeHandling::Truncate => index.clamp(0, size - 1),
+            EdgeHandling::Reflect => {
+                let index = index.rem_euclid(2 * size);
+                if index < size {
+                    index
+                } else {
+                    2 * size - 1 - index
+                }
+            }
+            EdgeHandling::Wrap => index.rem_euclid(size),
+        };
+        index as u32
+    }
+}
+
+impl FilterType {
+    /// The kernel of this filter with its support.
+    fn filter(self) -> Filter<'static> {
+        match self {
+            FilterType::Nearest => Filter {
+                kernel: Box::new(box_kernel),
+                support: 0.0,
+            },
+            FilterType::Triangle => Filter {
+                kernel: Box::new(triangle_kernel),
+                support: 1.0,
+            },
+            FilterType::CatmullRom => Filter {
+                kernel: Box::new(catmullrom_kernel),
+                support: 2.0,
+            },
+            FilterType::Gaussian => Filter {
+                kernel: Box::new(gaussian_kernel),
+                support: 3.0,
+            },
+            FilterType::Lanczos3 => Filter {
+                kernel: Box::new(lanczos3_kernel),
+                support: 3.0,
+            },
+        }
+    }
+}
+
 /// A Representation of a separable filter.
 pub(crate) struct Filter<'a> {
     /// The filter's filter function.
@@ -105,7 +174,7 @@ pub(crate) struct Filter<'a> {
     pub(crate) support: f32,
 }
 
-struct FloatNearest(f32);
+pub(crate) struct FloatNearest(pub(crate) f32);
 
 // to_i64, to_u64, and to_f64 implicitly affect all other lower conversions.
 // Note that to_f64 by default calls to_i64 and thus needs to be overridden.
@@ -216,6 +285,70 @@ pub(crate) fn box_kernel(_x: f32) -> f32 {
     1.0
 }
 
+// Collect the input pixels contributing to one output pixel with their normalized weights.
+// ```input``` is the position of the centre of the output pixel in the input row or column of
+// ```size``` pixels, and ```sratio``` is the factor by which the kernel is stretched.
+fn filter_taps(
+    filter: &mut Filter,
+    edge: EdgeHandling,
+    input: f32,
+    sratio: f32,
+    size: u32,
+    taps: &mut Vec<(u32, f32)>,
+) {
+    let src_support = filter.support * sratio;
+
+    // Left and right are the bounds for the input pixels relevant
+    // to the output pixel we are calculating.  Pixel x is relevant
+    // if and only if (x >= left) && (x < right).
+    let left = (input - src_support).floor() as i64;
+    let right = (input + src_support).ceil() as i64;
+    let (left, right) = match edge {
+        // Invariant: 0 <= left < right <= size
+        EdgeHandling::Truncate => {
+            let left = clamp(left, 0, <i64 as From<_>>::from(size) - 1);
+            (left, clamp(right, left + 1, <i64 as From<_>>::from(size)))
+        }
+        // Pixels outside of the image are mapped back into it.
+        EdgeHandling::Reflect | EdgeHandling::Wrap => (left, right.max(left + 1)),
+    };
+
+    // Go back to left boundary of pixel, to properly compare with i
+    // below, as the kernel treats the centre of a pixel as 0.
+    let input = input - 0.5;
+
+    taps.clear();
+    let mut sum = 0.0;
+    for i in left..right {
+        let w = (filter.kernel)((i as f32 - input) / sratio);
+        taps.push((edge.resolve(i, size), w));
+        sum += w;
+    }
+    taps.iter_mut().for_each(|(_, w)| *w /= sum);
+}
+
+/// Collect the taps of every output pixel along an axis resized from `size` to `new_size` pixels.
+fn axis_taps(
+    filter: &mut Filter,
+    edge: EdgeHandling,
+    size: u32,
+    new_size: u32,
+) -> Vec<Vec<(u32, f32)>> {
+    let ratio = size as f32 / new_size as f32;
+    let sratio = if ratio < 1.0 { 1.0 } else { ratio };
+
+    (0..new_size)
+        .map(|out| {
+            // Find the point in the input image corresponding to the centre
+            // of the current pixel in the output image.
+            let input = (out as f32 + 0.5) * ratio;
+            let mut taps = Vec::new();
+            filter_taps(filter, edge, input, sratio, size, &mut taps);
+            taps
+        })
+        .collect()
+}
+
 // Sample the rows of the supplied image using the provided filter.
 // The height of the image remains unchanged.
 // ```new_width``` is the desired width of the new image
@@ -225,68 +358,63 @@ fn horizontal_sample<P, S>(
     image: &Rgba32FImage,
     new_width: u32,
     filter: &mut Filter,
+    edge: EdgeHandling,
 ) -> ImageBuffer<P, Vec<S>>
 where
     P: Pixel<Subpixel = S> + 'static,
     S: Primitive + 'static,
 {
-    let (width, height) = image.dimensions();
-    let mut out = ImageBuffer::new(new_width, height);
-    let mut ws = Vec::new();
+    let mut out = ImageBuffer::new(new_width, image.height());
+    horizontal_sample_in(image, &mut out, filter, edge);
+    out
+}
 
-    let max: f32 = NumCast::from(S::DEFAULT_MAX_VALUE).unwrap();
-    let min: f32 = NumCast::from(S::DEFAULT_MIN_VALUE).unwrap();
-    let ratio = width as f32 / new_width as f32;
-    let sratio = if ratio < 1.0 { 1.0 } else { ratio };
-    let src_support = filter.support * sratio;
+/// Resample the rows of `image` into `out`, which must have the same height.
+fn horizontal_sample_in<P, S, Container>(
+    image: &Rgba32FImage,
+    out: &mut ImageBuffer<P, Container>,
+    filter: &mut Filter,
+    edge: EdgeHandling,
+) where
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+    Container: DerefMut<Target = [S]>,
+{
+    let taps = axis_taps(filter, edge, image.width(), out.width());
+    horizontal_sample_taps(image, out, &taps);
+}
 
-    for outx in 0..new_width {
-        // Find the point in the input image corresponding to the centre
-        // of the current pixel in the output image.
-        let inputx = (outx as f32 + 0.5) * ratio;
-
-        // Left and right are slice bounds for the input pixels relevant
-        // to the output pixel we are calculating.  Pixel x is relevant
-        // if and only if (x >= left) && (x < right).
-
-        // Invariant: 0 <= left < right <= width
-
-        let left = (inputx - src_support).floor() as i64;
-        let left = clamp(left, 0, <i64 as From<_>>::from(width) - 1) as u32;
-
-        let right = (inputx + src_support).ceil() as i64;
-        let right = clamp(
-            right,
-            <i64 as From<_>>::from(left) + 1,
-            <i64 as From<_>>::from(width),
-        ) as u32;
-
-        // Go back to left boundary of pixel, to properly compare with i
-        // below, as the kernel treats the centre of a pixel as 0.
-        let inputx = inputx - 0.5;
-
-        ws.clear();
-        let mut sum = 0.0;
-        for i in left..right {
-            let w = (filter.kernel)((i as f32 - inputx) / sratio);
-            ws.push(w);
-            sum += w;
-        }
-        ws.iter_mut().for_each(|w| *w /= sum);
+/// Resample the rows of `image` into `out` with the taps of every output column.
+fn horizontal_sample_taps<P, S, Container>(
+    image: &Rgba32FImage,
+    out: &mut ImageBuffer<P, Container>,
+    taps: &[Vec<(u32, f32)>],
+) where
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+    Container: DerefMut<Target = [S]>,
+{
+    let height = image.height();
 
-        for y in 0..height {
-            let mut t = (0.0, 0.0, 0.0, 0.0);
+    // Clamp to the representable range only, floating point channels may exceed `1.0` in HDR
+    // images.
+    let max: f32 = NumCast::from(S::max_value()).unwrap_or(f32::MAX);
+    let min: f32 = NumCast::from(S::min_value()).unwrap_or(f32::MIN);
 
-            for (i, w) in ws.iter().enumerate() {
-                let p = image.get_pixel(left + i as u32, y);
+    // Row by row, such that both images are read and written in memory order.
+    let row_len = image.width() as usize * 4;
+    for (y, row) in (0..height).zip(image.chunks_exact(row_len.max(1))) {
+        for (outx, taps) in (0..out.width()).zip(taps) {
+            let mut t = (0.0, 0.0, 0.0, 0.0);
 
-                #[allow(deprecated)]
-                let vec = p.channels4();
+            for &(i, w) in taps {
+                let i = i as usize * 4;
+                let p = &row[i..i + 4];
 
-                t.0 += vec.0 * w;
-                t.1 += vec.1 * w;
-                t.2 += vec.2 * w;
-                t.3 += vec.3 * w;
+                t.0 += p[0] * w;
+                t.1 += p[1] * w;
+                t.2 += p[2] * w;
+                t.3 += p[3] * w;
             }
 
             #[allow(deprecated)]
@@ -300,8 +428,6 @@ where
             out.put_pixel(outx, y, t);
         }
     }
-
-    out
 }
 
 /// Linearly sample from an image using coordinates in [0, 1].
@@ -463,72 +589,67 @@ pub fn interpolate_bilinear<P: Pixel>(
 // ```filter``` is the filter to use for sampling.
 // The return value is not necessarily Rgba, the underlying order of channels in ```image``` is
 // preserved.
-fn vertical_sample<I, P, S>(image: &I, new_height: u32, filter: &mut Filter) -> Rgba32FImage
+fn vertical_sample<I, P, S>(
+    image: &I,
+    new_height: u32,
+    filter: &mut Filter,
+    edge: EdgeHandling,
+) -> Rgba32FImage
 where
     I: GenericImageView<Pixel = P>,
     P: Pixel<Subpixel = S> + 'static,
     S: Primitive + 'static,
 {
-    let (width, height) = image.dimensions();
-    let mut out = ImageBuffer::new(width, new_height);
-    let mut ws = Vec::new();
-
-    let ratio = height as f32 / new_height as f32;
-    let sratio = if ratio < 1.0 { 1.0 } else { ratio };
-    let src_support = filter.support * sratio;
-
-    for outy in 0..new_height {
-        // For an explanation of this algorithm, see the comments
-        // in horizontal_sample.
-        let inputy = (outy as f32 + 0.5) * ratio;
-
-        let left = (inputy - src_support).floor() as i64;
-        let left = clamp(left, 0, <i64 as From<_>>::from(height) - 1) as u32;
-
-        let right = (inputy + src_support).ceil() as i64;
-        let right = clamp(
-            right,
-            <i64 as From<_>>::from(left) + 1,
-            <i64 as From<_>>::from(height),
-        ) as u32;
-
-        let inputy = inputy - 0.5;
-
-        ws.clear();
-        let mut sum = 0.0;
-        for i in left..right {
-            let w = (filter.kernel)((i as f32 - inputy) / sratio);
-            ws.push(w);
-            sum += w;
-        }
-        ws.iter_mut().for_each(|w| *w /= sum);
-
-        for x in 0..width {
-            let mut t = (0.0, 0.0, 0.0, 0.0);
-
-            for (i, w) in ws.iter().enumerate() {
-                let p = image.get_pixel(x, left + i as u32);
+    vertical_sample_taps(image, &axis_taps(filter, edge, image.height(), new_height))
+}
 
-                #[allow(deprecated)]
-                let (k1, k2, k3, k4) = p.channels4();
-                let vec: (f32, f32, f32, f32) = (
-                    NumCast::from(k1).unwrap(),
-                    NumCast::from(k2).unwrap(),
-                    NumCast::from(k3).unwrap(),
-                    NumCast::from(k4).unwrap(),
-                );
+/// Resample the columns of `image` with the taps of every output row.
+fn vertical_sample_taps<I, P, S>(image: &I, taps: &[Vec<(u32, f32)>]) -> Rgba32FImage
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let width = image.width();
+    let mut out: Rgba32FImage = ImageBuffer::new(width, taps.len() as u32);
+    let row_len = width as usize * 4;
+
+    // Consecutive output rows mostly share their input rows, so the most recently converted ones
+    // are kept, as many as a single output row uses. They are replaced oldest first.
+    let capacity = taps.iter().map(Vec::len).max().unwrap_or(0);
+    let mut rows: Vec<(u32, Vec<f32>)> = Vec::with_capacity(capacity);
+    let mut oldest = 0;
+
+    for (sum, taps) in out.chunks_exact_mut(row_len.max(1)).zip(taps) {
+        // All pixels of the row share the taps, so whole rows are accumulated at once. The
+        // channels are not necessarily Rgba.
+        for &(i, w) in taps {
+            let slot = match rows.iter().position(|&(y, _)| y == i) {
+                Some(slot) => slot,
+                None => {
+                    let slot = if rows.len() < capacity {
+                        rows.push((i, vec![0.0; row_len]));
+                        rows.len() - 1
+                    } else {
+                        oldest = (oldest + 1) % capacity;
+                        (oldest + capacity - 1) % capacity
+                    };
+                    let (y, row) = &mut rows[slot];
+                    *y = i;
+                    for (x, value) in (0..width).zip(row.chunks_exact_mut(4)) {
+                        #[allow(deprecated)]
+                        let (k1, k2, k3, k4) = image.get_pixel(x, i).channels4();
+                        for (v, k) in value.iter_mut().zip([k1, k2, k3, k4]) {
+                            *v = NumCast::from(k).unwrap();
+                        }
+                    }
+                    slot
+                }
+            };
 
-                t.0 += vec.0 * w;
-                t.1 += vec.1 * w;
-                t.2 += vec.2 * w;
-                t.3 += vec.3 * w;
+            for (s, &v) in sum.iter_mut().zip(&rows[slot].1) {
+                *s += v * w;
             }
-
-            #[allow(deprecated)]
-            // This is not necessarily Rgba.
-            let t = Pixel::from_channels(t.0, t.1, t.2, t.3);
-
-            out.put_pixel(x, outy, t);
         }
     }
 
@@ -906,6 +1027,12 @@ where
 /// Resize the supplied image to the specified dimensions.
 /// ```nwidth``` and ```nheight``` are the new dimensions.
 /// ```filter``` is the sampling filter to use.
+///
+/// Floating point images are resampled in full precision and are not clamped to `0.0..=1.0`,
+/// so the range of HDR images is preserved.
+///
+/// An empty image has nothing to sample from, so resizing one to a non-empty size returns an
+/// image of the requested size with all samples zero.
 pub fn resize<I: GenericImageView>(
     image: &I,
     nwidth: u32,
@@ -916,39 +1043,452 @@ where
     I::Pixel: 'static,
     <I::Pixel as Pixel>::Subpixel: 'static,
 {
+    resample(
+        image,
+        nwidth,
+        nheight,
+        (filter, filter),
+        EdgeHandling::Truncate,
+    )
+}
+
+/// Resize the supplied image to the dimensions of the destination [`ImageBuffer`].
+/// ```filter``` is the sampling filter to use.
+///
+/// The result is identical to that of [`resize`], but is written into existing memory. This
+/// avoids allocating a new buffer for every output image when resizing many images to the same
+/// size.
+///
+/// Returns an error if exactly one of the images is empty, as there is nothing to sample from.
+pub fn resize_into<I, Container>(
+    image: &I,
+    destination: &mut ImageBuffer<I::Pixel, Container>,
+    filter: FilterType,
+) -> ImageResult<()>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+    Container: DerefMut<Target = [<I::Pixel as Pixel>::Subpixel]>,
+{
+    resample_into(image, destination, (filter, filter), EdgeHandling::Truncate)
+}
+
+/// Resize the supplied image to the specified dimensions, treating its edges according to `edge`.
+///
+/// This is [`resize`] with a choice of what the filter samples beyond the edges of the image.
+/// Resizing a tileable texture with [`EdgeHandling::Wrap`] keeps it tileable, whereas cutting
+/// the filter off at the edges would leave a visible seam where copies of it meet.
+///
+/// As with [`resize`], an empty image is resized to one with all samples zero.
+pub fn resize_with_edges<I: GenericImageView>(
+    image: &I,
+    nwidth: u32,
+    nheight: u32,
+    filter: FilterType,
+    edge: EdgeHandling,
+) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+{
+    resample(image, nwidth, nheight, (filter, filter), edge)
+}
+
+/// Resize the supplied image to the specified dimensions, with a different filter per axis.
+///
+/// The rows are resampled with `filter_x` and the columns with `filter_y`. Content with a
+/// direction, such as text or scan lines, may look best when kept sharp along one axis and
+/// smoothed along the other. With the same filter for both axes this is [`resize`].
+///
+/// As with [`resize`], an empty image is resized to one with all samples zero.
+pub fn resize_xy<I: GenericImageView>(
+    image: &I,
+    nwidth: u32,
+    nheight: u32,
+    filter_x: FilterType,
+    filter_y: FilterType,
+) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+{
+    resample(
+        image,
+        nwidth,
+        nheight,
+        (filter_x, filter_y),
+        EdgeHandling::Truncate,
+    )
+}
+
+/// Resize the supplied image to the specified dimensions, keeping pixels of the color `key` out
+/// of the interpolation.
+///
+/// Sprites and indexed images often mark transparent pixels with a key color such as magenta
+/// instead of an alpha channel. Resizing them as usual mixes the key into the colors along the
+/// edges of the sprite. Here the key pixels are instead treated as fully transparent, much like
+/// resizing with premultiplied alpha: only the other pixels contribute to the colors. An output
+/// pixel is set to `key` if less than half of its weight comes from non-key pixels.
+pub fn resize_with_color_key<I, P, S>(
+    image: &I,
+    nwidth: u32,
+    nheight: u32,
+    filter: FilterType,
+    key: P,
+) -> ImageBuffer<P, Vec<S>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + PartialEq + 'static,
+    S: Primitive + 'static,
+{
+    let (width, height) = image.dimensions();
+    let mut colors = Rgba32FImage::new(width, height);
+    let mut coverage: ImageBuffer<Luma<f32>, Vec<f32>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in image.pixels() {
+        if pixel != key {
+            #[allow(deprecated)]
+            let (k1, k2, k3, k4) = pixel.channels4();
+            let channels: [f32; 4] = [k1, k2, k3, k4].map(|k| NumCast::from(k).unwrap());
+            colors.put_pixel(x, y, Rgba(channels));
+            coverage.put_pixel(x, y, Luma([1.0]));
+        }
+    }
+
+    let colors = resize(&colors, nwidth, nheight, filter);
+    let coverage = resize(&coverage, nwidth, nheight, filter);
+
+    let max: f32 = NumCast::from(S::max_value()).unwrap_or(f32::MAX);
+    let min: f32 = NumCast::from(S::min_value()).unwrap_or(f32::MIN);
+    ImageBuffer::from_fn(nwidth, nheight, |x, y| {
+        let Luma([weight]) = *coverage.get_pixel(x, y);
+        if weight < 0.5 {
+            return key;
+        }
+        let [k1, k2, k3, k4] = colors
+            .get_pixel(x, y)
+            .0
+            .map(|c| NumCast::from(FloatNearest(clamp(c / weight, min, max))).unwrap());
+        #[allow(deprecated)]
+        Pixel::from_channels(k1, k2, k3, k4)
+    })
+}
+
+/// Filter weights precomputed for resizing many images of one size to another.
+///
+/// [`resize`] works out which input pixels contribute to every output pixel, and by how much,
+/// for every image anew. When many images of the same dimensions are resized to the same target,
+/// such as when generating thumbnails of video frames or tiles, a plan computes these tables once
+/// and reuses them. The result is the same as that of [`resize`].
+#[derive(Clone, Debug)]
+pub struct ResizePlan {
+    source: (u32, u32),
+    target: (u32, u32),
+    horizontal: Vec<Vec<(u32, f32)>>,
+    vertical: Vec<Vec<(u32, f32)>>,
+}
+
+impl ResizePlan {
+    /// Plan the resizing of images with the dimensions `source` to `target` with `filter`.
+    pub fn new(source: (u32, u32), target: (u32, u32), filter: FilterType) -> Self {
+        let empty = |(width, height)| width == 0 || height == 0;
+        let (horizontal, vertical) = if empty(source) || empty(target) {
+            (Vec::new(), Vec::new())
+        } else {
+            let mut filter = filter.filter();
+            let edge = EdgeHandling::Truncate;
+            (
+                axis_taps(&mut filter, edge, source.0, target.0),
+                axis_taps(&mut filter, edge, source.1, target.1),
+            )
+        };
+        ResizePlan {
+            source,
+            target,
+            horizontal,
+            vertical,
+        }
+    }
+
+    /// Resize `image`, which must have the source dimensions of the plan.
+    ///
+    /// Returns an error if the dimensions of the image differ from those the plan was made for,
+    /// or if only one of the source and target dimensions is empty.
+    pub fn resize<I, P, S>(&self, image: &I) -> ImageResult<ImageBuffer<P, Vec<S>>>
+    where
+        I: GenericImageView<Pixel = P>,
+        P: Pixel<Subpixel = S> + 'static,
+        S: Primitive + 'static,
+    {
+        let empty = |(width, height)| width == 0 || height == 0;
+        if image.dimensions() != self.source || empty(self.source) != empty(self.target) {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            )));
+        }
+
+        let mut out = ImageBuffer::new(self.target.0, self.target.1);
+        if self.source == self.target {
+            out.copy_from(image, 0, 0)?;
+        } else if !empty(self.source) {
+            let tmp = vertical_sample_taps(image, &self.vertical);
+            horizontal_sample_taps(&tmp, &mut out, &self.horizontal);
+        }
+        Ok(out)
+    }
+}
+
+fn resample_into<I, Container>(
+    image: &I,
+    destination: &mut ImageBuffer<I::Pixel, Container>,
+    filters: (FilterType, FilterType),
+    edge: EdgeHandling,
+) -> ImageResult<()>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+    Container: DerefMut<Target = [<I::Pixel as Pixel>::Subpixel]>,
+{
+    let (width, height) = image.dimensions();
+    let (nwidth, nheight) = destination.dimensions();
+    if (width == 0 || height == 0) != (nwidth == 0 || nheight == 0) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::DimensionMismatch,
+        )));
+    }
+    resample_in(image, destination, filters, edge);
+    Ok(())
+}
+
+/// Resample `image` into a new buffer of the given size, which stays zeroed if `image` is empty.
+fn resample<I>(
+    image: &I,
+    nwidth: u32,
+    nheight: u32,
+    filters: (FilterType, FilterType),
+    edge: EdgeHandling,
+) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+{
+    let mut out = ImageBuffer::new(nwidth, nheight);
+    resample_in(image, &mut out, filters, edge);
+    out
+}
+
+/// Resample `image` into `destination`. If either of them is empty there is nothing to do, and
+/// `destination` is left as it is.
+fn resample_in<I, Container>(
+    image: &I,
+    destination: &mut ImageBuffer<I::Pixel, Container>,
+    (filter_x, filter_y): (FilterType, FilterType),
+    edge: EdgeHandling,
+) where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+    Container: DerefMut<Target = [<I::Pixel as Pixel>::Subpixel]>,
+{
+    let (width, height) = image.dimensions();
+    let (nwidth, nheight) = destination.dimensions();
+    if width == 0 || height == 0 || nwidth == 0 || nheight == 0 {
+        return;
+    }
+
     // check if the new dimensions are the same as the old. if they are, make a copy instead of resampling
-    if (nwidth, nheight) == image.dimensions() {
-        let mut tmp = ImageBuffer::new(image.width(), image.height());
-        tmp.copy_from(image, 0, 0).unwrap();
-        return tmp;
-    }
-
-    let mut method = match filter {
-        FilterType::Nearest => Filter {
-            kernel: Box::new(box_kernel),
-            support: 0.0,
-        },
-        FilterType::Triangle => Filter {
-            kernel: Box::new(triangle_kernel),
-            support: 1.0,
-        },
-        FilterType::CatmullRom => Filter {
-            kernel: Box::new(catmullrom_kernel),
-            support: 2.0,
-        },
-        FilterType::Gaussian => Filter {
-            kernel: Box::new(gaussian_kernel),
-            support: 3.0,
-        },
-        FilterType::Lanczos3 => Filter {
-            kernel: Box::new(lanczos3_kernel),
-            support: 3.0,
-        },
-    };
+    if (nwidth, nheight) == (width, height) {
+        for (x, y, pixel) in image.pixels() {
+            destination.put_pixel(x, y, pixel);
+        }
+        return;
+    }
 
     // Note: tmp is not necessarily actually Rgba
-    let tmp: Rgba32FImage = vertical_sample(image, nheight, &mut method);
-    horizontal_sample(&tmp, nwidth, &mut method)
+    let tmp: Rgba32FImage = vertical_sample(image, nheight, &mut filter_y.filter(), edge);
+    horizontal_sample_in(&tmp, destination, &mut filter_x.filter(), edge);
+}
+
+/// Resize the supplied image to the specified dimensions, suppressing aliasing when it is
+/// reduced by a large factor.
+///
+/// A single resampling filter only looks at a few pixels around each sample, so fine detail such
+/// as thin stripes turns into moiré patterns when an image is shrunk by more than about four
+/// times. Here every reduced axis is first smoothed with a Gaussian whose deviation is half the
+/// reduction factor, much like the levels of a mipmap, and then resampled with
+/// [`FilterType::Triangle`]. Detail too fine for the new size averages out to its mean colour.
+/// Axes that are enlarged are only resampled. This is slower than [`resize`], as the smoothing
+/// runs at the size of the input.
+pub fn resize_antialiased<I: GenericImageView>(
+    image: &I,
+    nwidth: u32,
+    nheight: u32,
+) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+{
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(nwidth, nheight);
+    if width == 0 || height == 0 || nwidth == 0 || nheight == 0 {
+        return out;
+    }
+
+    // Note: the intermediate images are not necessarily Rgba.
+    let mut vertical = prefilter(height, nheight).unwrap_or_else(identity_filter);
+    let mut tmp: Rgba32FImage =
+        vertical_sample(image, height, &mut vertical, EdgeHandling::Truncate);
+    if let Some(mut filter) = prefilter(width, nwidth) {
+        tmp = horizontal_sample(&tmp, width, &mut filter, EdgeHandling::Truncate);
+    }
+
+    let mut method = Filter {
+        kernel: Box::new(triangle_kernel),
+        support: 1.0,
+    };
+    let tmp: Rgba32FImage = vertical_sample(&tmp, nheight, &mut method, EdgeHandling::Truncate);
+    horizontal_sample_in(&tmp, &mut out, &mut method, EdgeHandling::Truncate);
+    out
+}
+
+// The Gaussian smoothing an axis of ```size``` pixels before it is reduced to ```new_size```, or
+// ```None``` if the axis is not reduced.
+fn prefilter(size: u32, new_size: u32) -> Option<Filter<'static>> {
+    let sigma = 0.5 * size as f32 / new_size as f32;
+    (size > new_size).then(|| Filter {
+        kernel: Box::new(move |x| gaussian(x, sigma)),
+        support: 3.0 * sigma,
+    })
+}
+
+// A filter that copies each pixel when sampling at the same size.
+fn identity_filter() -> Filter<'static> {
+    Filter {
+        kernel: Box::new(box_kernel),
+        support: 0.0,
+    }
+}
+
+/// Resize the supplied image to the specified dimensions using only integer arithmetic.
+///
+/// The output of [`resize`] depends on how floating point operations are rounded, which may
+/// differ slightly between platforms and compilers. Here the filter weights are exact fractions
+/// and the pixels are summed in fixed point, so the output is the same on every target. This
+/// suits golden tests and content hashes. Only [`FilterType::Nearest`] and
+/// [`FilterType::Triangle`] are supported, and the result matches [`resize`] with the same
+/// filter up to rounding.
+///
+/// Returns an error for the other filters, or if exactly one of the images is empty.
+pub fn resize_integer<I, P, S>(
+    image: &I,
+    nwidth: u32,
+    nheight: u32,
+    filter: FilterType,
+) -> ImageResult<ImageBuffer<P, Vec<S>>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + Into<u32> + 'static,
+{
+    if !matches!(filter, FilterType::Nearest | FilterType::Triangle) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::Generic(format!(
+                "integer resizing does not support the {filter:?} filter"
+            )),
+        )));
+    }
+    let (width, height) = image.dimensions();
+    if (width == 0 || height == 0) != (nwidth == 0 || nheight == 0) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::DimensionMismatch,
+        )));
+    }
+
+    let mut out = ImageBuffer::new(nwidth, nheight);
+    if nwidth == 0 || nheight == 0 {
+        return Ok(out);
+    }
+
+    // The columns are resampled first, keeping the fractional bits of the sums. The weights of
+    // each pass add up to one in fixed point, so even for 32-bit samples the sums stay below
+    // 2^(32 + 14) after the first pass and 2^(32 + 28) after the second.
+    let channels = P::CHANNEL_COUNT as usize;
+    let mut tmp = vec![0u64; width as usize * nheight as usize * channels];
+    let rows = tmp.chunks_exact_mut(width as usize * channels);
+    for (taps, row) in fixed_point_taps(filter, height, nheight).iter().zip(rows) {
+        for (x, sums) in (0..width).zip(row.chunks_exact_mut(channels)) {
+            for &(y, weight) in taps {
+                let pixel = image.get_pixel(x, y);
+                for (sum, &c) in sums.iter_mut().zip(pixel.channels()) {
+                    let c: u32 = c.into();
+                    *sum += c as u64 * weight as u64;
+                }
+            }
+        }
+    }
+
+    let rounding = 1u64 << (2 * FIXED_POINT_BITS - 1);
+    let columns = fixed_point_taps(filter, width, nwidth);
+    for (outy, row) in (0..nheight).zip(tmp.chunks_exact(width as usize * channels)) {
+        for (outx, taps) in (0..nwidth).zip(&columns) {
+            let pixel = out.get_pixel_mut(outx, outy);
+            for (i, c) in pixel.channels_mut().iter_mut().enumerate() {
+                let sum: u64 = taps
+                    .iter()
+                    .map(|&(x, weight)| row[x as usize * channels + i] * weight as u64)
+                    .sum();
+                *c = NumCast::from((sum + rounding) >> (2 * FIXED_POINT_BITS)).unwrap();
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// The number of fractional bits of the filter weights in [`resize_integer`].
+const FIXED_POINT_BITS: u32 = 14;
+
+// Collect the input pixels contributing to each output pixel along an axis of ```size``` pixels
+// resampled to ```new_size```, with weights that sum to exactly one in fixed point.
+fn fixed_point_taps(filter: FilterType, size: u32, new_size: u32) -> Vec<Vec<(u32, u32)>> {
+    let (size, new_size) = (size as i64, new_size as i64);
+    // Positions are measured in units of 1 / (2 * new_size) input pixels, so that the centres of
+    // both the input and the output pixels fall on integers.
+    let support = 2 * size.max(new_size);
+    (0..new_size)
+        .map(|out| {
+            let center = (2 * out + 1) * size;
+            if filter == FilterType::Nearest {
+                let nearest = (center / (2 * new_size)).min(size - 1);
+                return vec![(nearest as u32, 1 << FIXED_POINT_BITS)];
+            }
+
+            // The triangle filter falls off linearly towards its support, which is one input
+            // pixel when enlarging and stretched by the ratio of the sizes when reducing.
+            let first = ((center - support) / (2 * new_size)).max(0);
+            let last = ((center + support) / (2 * new_size)).min(size - 1);
+            let weights: Vec<(u32, i64)> = (first..=last)
+                .filter_map(|i| {
+                    let distance = (center - (2 * i + 1) * new_size).abs();
+                    (distance < support).then(|| (i as u32, support - distance))
+                })
+                .collect();
+
+            // Round the weights down and add what is missing to the largest one.
+            let total: i64 = weights.iter().map(|&(_, w)| w).sum();
+            let mut taps: Vec<(u32, u32)> = weights
+                .iter()
+                .map(|&(i, w)| (i, ((w << FIXED_POINT_BITS) / total) as u32))
+                .collect();
+            let missing = (1 << FIXED_POINT_BITS) - taps.iter().map(|&(_, w)| w).sum::<u32>();
+            if let Some(largest) = taps.iter_mut().max_by_key(|&&mut (_, w)| w) {
+                largest.1 += missing;
+            }
+            taps
+        })
+        .collect()
 }
 
 /// Performs a Gaussian blur on the supplied image.
@@ -972,8 +1512,8 @@ where
     // Keep width and height the same for horizontal and
     // vertical sampling.
     // Note: tmp is not necessarily actually Rgba
-    let tmp: Rgba32FImage = vertical_sample(image, height, &mut method);
-    horizontal_sample(&tmp, width, &mut method)
+    let tmp: Rgba32FImage = vertical_sample(image, height, &mut method, EdgeHandling::Truncate);
+    horizontal_sample(&tmp, width, &mut method, EdgeHandling::Truncate)
 }
 
 /// Performs an unsharpen mask on the supplied image.
@@ -1020,10 +1560,56 @@ where
     tmp
 }
 
+/// Sharpens an image by unsharp masking with an adjustable strength.
+///
+/// Every channel is computed as `value + amount * (value - blurred)`, where `blurred` is the
+/// result of a Gaussian [`blur`] with the given `sigma`, and clamped to the range of the channel.
+/// Channels that differ from their blurred value by no more than `threshold` are left unchanged,
+/// so that flat areas and faint noise are not amplified. The arithmetic is done in `f32`.
+///
+/// An `amount` of `1.0` matches [`unsharpen`].
+pub fn unsharp_mask<I, P, S>(
+    image: &I,
+    sigma: f32,
+    amount: f32,
+    threshold: i32,
+) -> ImageBuffer<P, Vec<S>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let mut out = blur(image, sigma);
+
+    let max: f32 = NumCast::from(S::DEFAULT_MAX_VALUE).unwrap();
+    let min: f32 = NumCast::from(S::DEFAULT_MIN_VALUE).unwrap();
+    let threshold = threshold as f32;
+
+    for (x, y, blurred) in out.enumerate_pixels_mut() {
+        let original = image.get_pixel(x, y);
+        *blurred = original.map2(blurred, |c, d| {
+            let value: f32 = NumCast::from(c).unwrap();
+            let diff = value - d.to_f32().unwrap();
+            if diff.abs() > threshold {
+                let sharpened = clamp(value + amount * diff, min, max);
+                NumCast::from(FloatNearest(sharpened)).unwrap()
+            } else {
+                c
+            }
+        });
+    }
+
+    out
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{resize, sample_bilinear, sample_nearest, FilterType};
-    use crate::{GenericImageView, ImageBuffer, RgbImage};
+    use super::{
+        resize, resize_antialiased, resize_integer, resize_into, resize_with_color_key,
+        resize_with_edges, resize_xy, sample_bilinear, sample_nearest, unsharp_mask, EdgeHandling,
+        FilterType, ResizePlan,
+    };
+    use crate::{GenericImageView, GrayImage, ImageBuffer, Luma, RgbImage};
     #[cfg(feature = "benchmarks")]
     use test;
 
@@ -1047,6 +1633,332 @@ mod tests {
         assert!(img.pixels().eq(resize.pixels()))
     }
 
+    #[test]
+    fn resize_with_color_key_does_not_bleed() {
+        let key = crate::Rgb([255, 0, 255]);
+        let green = crate::Rgb([0, 200, 0]);
+        let sprite = RgbImage::from_fn(16, 16, |x, y| {
+            if (4..12).contains(&x) && (4..12).contains(&y) {
+                green
+            } else {
+                key
+            }
+        });
+
+        for filter in [
+            FilterType::Triangle,
+            FilterType::CatmullRom,
+            FilterType::Lanczos3,
+        ] {
+            let resized = resize_with_color_key(&sprite, 6, 6, filter, key);
+            assert!(
+                resized.pixels().all(|&p| p == key || p == green),
+                "{filter:?}"
+            );
+            assert_eq!(*resized.get_pixel(3, 3), green);
+            assert_eq!(*resized.get_pixel(0, 0), key);
+        }
+
+        // Without the key, the border of the sprite is tinted.
+        let blended = resize(&sprite, 6, 6, FilterType::Triangle);
+        assert!(blended.pixels().any(|&p| p != key && p != green));
+    }
+
+    #[test]
+    fn resize_plan_matches_resize() {
+        let images: Vec<RgbImage> = (0..3)
+            .map(|seed| {
+                RgbImage::from_fn(23, 17, |x, y| {
+                    crate::Rgb([
+                        (x * 11 + seed) as u8,
+                        (y * 15) as u8,
+                        ((x ^ y) * seed) as u8,
+                    ])
+                })
+            })
+            .collect();
+        let filters = [
+            FilterType::Nearest,
+            FilterType::Triangle,
+            FilterType::CatmullRom,
+            FilterType::Gaussian,
+            FilterType::Lanczos3,
+        ];
+        for filter in filters {
+            for target in [(9, 30), (23, 17), (46, 5)] {
+                let plan = ResizePlan::new((23, 17), target, filter);
+                for image in &images {
+                    assert_eq!(
+                        plan.resize(image).unwrap(),
+                        resize(image, target.0, target.1, filter),
+                        "{filter:?} to {target:?}",
+                    );
+                }
+            }
+        }
+
+        let plan = ResizePlan::new((23, 17), (9, 30), FilterType::Triangle);
+        assert!(plan.resize(&RgbImage::new(17, 23)).is_err());
+        let plan = ResizePlan::new((0, 17), (0, 5), FilterType::Triangle);
+        assert_eq!(
+            plan.resize(&RgbImage::new(0, 17)).unwrap().dimensions(),
+            (0, 5)
+        );
+    }
+
+    #[test]
+    fn resize_hdr_preserves_range() {
+        let img = crate::Rgba32FImage::from_pixel(8, 8, crate::Rgba([4.0, 0.5, 2.0, 1.0]));
+
+        for filter in [FilterType::Triangle, FilterType::Lanczos3] {
+            let resized = resize(&img, 4, 4, filter);
+            for pixel in resized.pixels() {
+                for (&c, expected) in pixel.0.iter().zip([4.0, 0.5, 2.0, 1.0]) {
+                    assert!((c - expected).abs() < 1e-4, "{c} != {expected}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resize_into_reuses_destination() {
+        let first = RgbImage::from_fn(17, 11, |x, y| crate::Rgb([x as u8 * 15, y as u8 * 20, 7]));
+        let second = RgbImage::from_fn(9, 23, |x, y| crate::Rgb([(x * y) as u8, 200, y as u8]));
+
+        let mut out = RgbImage::new(6, 8);
+        let pointer = out.as_ptr();
+        for image in [&first, &second] {
+            resize_into(image, &mut out, FilterType::CatmullRom).unwrap();
+            assert_eq!(out, resize(image, 6, 8, FilterType::CatmullRom));
+            assert_eq!(out.as_ptr(), pointer);
+        }
+
+        resize_into(&first, &mut out, FilterType::Nearest).unwrap();
+        assert_eq!(out, resize(&first, 6, 8, FilterType::Nearest));
+
+        let mut empty = RgbImage::new(0, 8);
+        assert!(resize_into(&first, &mut empty, FilterType::Triangle).is_err());
+
+        let from_empty = resize(&empty, 3, 2, FilterType::Triangle);
+        assert_eq!(from_empty, RgbImage::new(3, 2));
+    }
+
+    #[test]
+    fn resize_antialiased_stripes() {
+        // Diagonal stripes, one pixel wide and three apart, far too fine for an eighth of the size.
+        let stripes = GrayImage::from_fn(256, 192, |x, y| Luma([((x + y) % 3 == 0) as u8 * 255]));
+        let range = |image: &GrayImage| {
+            let values = image.pixels().map(|p| p[0]);
+            (values.clone().min().unwrap(), values.max().unwrap())
+        };
+
+        // Point sampling turns the stripes into a pattern of its own.
+        let (min, max) = range(&resize(&stripes, 32, 24, FilterType::Nearest));
+        assert!(max - min > 200, "{min}..{max}");
+
+        // The stripes average out to a uniform gray of a third of the brightness.
+        let smooth = resize_antialiased(&stripes, 32, 24);
+        assert_eq!(smooth.dimensions(), (32, 24));
+        let (min, max) = range(&smooth);
+        assert!(min >= 82 && max <= 88, "{min}..{max}");
+
+        // Enlarged axes are interpolated as usual, and an empty image stays empty.
+        let wide = resize_antialiased(&stripes, 512, 24);
+        assert_eq!(wide.dimensions(), (512, 24));
+        assert_eq!(resize_antialiased(&stripes, 0, 24).dimensions(), (0, 24));
+    }
+
+    #[test]
+    fn resize_integer_golden() {
+        let image = RgbImage::from_fn(7, 5, |x, y| {
+            crate::Rgb([
+                (x * 37 + y * 11) as u8,
+                (x * y * 13) as u8,
+                (255 - x * 30 - y * 7) as u8,
+            ])
+        });
+
+        // The expected bytes are the exactly rounded results of the filters.
+        let reduced = resize_integer(&image, 3, 2, FilterType::Triangle).unwrap();
+        assert_eq!(
+            reduced.as_raw(),
+            &[43, 11, 222, 122, 39, 158, 201, 67, 94, 65, 33, 208, 144, 112, 144, 194, 137, 80],
+        );
+        let nearest = resize_integer(&image, 3, 2, FilterType::Nearest).unwrap();
+        assert_eq!(
+            nearest.as_raw(),
+            &[48, 13, 218, 122, 39, 158, 196, 65, 98, 70, 39, 204, 144, 117, 144, 218, 195, 84],
+        );
+
+        let gray = ImageBuffer::from_raw(2, 2, vec![0u16, 1000, 40000, 65535]).unwrap();
+        let enlarged: ImageBuffer<Luma<u16>, _> =
+            resize_integer(&gray, 3, 3, FilterType::Triangle).unwrap();
+        assert_eq!(
+            enlarged.as_raw(),
+            &[0, 500, 1000, 20000, 26634, 33268, 40000, 52768, 65535],
+        );
+
+        // Even the largest 32-bit samples do not overflow the fixed point sums.
+        let wide = ImageBuffer::from_raw(2, 1, vec![0u32, u32::MAX]).unwrap();
+        let enlarged: ImageBuffer<Luma<u32>, _> =
+            resize_integer(&wide, 3, 2, FilterType::Triangle).unwrap();
+        assert_eq!(
+            enlarged.as_raw(),
+            &[0, 1 << 31, u32::MAX, 0, 1 << 31, u32::MAX],
+        );
+
+        // Up to rounding, the results agree with the floating point implementation.
+        for (nwidth, nheight) in [(3, 2), (9, 7), (4, 11)] {
+            let exact = resize_integer(&image, nwidth, nheight, FilterType::Triangle).unwrap();
+            let float = resize(&image, nwidth, nheight, FilterType::Triangle);
+            for (a, b) in exact.as_raw().iter().zip(float.as_raw()) {
+                assert!(a.abs_diff(*b) <= 1, "{nwidth}x{nheight}: {a} != {b}");
+            }
+        }
+
+        assert!(resize_integer(&image, 3, 2, FilterType::Lanczos3).is_err());
+        assert!(resize_integer(&image, 0, 2, FilterType::Nearest).is_err());
+    }
+
+    #[test]
+    fn resize_xy_per_axis() {
+        let image = RgbImage::from_fn(23, 17, |x, y| {
+            crate::Rgb([(x * 11) as u8, (y * 15) as u8, ((x ^ y) * 9) as u8])
+        });
+        let filters = [
+            FilterType::Nearest,
+            FilterType::Triangle,
+            FilterType::CatmullRom,
+            FilterType::Gaussian,
+            FilterType::Lanczos3,
+        ];
+        for filter in filters {
+            assert_eq!(
+                resize_xy(&image, 9, 30, filter, filter),
+                resize(&image, 9, 30, filter),
+                "{filter:?}",
+            );
+        }
+
+        let mixed = resize_xy(&image, 9, 30, FilterType::Nearest, FilterType::Lanczos3);
+        assert_eq!(
+            mixed,
+            resize_xy(&image, 9, 30, FilterType::Nearest, FilterType::Lanczos3)
+        );
+        assert_ne!(mixed, resize(&image, 9, 30, FilterType::Nearest));
+        assert_ne!(mixed, resize(&image, 9, 30, FilterType::Lanczos3));
+
+        // Each filter only acts along its own axis. Vertical stripes look the same whatever the
+        // vertical filter, so they are resampled as if only the horizontal filter was used.
+        let stripes = GrayImage::from_fn(23, 17, |x, _| Luma([(x * 11) as u8]));
+        assert_eq!(
+            resize_xy(&stripes, 9, 30, FilterType::Nearest, FilterType::Lanczos3),
+            resize(&stripes, 9, 30, FilterType::Nearest),
+        );
+    }
+
+    #[test]
+    fn resize_matches_reference() {
+        // The weights of one output pixel along an axis, computed the straightforward way.
+        fn weights(filter: FilterType, size: u32, new_size: u32, out: u32) -> Vec<(u32, f32)> {
+            let filter = filter.filter();
+            let ratio = size as f32 / new_size as f32;
+            let sratio = ratio.max(1.0);
+            let input = (out as f32 + 0.5) * ratio;
+            let support = filter.support * sratio;
+            let left = ((input - support).floor() as i64).clamp(0, size as i64 - 1);
+            let right = ((input + support).ceil() as i64).clamp(left + 1, size as i64);
+            let weights: Vec<_> = (left..right)
+                .map(|i| {
+                    (
+                        i as u32,
+                        (filter.kernel)((i as f32 - (input - 0.5)) / sratio),
+                    )
+                })
+                .collect();
+            let sum: f32 = weights.iter().map(|&(_, w)| w).sum();
+            weights.into_iter().map(|(i, w)| (i, w / sum)).collect()
+        }
+
+        let image = crate::RgbaImage::from_fn(7, 5, |x, y| {
+            let v = |k: u32| ((x * 53 + y * 97 + k * 31) * (x + 2 * y + k + 1) % 256) as u8;
+            crate::Rgba([v(0), v(1), v(2), v(3)])
+        });
+        for filter in [
+            FilterType::Nearest,
+            FilterType::Triangle,
+            FilterType::CatmullRom,
+            FilterType::Gaussian,
+            FilterType::Lanczos3,
+        ] {
+            for (nwidth, nheight) in [(4, 9), (11, 3), (3, 3), (13, 1)] {
+                let expected = crate::RgbaImage::from_fn(nwidth, nheight, |x, y| {
+                    let mut pixel = [0.0f32; 4];
+                    for (i, wx) in weights(filter, 7, nwidth, x) {
+                        let mut column = [0.0f32; 4];
+                        for (j, wy) in weights(filter, 5, nheight, y) {
+                            let p = image.get_pixel(i, j).0;
+                            for c in 0..4 {
+                                column[c] += f32::from(p[c]) * wy;
+                            }
+                        }
+                        for c in 0..4 {
+                            pixel[c] += column[c] * wx;
+                        }
+                    }
+                    crate::Rgba(pixel.map(|v| v.clamp(0.0, 255.0).round() as u8))
+                });
+                let resized = resize(&image, nwidth, nheight, filter);
+                assert_eq!(resized, expected, "{filter:?} {nwidth}x{nheight}");
+            }
+        }
+    }
+
+    #[test]
+    fn resize_tileable_without_seams() {
+        // A checkerboard of 8x8 cells that tiles seamlessly at 64x64.
+        let board = GrayImage::from_fn(64, 64, |x, y| Luma([((x / 8 + y / 8) % 2 * 255) as u8]));
+
+        for (edge, filter) in [
+            (EdgeHandling::Wrap, FilterType::Lanczos3),
+            (EdgeHandling::Reflect, FilterType::CatmullRom),
+        ] {
+            let resized = resize_with_edges(&board, 24, 24, filter, edge);
+
+            // Surround the board with copies of itself, laid out the way the edge handling
+            // continues it. Away from the outer edges of that mosaic, every output pixel only
+            // sees pixels that were really there, so its center must match.
+            let mosaic = GrayImage::from_fn(192, 192, |x, y| {
+                let x = edge.resolve(i64::from(x) - 64, 64);
+                let y = edge.resolve(i64::from(y) - 64, 64);
+                *board.get_pixel(x, y)
+            });
+            let reference = resize(&mosaic, 72, 72, filter);
+
+            for (x, y, pixel) in resized.enumerate_pixels() {
+                let expected = reference.get_pixel(x + 24, y + 24);
+                assert!(
+                    pixel[0].abs_diff(expected[0]) <= 1,
+                    "{edge:?} at ({x}, {y}): {} != {}",
+                    pixel[0],
+                    expected[0],
+                );
+            }
+        }
+
+        // The wrapped edges are as bright as the rest, there is no seam when tiling.
+        let wrapped = resize_with_edges(&board, 24, 24, FilterType::Lanczos3, EdgeHandling::Wrap);
+        let column_mean = |x| {
+            (0..24)
+                .map(|y| f32::from(wrapped.get_pixel(x, y)[0]))
+                .sum::<f32>()
+                / 24.0
+        };
+        for x in [0, 23] {
+            assert!((column_mean(x) - column_mean(12)).abs() < 2.0);
+        }
+    }
+
     #[test]
     #[cfg(feature = "png")]
     fn test_sample_bilinear() {
@@ -1257,4 +2169,21 @@ mod tests {
         let result = resize(&image, 22, 22, FilterType::Lanczos3);
         assert!(result.into_raw().into_iter().any(|c| c != 0));
     }
+
+    #[test]
+    fn unsharp_mask_increases_edge_contrast() {
+        let image = GrayImage::from_fn(8, 3, |x, _| Luma([if x < 4 { 64 } else { 192 }]));
+        let sharpened = unsharp_mask(&image, 1.0, 1.0, 0);
+        for y in 0..3 {
+            assert!(sharpened.get_pixel(3, y)[0] < 64);
+            assert!(sharpened.get_pixel(4, y)[0] > 192);
+        }
+    }
+
+    #[test]
+    fn unsharp_mask_keeps_flat_region_below_threshold() {
+        let image = GrayImage::from_fn(8, 8, |x, y| Luma([100 + 2 * ((x + y) % 2) as u8]));
+        let sharpened = unsharp_mask(&image, 1.0, 4.0, 4);
+        assert_eq!(sharpened, image);
+    }
 }
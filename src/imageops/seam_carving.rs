@@ -0,0 +1,288 @@
+//! Content-aware resizing by seam carving
+//!
+//! See <https://en.wikipedia.org/wiki/Seam_carving>
+
+use std::collections::HashSet;
+
+use num_traits::NumCast;
+
+use crate::image::GenericImageView;
+use crate::imageops::sample::FloatNearest;
+use crate::traits::{Pixel, Primitive};
+use crate::ImageBuffer;
+
+/// Resize an image to `width` x `height` by removing or duplicating seams of low energy.
+///
+/// A seam is a connected path of pixels running from one border of the image to the opposite
+/// one, crossing each row (or column) exactly once. The energy of a pixel is the gradient
+/// magnitude of its luminance, so seams avoid edges and detailed regions and the prominent
+/// content of an image is preserved while flat regions shrink or grow. Columns are carved before
+/// rows.
+///
+/// When enlarging, the seams that would be removed first are duplicated, each inserted pixel
+/// being the average of the seam and its right (or lower) neighbor.
+pub fn seam_carve<I, P, S>(image: &I, width: u32, height: u32) -> ImageBuffer<P, Vec<S>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let (image_width, image_height) = image.dimensions();
+    if width == 0 || height == 0 || image_width == 0 || image_height == 0 {
+        return ImageBuffer::new(width, height);
+    }
+
+    let grid = Grid {
+        width: image_width as usize,
+        height: image_height as usize,
+        pixels: image.pixels().map(|(_, _, p)| p).collect(),
+    };
+
+    let grid = resize_columns(grid, width as usize);
+    let grid = resize_columns(grid.transpose(), height as usize).transpose();
+
+    let mut out = ImageBuffer::new(width, height);
+    for (target, pixel) in out.pixels_mut().zip(grid.pixels) {
+        *target = pixel;
+    }
+    out
+}
+
+/// Pixels of an image in row-major order.
+struct Grid<P> {
+    width: usize,
+    height: usize,
+    pixels: Vec<P>,
+}
+
+impl<P: Pixel> Grid<P> {
+    fn transpose(self) -> Self {
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for x in 0..self.width {
+            for y in 0..self.height {
+                pixels.push(self.pixels[y * self.width + x]);
+            }
+        }
+
+        Grid {
+            width: self.height,
+            height: self.width,
+            pixels,
+        }
+    }
+
+    /// The gradient magnitude of the luminance at each pixel, with borders clamped.
+    fn energy(&self) -> Vec<f32> {
+        let max: f32 = NumCast::from(P::Subpixel::DEFAULT_MAX_VALUE).unwrap();
+        let luma: Vec<f32> = self
+            .pixels
+            .iter()
+            .map(|p| {
+                let l: f32 = NumCast::from(p.to_luma().0[0]).unwrap();
+                l / max
+            })
+            .collect();
+
+        let (w, h) = (self.width, self.height);
+        let at = |x: usize, y: usize| luma[y * w + x];
+        let mut energy = Vec::with_capacity(luma.len());
+        for y in 0..h {
+            for x in 0..w {
+                let dx = at((x + 1).min(w - 1), y) - at(x.saturating_sub(1), y);
+                let dy = at(x, (y + 1).min(h - 1)) - at(x, y.saturating_sub(1));
+                energy.push(dx.abs() + dy.abs());
+            }
+        }
+        energy
+    }
+
+    /// Find the vertical seam of minimal total energy, as one column index per row.
+    fn find_seam(&self) -> Vec<usize> {
+        let (w, h) = (self.width, self.height);
+        let mut cost = self.energy();
+
+        for y in 1..h {
+            for x in 0..w {
+                let above = &cost[(y - 1) * w..y * w];
+                let best = above[x.saturating_sub(1)..(x + 2).min(w)]
+                    .iter()
+                    .copied()
+                    .fold(f32::INFINITY, f32::min);
+                cost[y * w + x] += best;
+            }
+        }
+
+        let mut seam = vec![0; h];
+        seam[h - 1] = min_index(&cost[(h - 1) * w..], 0);
+        for y in (0..h - 1).rev() {
+            let x = seam[y + 1];
+            let start = x.saturating_sub(1);
+            let end = (x + 2).min(w);
+            seam[y] = min_index(&cost[y * w + start..y * w + end], start);
+        }
+        seam
+    }
+
+    /// Remove one pixel per row, as given by `seam`.
+    fn remove_seam(&mut self, seam: &[usize]) {
+        self.pixels = without_seam(&self.pixels, self.width, seam);
+        self.width -= 1;
+    }
+}
+
+/// Copy `values`, arranged in rows of `width`, leaving out one value per row as given by `seam`.
+fn without_seam<T: Copy>(values: &[T], width: usize, seam: &[usize]) -> Vec<T> {
+    values
+        .chunks_exact(width)
+        .zip(seam)
+        .flat_map(|(row, &x)| row[..x].iter().chain(&row[x + 1..]).copied())
+        .collect()
+}
+
+/// Carve or insert vertical seams until the grid is `width` columns wide.
+fn resize_columns<P: Pixel>(mut grid: Grid<P>, width: usize) -> Grid<P> {
+    while grid.width > width {
+        let seam = grid.find_seam();
+        grid.remove_seam(&seam);
+    }
+
+    // Each pass can duplicate every column at most once.
+    while grid.width < width {
+        let count = (width - grid.width).min(grid.width);
+        grid = insert_seams(grid, count);
+    }
+
+    grid
+}
+
+/// Duplicate the `count` seams that would be removed first.
+fn insert_seams<P: Pixel>(grid: Grid<P>, count: usize) -> Grid<P> {
+    let (w, h) = (grid.width, grid.height);
+
+    // Carve a copy, tracking the original position of each remaining pixel.
+    let mut copy = Grid {
+        width: w,
+        height: h,
+        pixels: grid.pixels.clone(),
+    };
+    let mut origins: Vec<usize> = (0..w * h).collect();
+    let mut duplicated = HashSet::new();
+    for _ in 0..count {
+        let seam = copy.find_seam();
+        for (y, &x) in seam.iter().enumerate() {
+            duplicated.insert(origins[y * copy.width + x]);
+        }
+        origins = without_seam(&origins, copy.width, &seam);
+        copy.remove_seam(&seam);
+    }
+
+    let mut pixels = Vec::with_capacity((w + count) * h);
+    for y in 0..h {
+        for x in 0..w {
+            let index = y * w + x;
+            let pixel = grid.pixels[index];
+            pixels.push(pixel);
+            if duplicated.contains(&index) {
+                let next = grid.pixels[y * w + (x + 1).min(w - 1)];
+                pixels.push(pixel.map2(&next, average));
+            }
+        }
+    }
+
+    Grid {
+        width: w + count,
+        height: h,
+        pixels,
+    }
+}
+
+fn average<S: Primitive>(a: S, b: S) -> S {
+    let a: f32 = NumCast::from(a).unwrap();
+    let b: f32 = NumCast::from(b).unwrap();
+    NumCast::from(FloatNearest((a + b) / 2.0)).unwrap()
+}
+
+/// The index of the first minimum within `values`, plus `offset`.
+fn min_index(values: &[f32], offset: usize) -> usize {
+    let mut best = 0;
+    for (i, &value) in values.iter().enumerate() {
+        if value < values[best] {
+            best = i;
+        }
+    }
+    offset + best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GrayImage, Luma, RgbImage};
+
+    /// A checkerboard object on the left, a flat band on the right.
+    fn object_and_band(width: u32, height: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, y| {
+            if x < 16 {
+                Luma([if (x + y) % 2 == 0 { 0 } else { 255 }])
+            } else {
+                Luma([128])
+            }
+        })
+    }
+
+    #[test]
+    fn shrink_removes_flat_band() {
+        let image = object_and_band(40, 12);
+        let carved = seam_carve(&image, 28, 12);
+
+        assert_eq!(carved.dimensions(), (28, 12));
+        for y in 0..12 {
+            for x in 0..16 {
+                assert_eq!(carved.get_pixel(x, y), image.get_pixel(x, y));
+            }
+            for x in 17..28 {
+                assert_eq!(carved.get_pixel(x, y).0[0], 128);
+            }
+        }
+    }
+
+    #[test]
+    fn enlarge_duplicates_flat_band() {
+        let image = object_and_band(30, 10);
+        let carved = seam_carve(&image, 38, 10);
+
+        assert_eq!(carved.dimensions(), (38, 10));
+        for y in 0..10 {
+            for x in 0..16 {
+                assert_eq!(carved.get_pixel(x, y), image.get_pixel(x, y));
+            }
+            for x in 17..38 {
+                assert_eq!(carved.get_pixel(x, y).0[0], 128);
+            }
+        }
+    }
+
+    #[test]
+    fn carve_rows() {
+        let image = RgbImage::from_fn(10, 30, |x, y| {
+            if y < 10 {
+                crate::Rgb([(x * 25) as u8, (y * 25) as u8, 0])
+            } else {
+                crate::Rgb([7, 7, 7])
+            }
+        });
+        let carved = seam_carve(&image, 10, 15);
+
+        assert_eq!(carved.dimensions(), (10, 15));
+        for y in 0..10 {
+            for x in 0..10 {
+                assert_eq!(carved.get_pixel(x, y), image.get_pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn empty_target() {
+        let image = object_and_band(4, 4);
+        assert_eq!(seam_carve(&image, 0, 3).dimensions(), (0, 3));
+    }
+}
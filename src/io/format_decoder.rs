@@ -0,0 +1,140 @@
+use std::io::{BufRead, Seek};
+
+#[allow(unused)]
+use crate::codecs::*;
+use crate::color::{ColorType, ExtendedColorType};
+use crate::{ImageDecoder, ImageResult};
+
+/// The decoder for one of the supported formats, as chosen by a [`Reader`].
+///
+/// This implements [`ImageDecoder`] by dispatching to the decoder of the format, which can also be
+/// matched on to use methods specific to that format before decoding any pixels. Variants exist
+/// only for the formats whose features are enabled.
+///
+/// [`Reader`]: super::Reader
+#[non_exhaustive]
+// The decoder is usually consumed right away, so the size of the largest variant does not matter.
+#[allow(clippy::large_enum_variant)]
+pub enum FormatDecoder<R: BufRead + Seek> {
+    /// An AVIF image
+    #[cfg(feature = "avif-native")]
+    Avif(avif::AvifDecoder<R>),
+    /// A PNG or APNG image
+    #[cfg(feature = "png")]
+    Png(png::PngDecoder<R>),
+    /// A GIF image
+    #[cfg(feature = "gif")]
+    Gif(gif::GifDecoder<R>),
+    /// A JPEG image
+    #[cfg(feature = "jpeg")]
+    Jpeg(jpeg::JpegDecoder<R>),
+    /// A WebP image
+    #[cfg(feature = "webp")]
+    WebP(webp::WebPDecoder<R>),
+    /// A TIFF image
+    #[cfg(feature = "tiff")]
+    Tiff(tiff::TiffDecoder<R>),
+    /// A TGA image
+    #[cfg(feature = "tga")]
+    Tga(tga::TgaDecoder<R>),
+    /// A DDS image
+    #[cfg(feature = "dds")]
+    Dds(dds::DdsDecoder<R>),
+    /// A BMP image
+    #[cfg(feature = "bmp")]
+    Bmp(bmp::BmpDecoder<R>),
+    /// An ICO image
+    #[cfg(feature = "ico")]
+    Ico(ico::IcoDecoder<R>),
+    /// A Radiance HDR image
+    #[cfg(feature = "hdr")]
+    Hdr(hdr::HdrDecoder<R>),
+    /// An OpenEXR image
+    #[cfg(feature = "exr")]
+    OpenExr(openexr::OpenExrDecoder<R>),
+    /// A PNM image
+    #[cfg(feature = "pnm")]
+    Pnm(pnm::PnmDecoder<R>),
+    /// A farbfeld image
+    #[cfg(feature = "ff")]
+    Farbfeld(farbfeld::FarbfeldDecoder<R>),
+    /// A QOI image
+    #[cfg(feature = "qoi")]
+    Qoi(qoi::QoiDecoder<R>),
+    #[doc(hidden)]
+    Phantom(std::convert::Infallible, std::marker::PhantomData<R>),
+}
+
+macro_rules! dispatch {
+    ($decoder: expr, $inner: pat => $action: expr) => {
+        match $decoder {
+            #[cfg(feature = "avif-native")]
+            FormatDecoder::Avif($inner) => $action,
+            #[cfg(feature = "png")]
+            FormatDecoder::Png($inner) => $action,
+            #[cfg(feature = "gif")]
+            FormatDecoder::Gif($inner) => $action,
+            #[cfg(feature = "jpeg")]
+            FormatDecoder::Jpeg($inner) => $action,
+            #[cfg(feature = "webp")]
+            FormatDecoder::WebP($inner) => $action,
+            #[cfg(feature = "tiff")]
+            FormatDecoder::Tiff($inner) => $action,
+            #[cfg(feature = "tga")]
+            FormatDecoder::Tga($inner) => $action,
+            #[cfg(feature = "dds")]
+            FormatDecoder::Dds($inner) => $action,
+            #[cfg(feature = "bmp")]
+            FormatDecoder::Bmp($inner) => $action,
+            #[cfg(feature = "ico")]
+            FormatDecoder::Ico($inner) => $action,
+            #[cfg(feature = "hdr")]
+            FormatDecoder::Hdr($inner) => $action,
+            #[cfg(feature = "exr")]
+            FormatDecoder::OpenExr($inner) => $action,
+            #[cfg(feature = "pnm")]
+            FormatDecoder::Pnm($inner) => $action,
+            #[cfg(feature = "ff")]
+            FormatDecoder::Farbfeld($inner) => $action,
+            #[cfg(feature = "qoi")]
+            FormatDecoder::Qoi($inner) => $action,
+            FormatDecoder::Phantom(..) => unreachable!(),
+        }
+    };
+}
+
+// The arguments are unused if no format is enabled.
+#[allow(unused_variables)]
+impl<R: BufRead + Seek> ImageDecoder for FormatDecoder<R> {
+    fn dimensions(&self) -> (u32, u32) {
+        dispatch!(self, d => d.dimensions())
+    }
+
+    fn color_type(&self) -> ColorType {
+        dispatch!(self, d => d.color_type())
+    }
+
+    fn original_color_type(&self) -> ExtendedColorType {
+        dispatch!(self, d => d.original_color_type())
+    }
+
+    fn icc_profile(&mut self) -> ImageResult<Option<Vec<u8>>> {
+        dispatch!(self, d => d.icc_profile())
+    }
+
+    fn total_bytes(&self) -> u64 {
+        dispatch!(self, d => d.total_bytes())
+    }
+
+    fn read_image(self, buf: &mut [u8]) -> ImageResult<()> {
+        dispatch!(self, d => d.read_image(buf))
+    }
+
+    fn set_limits(&mut self, limits: crate::io::Limits) -> ImageResult<()> {
+        dispatch!(self, d => d.set_limits(limits))
+    }
+
+    fn read_image_boxed(self: Box<Self>, buf: &mut [u8]) -> ImageResult<()> {
+        (*self).read_image(buf)
+    }
+}
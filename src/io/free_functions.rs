@@ -26,6 +26,45 @@ pub fn load<R: BufRead + Seek>(r: R, format: ImageFormat) -> ImageResult<Dynamic
     reader.decode()
 }
 
+/// Create a new image from a Reader that can not seek, such as a network stream or a pipe.
+///
+/// Only formats that are decoded from start to end can be read this way: QOI, PNM, farbfeld,
+/// Radiance HDR, TGA and DDS. Other formats, including WebP, need to jump around in the data and
+/// return an [`ImageError::Unsupported`] error; use [`load`] for them. The image is decoded with
+/// the default [`Limits`](crate::io::Limits), as with [`io::Reader`](crate::io::Reader).
+#[allow(unused_variables, dead_code)] // When no streaming formats are supported
+pub fn load_streaming<R: BufRead>(r: R, format: ImageFormat) -> ImageResult<DynamicImage> {
+    fn decode(mut decoder: impl ImageDecoder) -> ImageResult<DynamicImage> {
+        let mut limits = crate::io::Limits::default();
+        limits.reserve(decoder.total_bytes())?;
+        decoder.set_limits(limits)?;
+        DynamicImage::from_decoder(decoder)
+    }
+
+    #[allow(unreachable_patterns)]
+    // Default is unreachable if all streaming formats are supported.
+    match format {
+        #[cfg(feature = "qoi")]
+        ImageFormat::Qoi => decode(qoi::QoiDecoder::new(r)?),
+        #[cfg(feature = "pnm")]
+        ImageFormat::Pnm => decode(pnm::PnmDecoder::new(r)?),
+        #[cfg(feature = "ff")]
+        ImageFormat::Farbfeld => decode(farbfeld::FarbfeldDecoder::new(r)?),
+        #[cfg(feature = "hdr")]
+        ImageFormat::Hdr => decode(hdr::HdrDecoder::new(r)?),
+        #[cfg(feature = "tga")]
+        ImageFormat::Tga => decode(tga::TgaDecoder::new(r)?),
+        #[cfg(feature = "dds")]
+        ImageFormat::Dds => decode(dds::DdsDecoder::new(r)?),
+        format => Err(ImageError::Unsupported(
+            UnsupportedError::from_format_and_kind(
+                ImageFormatHint::Exact(format),
+                UnsupportedErrorKind::GenericFeature("decoding without seeking".to_owned()),
+            ),
+        )),
+    }
+}
+
 #[allow(unused_variables)]
 // Most variables when no features are supported
 pub(crate) fn save_buffer_impl(
@@ -123,7 +162,7 @@ pub(crate) fn write_buffer_impl<W: std::io::Write + Seek>(
     }
 }
 
-static MAGIC_BYTES: [(&[u8], ImageFormat); 23] = [
+static MAGIC_BYTES: [(&[u8], ImageFormat); 21] = [
     (b"\x89PNG\r\n\x1a\n", ImageFormat::Png),
     (&[0xff, 0xd8, 0xff], ImageFormat::Jpeg),
     (b"GIF89a", ImageFormat::Gif),
@@ -143,8 +182,6 @@ static MAGIC_BYTES: [(&[u8], ImageFormat); 23] = [
     (b"P6", ImageFormat::Pnm),
     (b"P7", ImageFormat::Pnm),
     (b"farbfeld", ImageFormat::Farbfeld),
-    (b"\0\0\0 ftypavif", ImageFormat::Avif),
-    (b"\0\0\0\x1cftypavif", ImageFormat::Avif),
     (&[0x76, 0x2f, 0x31, 0x01], ImageFormat::OpenExr), // = &exr::meta::magic_number::BYTES
     (b"qoif", ImageFormat::Qoi),
 ];
@@ -157,11 +194,27 @@ static MAGIC_BYTES: [(&[u8], ImageFormat); 23] = [
 pub fn guess_format(buffer: &[u8]) -> ImageResult<ImageFormat> {
     match guess_format_impl(buffer) {
         Some(format) => Ok(format),
-        None => Err(ImageError::Unsupported(ImageFormatHint::Unknown.into())),
+        // An ISO base media file of another kind, such as MP4 or HEIC.
+        None => match ftyp_brands(buffer).and_then(|mut brands| brands.next()) {
+            Some(major_brand) => Err(ImageError::Unsupported(
+                UnsupportedError::from_format_and_kind(
+                    ImageFormatHint::Unknown,
+                    UnsupportedErrorKind::Format(ImageFormatHint::Name(
+                        String::from_utf8_lossy(major_brand).trim_end().to_owned(),
+                    )),
+                ),
+            )),
+            None => Err(ImageError::Unsupported(ImageFormatHint::Unknown.into())),
+        },
     }
 }
 
 pub(crate) fn guess_format_impl(buffer: &[u8]) -> Option<ImageFormat> {
+    if let Some(mut brands) = ftyp_brands(buffer) {
+        let is_avif = brands.any(|brand| brand == b"avif" || brand == b"avis");
+        return is_avif.then_some(ImageFormat::Avif);
+    }
+
     for &(signature, format) in &MAGIC_BYTES {
         if buffer.starts_with(signature) {
             return Some(format);
@@ -170,3 +223,121 @@ pub(crate) fn guess_format_impl(buffer: &[u8]) -> Option<ImageFormat> {
 
     None
 }
+
+/// The brands of an ISO base media file, read from its leading `ftyp` box.
+///
+/// Yields the major brand first, followed by those compatible brands that lie within `buffer`.
+fn ftyp_brands(buffer: &[u8]) -> Option<impl Iterator<Item = &[u8]>> {
+    let size = u32::from_be_bytes(buffer.get(..4)?.try_into().ok()?) as usize;
+    if buffer.get(4..8)? != b"ftyp" || size < 16 {
+        return None;
+    }
+
+    let major_brand = buffer.get(8..12)?;
+    // The minor version is skipped.
+    let compatible_brands = buffer.get(16..size.min(buffer.len())).unwrap_or_default();
+    Some(std::iter::once(major_brand).chain(compatible_brands.chunks_exact(4)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ftyp(major_brand: &[u8; 4], compatible_brands: &[&[u8; 4]]) -> Vec<u8> {
+        let size = 16 + 4 * compatible_brands.len() as u32;
+        let mut data = size.to_be_bytes().to_vec();
+        data.extend_from_slice(b"ftyp");
+        data.extend_from_slice(major_brand);
+        data.extend_from_slice(&[0; 4]);
+        for brand in compatible_brands {
+            data.extend_from_slice(*brand);
+        }
+        data
+    }
+
+    #[test]
+    fn guess_avif_brands() {
+        let avif = ftyp(b"avif", &[b"mif1", b"miaf"]);
+        assert_eq!(guess_format(&avif).unwrap(), ImageFormat::Avif);
+
+        let avis = ftyp(b"avis", &[b"msf1"]);
+        assert_eq!(guess_format(&avis).unwrap(), ImageFormat::Avif);
+
+        let compatible = ftyp(b"mif1", &[b"avif"]);
+        assert_eq!(guess_format(&compatible).unwrap(), ImageFormat::Avif);
+
+        let reader = crate::io::Reader::new(std::io::Cursor::new(compatible))
+            .with_guessed_format()
+            .unwrap();
+        assert_eq!(reader.format(), Some(ImageFormat::Avif));
+    }
+
+    #[test]
+    fn reject_other_iso_media() {
+        for data in [ftyp(b"mp42", &[b"isom"]), ftyp(b"heic", &[b"mif1"])] {
+            match guess_format(&data) {
+                Err(ImageError::Unsupported(err)) => {
+                    assert!(matches!(
+                        err.kind(),
+                        UnsupportedErrorKind::Format(ImageFormatHint::Name(_))
+                    ));
+                }
+                other => panic!("unexpected result {other:?}"),
+            }
+        }
+
+        // Brands beyond the size of the box are not considered.
+        let mut truncated = ftyp(b"mp42", &[]);
+        truncated.extend_from_slice(b"avif");
+        assert!(guess_format(&truncated).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "qoi")]
+    fn load_qoi_without_seeking() {
+        let rgba: Vec<u8> = (0..6 * 4 * 4).map(|i| (i * 13) as u8).collect();
+        let mut data = Vec::new();
+        qoi::QoiEncoder::new(&mut data)
+            .write_image(&rgba, 6, 4, ExtendedColorType::Rgba8)
+            .unwrap();
+
+        // A byte slice is buffered but can not seek.
+        let decoded = load_streaming(&data[..], ImageFormat::Qoi).unwrap();
+        assert_eq!(decoded.color(), crate::ColorType::Rgba8);
+        assert_eq!(decoded.into_rgba8().into_raw(), rgba);
+
+        match load_streaming(&data[..], ImageFormat::WebP) {
+            Err(ImageError::Unsupported(err)) => {
+                assert_eq!(err.format_hint(), ImageFormatHint::Exact(ImageFormat::WebP));
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn save_without_extension() {
+        let path = std::env::temp_dir().join(format!("image-rs-webp-{}", std::process::id()));
+        let rgba: Vec<u8> = (0..5 * 3 * 4).map(|i| (i * 17) as u8).collect();
+        crate::save_buffer_with_format(
+            &path,
+            &rgba,
+            5,
+            3,
+            ExtendedColorType::Rgba8,
+            ImageFormat::WebP,
+        )
+        .unwrap();
+
+        // The format cannot be told from the path.
+        assert!(crate::save_buffer(&path, &rgba, 5, 3, ExtendedColorType::Rgba8).is_err());
+        let decoded = crate::io::Reader::open(&path)
+            .and_then(|reader| reader.with_guessed_format())
+            .map(|reader| (reader.format(), reader.decode()));
+        std::fs::remove_file(&path).unwrap();
+
+        let (format, decoded) = decoded.unwrap();
+        assert_eq!(format, Some(ImageFormat::WebP));
+        assert_eq!(decoded.unwrap().into_rgba8().into_raw(), rgba);
+    }
+}
@@ -2,9 +2,11 @@
 
 use crate::{error, ColorType, ImageError, ImageResult};
 
+mod format_decoder;
 pub(crate) mod free_functions;
 mod reader;
 
+pub use self::format_decoder::FormatDecoder;
 pub use self::reader::Reader;
 
 /// Set of supported strict limits for a decoder.
@@ -7,7 +7,7 @@ use crate::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
 use crate::image::ImageFormat;
 use crate::{ImageDecoder, ImageError, ImageResult};
 
-use super::free_functions;
+use super::{free_functions, FormatDecoder};
 
 /// A multi-format image reader.
 ///
@@ -140,7 +140,7 @@ impl<'a, R: 'a + BufRead + Seek> Reader<R> {
         format: ImageFormat,
         reader: R,
         limits_for_png: super::Limits,
-    ) -> ImageResult<Box<dyn ImageDecoder + 'a>> {
+    ) -> ImageResult<FormatDecoder<R>> {
         #[allow(unused)]
         use crate::codecs::*;
 
@@ -148,35 +148,39 @@ impl<'a, R: 'a + BufRead + Seek> Reader<R> {
         // Default is unreachable if all features are supported.
         Ok(match format {
             #[cfg(feature = "avif-native")]
-            ImageFormat::Avif => Box::new(avif::AvifDecoder::new(reader)?),
+            ImageFormat::Avif => FormatDecoder::Avif(avif::AvifDecoder::new(reader)?),
             #[cfg(feature = "png")]
-            ImageFormat::Png => Box::new(png::PngDecoder::with_limits(reader, limits_for_png)?),
+            ImageFormat::Png => {
+                FormatDecoder::Png(png::PngDecoder::with_limits(reader, limits_for_png)?)
+            }
             #[cfg(feature = "gif")]
-            ImageFormat::Gif => Box::new(gif::GifDecoder::new(reader)?),
+            ImageFormat::Gif => FormatDecoder::Gif(gif::GifDecoder::new(reader)?),
             #[cfg(feature = "jpeg")]
-            ImageFormat::Jpeg => Box::new(jpeg::JpegDecoder::new(reader)?),
+            ImageFormat::Jpeg => FormatDecoder::Jpeg(jpeg::JpegDecoder::new(reader)?),
             #[cfg(feature = "webp")]
-            ImageFormat::WebP => Box::new(webp::WebPDecoder::new(reader)?),
+            ImageFormat::WebP => FormatDecoder::WebP(webp::WebPDecoder::new(reader)?),
             #[cfg(feature = "tiff")]
-            ImageFormat::Tiff => Box::new(tiff::TiffDecoder::new(reader)?),
+            ImageFormat::Tiff => FormatDecoder::Tiff(tiff::TiffDecoder::new(reader)?),
             #[cfg(feature = "tga")]
-            ImageFormat::Tga => Box::new(tga::TgaDecoder::new(reader)?),
+            ImageFormat::Tga => FormatDecoder::Tga(tga::TgaDecoder::new(reader)?),
             #[cfg(feature = "dds")]
-            ImageFormat::Dds => Box::new(dds::DdsDecoder::new(reader)?),
+            ImageFormat::Dds => FormatDecoder::Dds(dds::DdsDecoder::new(reader)?),
             #[cfg(feature = "bmp")]
-            ImageFormat::Bmp => Box::new(bmp::BmpDecoder::new(reader)?),
+            ImageFormat::Bmp => FormatDecoder::Bmp(bmp::BmpDecoder::new(reader)?),
             #[cfg(feature = "ico")]
-            ImageFormat::Ico => Box::new(ico::IcoDecoder::new(reader)?),
+            ImageFormat::Ico => FormatDecoder::Ico(ico::IcoDecoder::new(reader)?),
             #[cfg(feature = "hdr")]
-            ImageFormat::Hdr => Box::new(hdr::HdrDecoder::new(reader)?),
+            ImageFormat::Hdr => FormatDecoder::Hdr(hdr::HdrDecoder::new(reader)?),
             #[cfg(feature = "exr")]
-            ImageFormat::OpenExr => Box::new(openexr::OpenExrDecoder::new(reader)?),
+            ImageFormat::OpenExr => FormatDecoder::OpenExr(openexr::OpenExrDecoder::new(reader)?),
             #[cfg(feature = "pnm")]
-            ImageFormat::Pnm => Box::new(pnm::PnmDecoder::new(reader)?),
+            ImageFormat::Pnm => FormatDecoder::Pnm(pnm::PnmDecoder::new(reader)?),
             #[cfg(feature = "ff")]
-            ImageFormat::Farbfeld => Box::new(farbfeld::FarbfeldDecoder::new(reader)?),
+            ImageFormat::Farbfeld => {
+                FormatDecoder::Farbfeld(farbfeld::FarbfeldDecoder::new(reader)?)
+            }
             #[cfg(feature = "qoi")]
-            ImageFormat::Qoi => Box::new(qoi::QoiDecoder::new(reader)?),
+            ImageFormat::Qoi => FormatDecoder::Qoi(qoi::QoiDecoder::new(reader)?),
             format => {
                 return Err(ImageError::Unsupported(
                     ImageFormatHint::Exact(format).into(),
@@ -186,7 +190,16 @@ impl<'a, R: 'a + BufRead + Seek> Reader<R> {
     }
 
     /// Convert the reader into a decoder.
-    pub fn into_decoder(mut self) -> ImageResult<impl ImageDecoder + 'a> {
+    pub fn into_decoder(self) -> ImageResult<impl ImageDecoder + 'a> {
+        self.into_format_decoder()
+    }
+
+    /// Convert the reader into the decoder of the detected format.
+    ///
+    /// Unlike [`into_decoder`](Self::into_decoder), the result can be matched on to call methods
+    /// specific to the format, such as querying whether a WebP image is animated, before any
+    /// pixels are decoded.
+    pub fn into_format_decoder(mut self) -> ImageResult<FormatDecoder<R>> {
         let mut decoder =
             Self::make_decoder(self.require_format()?, self.inner, self.limits.clone())?;
         decoder.set_limits(self.limits)?;
@@ -229,13 +242,13 @@ impl<'a, R: 'a + BufRead + Seek> Reader<R> {
     }
 
     fn guess_format(&mut self) -> io::Result<Option<ImageFormat>> {
-        let mut start = [0; 16];
+        let mut start = [0; 32];
 
         // Save current offset, read start, restore offset.
         let cur = self.inner.stream_position()?;
         let len = io::copy(
-            // Accept shorter files but read at most 16 bytes.
-            &mut self.inner.by_ref().take(16),
+            // Accept shorter files but read at most 32 bytes.
+            &mut self.inner.by_ref().take(32),
             &mut Cursor::new(&mut start[..]),
         )?;
         self.inner.seek(SeekFrom::Start(cur))?;
@@ -305,3 +318,27 @@ impl Reader<BufReader<File>> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn format_decoder_webp_animation() {
+        let decoder = Reader::open("tests/images/webp/extended_images/anim.webp")
+            .unwrap()
+            .with_guessed_format()
+            .unwrap()
+            .into_format_decoder()
+            .unwrap();
+        match decoder {
+            FormatDecoder::WebP(ref webp) => assert!(webp.has_animation()),
+            _ => panic!("expected a WebP decoder"),
+        }
+
+        let dimensions = decoder.dimensions();
+        let image = DynamicImage::from_decoder(decoder).unwrap();
+        assert_eq!((image.width(), image.height()), dimensions);
+    }
+}
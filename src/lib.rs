@@ -163,12 +163,12 @@ pub use crate::traits::{EncodableLayout, Pixel, PixelWithColorType, Primitive};
 
 // Opening and loading images
 pub use crate::dynimage::{
-    image_dimensions, load_from_memory, load_from_memory_with_format, open, save_buffer,
-    save_buffer_with_format, write_buffer_with_format,
+    buffer_to_native_endian, image_dimensions, load_from_memory, load_from_memory_with_format,
+    open, save_buffer, save_buffer_with_format, write_buffer_with_format,
 };
-pub use crate::io::free_functions::{guess_format, load};
+pub use crate::io::free_functions::{guess_format, load, load_streaming};
 
-pub use crate::dynimage::DynamicImage;
+pub use crate::dynimage::{DynamicImage, Endianness};
 
 pub use crate::animation::{Delay, Frame, Frames};
 
@@ -289,6 +289,28 @@ pub trait Pixel: Copy + Clone {
     /// that the slice is long enough to prevent panics if the pixel is used later on.
     fn from_slice_mut(slice: &mut [Self::Subpixel]) -> &mut Self;
 
+    /// Returns a view into a slice, or `None` if its length is not the number of channels.
+    ///
+    /// Unlike [`from_slice`](Pixel::from_slice), this never panics, which makes it suitable for
+    /// slices of untrusted length.
+    fn from_slice_checked(slice: &[Self::Subpixel]) -> Option<&Self> {
+        if slice.len() == Self::CHANNEL_COUNT as usize {
+            Some(Self::from_slice(slice))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable view into a mutable slice, or `None` if its length is not the number of
+    /// channels.
+    fn from_slice_mut_checked(slice: &mut [Self::Subpixel]) -> Option<&mut Self> {
+        if slice.len() == Self::CHANNEL_COUNT as usize {
+            Some(Self::from_slice_mut(slice))
+        } else {
+            None
+        }
+    }
+
     /// Convert this pixel to RGB
     fn to_rgb(&self) -> Rgb<Self::Subpixel>;
 
@@ -344,6 +366,24 @@ pub trait Pixel: Copy + Clone {
         self.apply_with_alpha(f, |x| x);
     }
 
+    /// Returns the value of the alpha channel, or `None` if this pixel has none.
+    fn alpha(&self) -> Option<Self::Subpixel> {
+        let mut alpha = None;
+        self.map_with_alpha(
+            |c| c,
+            |a| {
+                alpha = Some(a);
+                a
+            },
+        );
+        alpha
+    }
+
+    /// Sets the alpha channel to `value`. Pixels without an alpha channel are left unchanged.
+    fn set_alpha(&mut self, value: Self::Subpixel) {
+        self.apply_with_alpha(|c| c, |_| value);
+    }
+
     /// Apply the function ```f``` to each channel of this pixel and
     /// ```other``` pairwise.
     fn map2<F>(&self, other: &Self, f: F) -> Self